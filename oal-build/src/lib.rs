@@ -0,0 +1,87 @@
+//! Compiles Oxlip programs into Rust types from build scripts.
+//!
+//! ```no_run
+//! // In the main function of build.rs:
+//! oal_build::compile("api/main.oal").unwrap();
+//! ```
+//!
+//! The generated module is then included in the crate,
+//! which depends on `serde` with the `derive` feature and on `serde_json`:
+//!
+//! ```ignore
+//! mod api {
+//!     include!(concat!(env!("OUT_DIR"), "/main.rs"));
+//! }
+//! ```
+
+#[cfg(test)]
+mod tests;
+
+use anyhow::anyhow;
+use oal_client::cli::Processor;
+use oal_model::locator::Locator;
+use std::path::{Path, PathBuf};
+use url::Url;
+
+/// The compilation of a program into a Rust module.
+pub struct Builder {
+    main: PathBuf,
+    output: Option<PathBuf>,
+}
+
+impl Builder {
+    pub fn new<P: AsRef<Path>>(main: P) -> Self {
+        Builder {
+            main: main.as_ref().to_owned(),
+            output: None,
+        }
+    }
+
+    /// Sets the path of the generated module, `$OUT_DIR/<program>.rs` by default.
+    pub fn with_output<P: AsRef<Path>>(mut self, output: P) -> Self {
+        self.output = Some(output.as_ref().to_owned());
+        self
+    }
+
+    fn output(&self) -> anyhow::Result<PathBuf> {
+        if let Some(path) = &self.output {
+            return Ok(path.clone());
+        }
+        let dir = std::env::var_os("OUT_DIR")
+            .ok_or_else(|| anyhow!("OUT_DIR is not set, the output must be explicit"))?;
+        let stem = self
+            .main
+            .file_stem()
+            .ok_or_else(|| anyhow!("invalid program path {}", self.main.display()))?;
+        Ok(Path::new(&dir).join(stem).with_extension("rs"))
+    }
+
+    /// Compiles the program and writes the generated module, returning its path.
+    ///
+    /// Diagnostics are reported on the standard error,
+    /// and Cargo is told to run the build script again whenever a module of the program changes.
+    pub fn compile(self) -> anyhow::Result<PathBuf> {
+        let path = self.main.canonicalize()?;
+        let url = Url::from_file_path(&path)
+            .map_err(|_| anyhow!("invalid program path {}", path.display()))?;
+        let main = Locator::from(url);
+
+        let proc = Processor::new();
+        let mods = proc.load(&main)?;
+        for loc in mods.locators() {
+            if let Ok(p) = loc.url().to_file_path() {
+                println!("cargo:rerun-if-changed={}", p.display());
+            }
+        }
+        let spec = proc.eval(&mods)?;
+
+        let output = self.output()?;
+        std::fs::write(&output, oal_rust::Builder::new(spec).build())?;
+        Ok(output)
+    }
+}
+
+/// Compiles a program into `$OUT_DIR/<program>.rs`.
+pub fn compile<P: AsRef<Path>>(main: P) -> anyhow::Result<PathBuf> {
+    Builder::new(main).compile()
+}
@@ -0,0 +1,22 @@
+use crate::Builder;
+
+#[test]
+fn build_compile() -> anyhow::Result<()> {
+    let dir = std::env::temp_dir().join(format!("oal-build-{}", std::process::id()));
+    std::fs::create_dir_all(&dir)?;
+    let main = dir.join("api.oal");
+    std::fs::write(
+        &main,
+        "let @item = { 'id! int };\nres /items on get -> @item;\n",
+    )?;
+
+    let output = Builder::new(&main)
+        .with_output(dir.join("api.rs"))
+        .compile()?;
+
+    let code = std::fs::read_to_string(output)?;
+    assert!(code.contains("pub struct Item {\n    pub id: i64,\n}\n"));
+
+    std::fs::remove_dir_all(dir)?;
+    Ok(())
+}
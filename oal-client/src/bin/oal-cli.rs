@@ -1,35 +1,436 @@
-use log::{debug, error, info};
+use log::{debug, error, info, warn};
 use oal_client::cli::Processor;
-use oal_client::{config, DefaultFileSystem, FileSystem};
+use oal_client::config::{self, Command, Format, Target};
+use oal_client::{DefaultFileSystem, FileSystem};
+use oal_compiler::diff::diff;
+use oal_compiler::lint;
+use oal_compiler::module::ModuleSet;
+use oal_compiler::spec::Spec;
+use oal_model::locator::Locator;
+use oal_model::span::Span;
+use oal_openapi::Naming;
 use std::process::ExitCode;
+use std::time::{Duration, SystemTime};
 
-fn run(config: config::Config) -> anyhow::Result<()> {
-    let main = config.main()?;
-    let target = config.target()?;
-    let base = config.base()?;
+/// The interval at which the mock server checks for program changes.
+const MOCK_POLL_INTERVAL: Duration = Duration::from_millis(500);
 
+fn run(config: config::Config) -> anyhow::Result<()> {
     let proc = Processor::new();
-    let mods = proc.load(&main)?;
+    let lint = config.lint();
+
+    if let Some(Command::Import {
+        source,
+        program,
+        base,
+        strict,
+    }) = config.command()
+    {
+        let source = config.locator(source)?;
+        let program = match program.as_deref() {
+            None | Some("-") => Target::Stdout,
+            Some(p) => Target::File(config.locator(p)?),
+        };
+        let base = base.as_deref().map(|b| config.locator(b)).transpose()?;
+        return run_import(&proc, &source, &program, base.as_ref(), *strict);
+    }
+
+    if let Some(Command::Diff { old, new, json }) = config.command() {
+        let builds = config.builds()?;
+        let [build] = builds.as_slice() else {
+            return Err(anyhow::Error::msg(
+                "comparing versions requires a single target, select one by name",
+            ));
+        };
+        let old = build.locator(old)?;
+        let new = match new {
+            Some(p) => build.locator(p)?,
+            None => build.main()?,
+        };
+        return run_diff(&proc, build, &old, &new, *json);
+    }
+
+    if let Some(Command::Validate(args)) = config.command() {
+        let builds = config.builds()?;
+        let [build] = builds.as_slice() else {
+            return Err(anyhow::Error::msg(
+                "validating a payload requires a single target, select one by name",
+            ));
+        };
+        return run_validate(&proc, build, args);
+    }
+
+    if let Some(Command::Mock { address }) = config.command() {
+        let builds = config.builds()?;
+        let [build] = builds.as_slice() else {
+            return Err(anyhow::Error::msg(
+                "serving a mock requires a single target, select one by name",
+            ));
+        };
+        return run_mock(&proc, build, &lint, address);
+    }
+
+    for build in config.builds()? {
+        if let Some(name) = build.name() {
+            info!("Building target {name}");
+        }
+        run_build(&proc, &build, &lint)?;
+    }
 
+    Ok(())
+}
+
+/// Compiles a program and selects the audiences and version of the build.
+fn spec(
+    proc: &Processor,
+    build: &config::Build,
+    main: &Locator,
+    lint: Option<&lint::Config>,
+) -> anyhow::Result<Spec> {
+    let mods = proc.load(main)?;
+    select(proc, build, &mods, lint)
+}
+
+/// Evaluates loaded modules and selects the audiences and version of the build.
+fn select(
+    proc: &Processor,
+    build: &config::Build,
+    mods: &ModuleSet,
+    lint: Option<&lint::Config>,
+) -> anyhow::Result<Spec> {
     debug!("Generating API definition");
-    let spec = proc.eval(&mods)?;
-    let mut builder = oal_openapi::Builder::new(spec);
+    let mut spec = proc.eval(mods)?;
 
-    if let Some(ref loc) = base {
-        let file = DefaultFileSystem.open_file(loc)?;
-        let base = serde_yaml::from_reader(file)?;
-        builder = builder.with_base(base);
+    if let Some(lint) = lint {
+        debug!("Checking lint rules");
+        proc.lint(mods, &spec, lint)?;
+    }
+
+    if let Some(selector) = build.audience() {
+        debug!("Selecting audiences");
+        proc.select(mods, &mut spec, &selector)?;
+    }
+
+    if let Some(version) = build.version() {
+        debug!("Selecting version {version}");
+        proc.select_version(mods, &mut spec, &version)?;
+    }
+
+    Ok(spec)
+}
+
+fn run_diff(
+    proc: &Processor,
+    build: &config::Build,
+    old: &Locator,
+    new: &Locator,
+    json: bool,
+) -> anyhow::Result<()> {
+    let old_spec = spec(proc, build, old, None)?;
+    let new_spec = spec(proc, build, new, None)?;
+
+    debug!("Comparing {old} with {new}");
+    let changes = diff(&old_spec, &new_spec);
+    let breaking = changes.iter().filter(|c| c.is_breaking()).count();
+
+    if json {
+        let report = serde_json::json!({ "breaking": breaking > 0, "changes": changes });
+        print!("{}", Format::Json.serialize(&report)?);
+    } else {
+        for change in changes.iter() {
+            println!("{}: {change}", change.impact);
+        }
+        println!(
+            "{breaking} breaking and {} non-breaking changes",
+            changes.len() - breaking
+        );
+    }
+
+    if breaking > 0 {
+        Err(anyhow::Error::msg("breaking changes found"))
+    } else {
+        Ok(())
+    }
+}
+
+/// The modification times of the files of a program, to detect changes.
+type Watched = Vec<(Locator, Option<SystemTime>)>;
+
+fn watch(locators: impl Iterator<Item = Locator>) -> Watched {
+    locators
+        .map(|loc| {
+            let time = DefaultFileSystem.modified(&loc).ok();
+            (loc, time)
+        })
+        .collect()
+}
+
+/// Compiles a program into a mock, with the files to watch for changes.
+fn compile_mock(
+    proc: &Processor,
+    build: &config::Build,
+    main: &Locator,
+    lint: &lint::Config,
+) -> anyhow::Result<(oal_mock::Mock, Watched)> {
+    let mods = proc.load(main)?;
+    let mut watched = watch(mods.locators().cloned());
+    let spec = select(proc, build, &mods, Some(lint))?;
+    let mock = oal_mock::Mock::new(spec).with_examples(|url| {
+        let loc = build.locator(url).ok()?;
+        // Examples are watched as well, even when missing, to pick them up once created.
+        watched.extend(watch(std::iter::once(loc.clone())));
+        match DefaultFileSystem.read_file(&loc) {
+            Ok(text) => Some(text),
+            Err(err) => {
+                warn!("Cannot read example {loc}: {err}");
+                None
+            }
+        }
+    });
+    Ok((mock, watched))
+}
+
+fn run_mock(
+    proc: &Processor,
+    build: &config::Build,
+    lint: &lint::Config,
+    address: &str,
+) -> anyhow::Result<()> {
+    let main = build.main()?;
+    let (mut mock, mut watched) = compile_mock(proc, build, &main, lint)?;
+
+    let server = oal_mock::Server::bind(address)?;
+    info!("Serving mock on http://{}", server.address());
+
+    loop {
+        if let Some((req, res)) = server.serve(&mock, MOCK_POLL_INTERVAL)? {
+            info!("{} {} {}", req.method, req.path, res.status);
+        }
+
+        let changed = watched
+            .iter()
+            .any(|(loc, time)| DefaultFileSystem.modified(loc).ok() != *time);
+        if changed {
+            info!("Reloading {main}");
+            match compile_mock(proc, build, &main, lint) {
+                Ok((m, w)) => (mock, watched) = (m, w),
+                Err(err) => {
+                    // Keep serving the previous mock until the program is fixed.
+                    error!("{err}");
+                    watched = watch(watched.into_iter().map(|(loc, _)| loc));
+                }
+            }
+        }
+    }
+}
+
+fn run_validate(
+    proc: &Processor,
+    build: &config::Build,
+    args: &config::Validate,
+) -> anyhow::Result<()> {
+    let main = build.main()?;
+    let spec = spec(proc, build, &main, None)?;
+
+    let headers = args
+        .headers
+        .iter()
+        .map(|h| match h.split_once(':') {
+            Some((name, value)) => Ok((name.trim().to_owned(), value.trim().to_owned())),
+            None => Err(anyhow::Error::msg(format!("invalid header '{h}'"))),
+        })
+        .collect::<anyhow::Result<_>>()?;
+
+    let body = match args.body.as_deref() {
+        None => None,
+        Some("-") => Some(std::io::read_to_string(std::io::stdin())?),
+        Some(p) => Some(DefaultFileSystem.read_file(&build.locator(p)?)?),
+    };
+    let body = body.map(|b| serde_json::from_str(&b)).transpose()?;
+
+    let message = oal_validate::Message {
+        headers,
+        media: None,
+        body,
+    };
+
+    let mut contract = oal_validate::Contract::new(&spec);
+    if args.allow_unknown {
+        contract = contract.allow_unknown_fields();
+    }
+
+    let (path, query) = args.path.split_once('?').unwrap_or((&args.path, ""));
+    let op = contract.operation(&args.method, path)?;
+    let violations = match args.status {
+        Some(status) => {
+            debug!("Validating response {status} of {} {path}", args.method);
+            op.validate_response(status, &message)
+        }
+        None => {
+            debug!("Validating request {} {path}", args.method);
+            let query: Vec<_> = url::form_urlencoded::parse(query.as_bytes())
+                .into_owned()
+                .collect();
+            op.validate_request(&query, &message)
+        }
+    };
+
+    if args.json {
+        let report =
+            serde_json::json!({ "valid": violations.is_empty(), "violations": violations });
+        print!("{}", Format::Json.serialize(&report)?);
+    } else {
+        for violation in violations.iter() {
+            println!("{violation}");
+        }
+        println!("{} violations", violations.len());
+    }
+
+    if violations.is_empty() {
+        Ok(())
+    } else {
+        Err(anyhow::Error::msg("validation failed"))
+    }
+}
+
+fn run_import(
+    proc: &Processor,
+    source: &Locator,
+    program: &Target,
+    base: Option<&Locator>,
+    strict: bool,
+) -> anyhow::Result<()> {
+    debug!("Importing OpenAPI definition from {source}");
+    let file = DefaultFileSystem.open_file(source)?;
+    let api = Format::from_locator(source)
+        .unwrap_or(Format::Yaml)
+        .deserialize(file)?;
+    let import = oal_openapi::import(&api)?;
+    for pointer in import.unsupported.iter() {
+        let msg = format!("{pointer} is kept as an annotation but not compiled back");
+        proc.warn(Span::new(source.clone(), 0..0), msg)?;
+    }
+
+    match program {
+        Target::Stdout => print!("{}", import.program),
+        Target::File(loc) => {
+            info!("Writing program to {loc}");
+            DefaultFileSystem.write_file(loc, import.program)?;
+        }
+    }
+
+    if let Some(loc) = base {
+        info!("Writing base OpenAPI definition to {loc}");
+        let format = Format::from_locator(loc).unwrap_or(Format::Yaml);
+        DefaultFileSystem.write_file(loc, format.serialize(&import.base)?)?;
     }
 
-    let api = builder.into_openapi();
-    let api_yaml = serde_yaml::to_string(&api)?;
+    if strict && !import.unsupported.is_empty() {
+        Err(anyhow::Error::msg(
+            "some constructs are kept as annotations but not compiled back",
+        ))
+    } else {
+        Ok(())
+    }
+}
+
+fn run_build(proc: &Processor, build: &config::Build, lint: &lint::Config) -> anyhow::Result<()> {
+    let main = build.main()?;
+    let target = build.target()?;
+    let format = build.format()?;
+    let naming = build.naming()?;
 
-    info!("Writing OpenAPI definition to {target}");
-    DefaultFileSystem.write_file(&target, api_yaml)?;
+    let spec = spec(proc, build, &main, Some(lint))?;
+
+    // The additional pages linked from the output, if any.
+    let mut pages = Vec::new();
+
+    let (output, kind) = match format {
+        Format::Typescript => {
+            let builder = oal_typescript::Builder::new(spec).with_naming(naming);
+            (
+                proc.generate_typescript(builder)?,
+                "TypeScript declarations",
+            )
+        }
+        Format::Rust => (oal_rust::Builder::new(spec).build(), "Rust types"),
+        Format::Protobuf => {
+            let builder = oal_protobuf::Builder::new(spec).with_naming(naming);
+            (
+                proc.generate_protobuf(builder)?,
+                "Protocol Buffers definitions",
+            )
+        }
+        Format::Markdown | Format::Html => {
+            let docs_format = match format {
+                Format::Html => oal_docs::Format::Html,
+                _ => oal_docs::Format::Markdown,
+            };
+            let docs = oal_docs::Builder::new(spec)
+                .with_naming(naming)
+                .with_format(docs_format)
+                .build();
+            pages = docs.pages;
+            (docs.index, "API reference")
+        }
+        _ => {
+            let api = openapi(proc, build, spec, naming)?;
+            (format.serialize(&api)?, "OpenAPI definition")
+        }
+    };
+
+    match target {
+        Target::Stdout if !pages.is_empty() => {
+            return Err(anyhow::Error::msg(format!(
+                "{kind} has several pages and cannot be written to the standard output"
+            )));
+        }
+        Target::Stdout => print!("{output}"),
+        Target::File(loc) => {
+            info!("Writing {kind} to {loc}");
+            DefaultFileSystem.write_file(&loc, output)?;
+            for page in pages {
+                let loc = loc.join(&page.path)?;
+                debug!("Writing page {loc}");
+                DefaultFileSystem.write_file(&loc, page.content)?;
+            }
+        }
+    }
 
     Ok(())
 }
 
+/// Generates the OpenAPI description of a build.
+fn openapi(
+    proc: &Processor,
+    build: &config::Build,
+    spec: Spec,
+    naming: Naming,
+) -> anyhow::Result<openapiv3::OpenAPI> {
+    let base = build.base()?;
+    let merge = build.merge()?;
+    let inlining = build.inlining()?;
+
+    let mut builder = oal_openapi::Builder::new(spec)
+        .with_naming(naming)
+        .with_merge(merge)
+        .with_inlining(inlining);
+
+    if let Some(media_type) = build.media_type() {
+        builder = builder.with_media_type(media_type);
+    }
+
+    if let Some(ref loc) = base {
+        let file = DefaultFileSystem.open_file(loc)?;
+        let base = Format::from_locator(loc)
+            .unwrap_or(Format::Yaml)
+            .deserialize(file)?;
+        builder = builder.with_base(base);
+    }
+
+    proc.generate(builder)
+}
+
 fn main() -> ExitCode {
     let config = match config::Config::new(None) {
         Ok(config) => config,
@@ -1,12 +1,18 @@
 use crate::{DefaultFileSystem, FileSystem};
 use anyhow::anyhow;
-use ariadne::{ColorGenerator, Label, Report, ReportKind, Source};
-use log::debug;
+use ariadne::{sources, ColorGenerator, Label, Report, ReportKind};
+use log::{debug, error, warn};
+use oal_compiler::audience::{self, Selector};
+use oal_compiler::errors::Error;
+use oal_compiler::lint;
 use oal_compiler::module::{Loader, ModuleSet};
 use oal_compiler::spec::Spec;
 use oal_compiler::tree::Tree;
+use oal_compiler::versioning::{self, Version};
 use oal_model::locator::Locator;
 use oal_model::span::Span;
+use oal_openapi::Conflict;
+use std::collections::HashMap;
 
 #[derive(Default)]
 /// The CLI compilation processor.
@@ -21,17 +27,67 @@ impl Processor {
 impl Processor {
     /// Reports an error.
     pub fn report<M: ToString>(&self, span: Span, msg: M) -> anyhow::Result<()> {
+        self.report_with(span, msg, &[])
+    }
+
+    /// Reports an error along with secondary labelled spans.
+    pub fn report_with<M: ToString>(
+        &self,
+        span: Span,
+        msg: M,
+        labels: &[(Span, String)],
+    ) -> anyhow::Result<()> {
+        self.report_as(ReportKind::Error, span, msg, labels)
+    }
+
+    /// Reports a warning.
+    pub fn warn<M: ToString>(&self, span: Span, msg: M) -> anyhow::Result<()> {
+        self.report_as(ReportKind::Warning, span, msg, &[])
+    }
+
+    /// Reports a diagnostic of the given kind along with secondary labelled spans.
+    fn report_as<M: ToString>(
+        &self,
+        kind: ReportKind,
+        span: Span,
+        msg: M,
+        labels: &[(Span, String)],
+    ) -> anyhow::Result<()> {
         let mut colors = ColorGenerator::new();
-        let color = colors.next();
         let loc = span.locator().clone();
-        let input = DefaultFileSystem.read_file(&loc)?;
-        let mut builder =
-            Report::build(ReportKind::Error, loc.clone(), span.start()).with_message(msg);
+        let mut inputs = HashMap::new();
+        for l in std::iter::once(&loc).chain(labels.iter().map(|(s, _)| s.locator())) {
+            if !inputs.contains_key(l) {
+                inputs.insert(l.clone(), DefaultFileSystem.read_file(l)?);
+            }
+        }
+        let mut builder = Report::build(kind, loc.clone(), span.start()).with_message(msg);
         if !span.range().is_empty() {
-            let s = CharSpan::from(&input, span);
-            builder.add_label(Label::new(s).with_color(color))
+            let s = CharSpan::from(&inputs[&loc], span);
+            builder.add_label(Label::new(s).with_color(colors.next()))
+        }
+        for (span, text) in labels {
+            let s = CharSpan::from(&inputs[span.locator()], span.clone());
+            builder.add_label(Label::new(s).with_message(text).with_color(colors.next()))
+        }
+        builder.finish().eprint(sources(inputs))?;
+        Ok(())
+    }
+
+    /// Reports a collection of compiler errors and warnings.
+    fn report_all(&self, loc: &Locator, errs: &[Error]) -> anyhow::Result<()> {
+        for err in errs.iter() {
+            let span = match err.span() {
+                Some(s) => s.clone(),
+                None => Span::new(loc.clone(), 0..0),
+            };
+            let kind = if err.is_warning() {
+                ReportKind::Warning
+            } else {
+                ReportKind::Error
+            };
+            self.report_as(kind, span, err, err.labels())?;
         }
-        builder.finish().eprint((loc, Source::from(input)))?;
         Ok(())
     }
 
@@ -43,18 +99,131 @@ impl Processor {
     /// Evaluates a program.
     pub fn eval(&self, mods: &ModuleSet) -> anyhow::Result<Spec> {
         match oal_compiler::eval::eval(mods) {
-            Err(err) => {
-                let span = match err.span() {
-                    Some(s) => s.clone(),
-                    None => Span::new(mods.base().clone(), 0..0),
-                };
-                self.report(span, &err)?;
+            Err(errs) => {
+                self.report_all(mods.base(), &errs)?;
                 Err(anyhow!("evaluation failed"))
             }
             Ok(spec) => Ok(spec),
         }
     }
 
+    /// Checks a program against the lint rules.
+    pub fn lint(&self, mods: &ModuleSet, spec: &Spec, config: &lint::Config) -> anyhow::Result<()> {
+        let diags = lint::lint(mods, spec, config);
+        self.report_all(mods.base(), &diags)?;
+        if diags.iter().all(|d| d.is_warning()) {
+            Ok(())
+        } else {
+            Err(anyhow!("linting failed"))
+        }
+    }
+
+    /// Prunes the specification of the elements not visible to the selected audiences.
+    pub fn select(
+        &self,
+        mods: &ModuleSet,
+        spec: &mut Spec,
+        selector: &Selector,
+    ) -> anyhow::Result<()> {
+        match audience::select(spec, selector) {
+            Err(errs) => {
+                self.report_all(mods.base(), &errs)?;
+                Err(anyhow!("audience selection failed"))
+            }
+            Ok(()) => Ok(()),
+        }
+    }
+
+    /// Restricts the specification to the elements available in the given version.
+    pub fn select_version(
+        &self,
+        mods: &ModuleSet,
+        spec: &mut Spec,
+        version: &Version,
+    ) -> anyhow::Result<()> {
+        match versioning::select(spec, version) {
+            Err(errs) => {
+                self.report_all(mods.base(), &errs)?;
+                Err(anyhow!("version selection failed"))
+            }
+            Ok(()) => Ok(()),
+        }
+    }
+
+    /// Reports conflicts with the base document.
+    fn report_conflicts(&self, kind: ReportKind, conflicts: &[Conflict]) -> anyhow::Result<()> {
+        for conflict in conflicts {
+            match conflict.span() {
+                Some(span) => self.report_as(kind, span.clone(), conflict, &[])?,
+                None if kind == ReportKind::Warning => warn!("{conflict}"),
+                None => error!("{conflict}"),
+            }
+        }
+        Ok(())
+    }
+
+    /// Generates an OpenAPI definition.
+    pub fn generate(&self, builder: oal_openapi::Builder) -> anyhow::Result<openapiv3::OpenAPI> {
+        match builder.build() {
+            Err(oal_openapi::errors::Error::Conflicts(conflicts)) => {
+                self.report_conflicts(ReportKind::Error, &conflicts)?;
+                Err(anyhow!("merging with the base document failed"))
+            }
+            Err(err) => {
+                if let Some(span) = err.span() {
+                    self.report_with(span.clone(), &err, &err.labels())?;
+                    Err(anyhow!("generation failed"))
+                } else {
+                    Err(err.into())
+                }
+            }
+            Ok((api, conflicts)) => {
+                self.report_conflicts(ReportKind::Warning, &conflicts)?;
+                Ok(api)
+            }
+        }
+    }
+
+    /// Generates TypeScript declarations.
+    pub fn generate_typescript(&self, builder: oal_typescript::Builder) -> anyhow::Result<String> {
+        match builder.build() {
+            Err(err) => {
+                if let Some(span) = err.span() {
+                    self.report_with(span.clone(), &err, &err.labels())?;
+                    Err(anyhow!("generation failed"))
+                } else {
+                    Err(err.into())
+                }
+            }
+            Ok(ts) => Ok(ts),
+        }
+    }
+
+    /// Generates Protocol Buffers definitions.
+    pub fn generate_protobuf(&self, builder: oal_protobuf::Builder) -> anyhow::Result<String> {
+        match builder.build() {
+            Err(err) => {
+                if let Some(span) = err.span() {
+                    self.report_with(span.clone(), &err, &err.labels())?;
+                    Err(anyhow!("generation failed"))
+                } else {
+                    Err(err.into())
+                }
+            }
+            Ok((proto, unrepresentable)) => {
+                for item in unrepresentable.iter() {
+                    match item.span() {
+                        Some(span) => {
+                            self.report_as(ReportKind::Warning, span.clone(), item, &[])?
+                        }
+                        None => warn!("{item}"),
+                    }
+                }
+                Ok(proto)
+            }
+        }
+    }
+
     pub fn loader(&self) -> impl Loader<anyhow::Error> + '_ {
         ProcLoader(self)
     }
@@ -77,15 +246,16 @@ impl<'a> Loader<anyhow::Error> for ProcLoader<'a> {
     /// Parses a source file into a concrete syntax tree.
     fn parse(&mut self, loc: Locator, input: String) -> anyhow::Result<Tree> {
         debug!("Parsing module {loc}");
-        let (tree, mut errs) = oal_syntax::parse(loc.clone(), input);
-        if let Some(err) = errs.pop() {
-            // We don't care about error recovery for the command line interface.
-            let span = match err {
-                oal_syntax::errors::Error::Grammar(ref err) => err.span(),
-                oal_syntax::errors::Error::Lexicon(ref err) => err.span(),
-                _ => Span::new(loc, 0..0),
-            };
-            self.0.report(span, &err)?;
+        let (tree, errs) = oal_syntax::parse(loc.clone(), input);
+        if !errs.is_empty() {
+            for err in errs.iter() {
+                let span = match err {
+                    oal_syntax::errors::Error::Grammar(ref err) => err.span(),
+                    oal_syntax::errors::Error::Lexicon(ref err) => err.span(),
+                    _ => Span::new(loc.clone(), 0..0),
+                };
+                self.0.report(span, err)?;
+            }
             Err(anyhow!("parsing failed"))
         } else {
             tree.ok_or_else(|| anyhow!("parsing failed"))
@@ -95,17 +265,17 @@ impl<'a> Loader<anyhow::Error> for ProcLoader<'a> {
     /// Compiles a program.
     fn compile(&mut self, mods: &ModuleSet, loc: &Locator) -> anyhow::Result<()> {
         debug!("Compiling module {loc}");
-        if let Err(err) = oal_compiler::compile::compile(mods, loc) {
-            let span = match err.span() {
-                Some(s) => s.clone(),
-                None => Span::new(loc.clone(), 0..0),
-            };
-            self.0.report(span, &err)?;
+        if let Err(errs) = oal_compiler::compile::compile(mods, loc) {
+            self.0.report_all(loc, &errs)?;
             Err(anyhow!("compilation failed"))
         } else {
             Ok(())
         }
     }
+    /// Reports a warning raised while loading a module.
+    fn warn(&mut self, loc: &Locator, warning: Error) -> anyhow::Result<()> {
+        self.0.report_all(loc, &[warning])
+    }
 }
 
 struct CharSpan(oal_model::span::CharSpan);
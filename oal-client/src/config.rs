@@ -1,12 +1,20 @@
 use clap::Parser as ClapParser;
+use oal_compiler::audience::Selector;
+use oal_compiler::lint;
+use oal_compiler::versioning::Version;
 use oal_model::locator::Locator;
+use oal_openapi::{Inlining, Merge, Naming};
 use serde::Deserialize;
+use std::collections::{BTreeMap, HashMap};
 use std::path::{Path, PathBuf};
 use url::Url;
 
-/// Compiles an Oxlip program into an OpenAPI description in YAML.
+/// Compiles an Oxlip program into an OpenAPI description in YAML or JSON.
 #[derive(ClapParser, Debug)]
 struct Args {
+    #[command(subcommand)]
+    command: Option<Command>,
+
     /// The relative URL to the main program
     #[arg(short = 'm', long)]
     main: Option<String>,
@@ -19,6 +27,18 @@ struct Args {
     #[arg(short = 'b', long)]
     base: Option<String>,
 
+    /// The output format, inferred from the target extension by default
+    #[arg(short = 'f', long, value_enum)]
+    format: Option<Format>,
+
+    /// The API version to build, all versions by default
+    #[arg(long)]
+    api_version: Option<String>,
+
+    /// The name of the configured target to build, all targets by default
+    #[arg(short = 'n', long)]
+    name: Option<String>,
+
     /// The path to the configuration file
     #[arg(short = 'c', long = "conf")]
     config: Option<PathBuf>,
@@ -32,16 +52,214 @@ struct Args {
     quiet: bool,
 }
 
+/// A command other than building the configured targets.
+#[derive(clap::Subcommand, Clone, Debug, PartialEq, Eq)]
+pub enum Command {
+    /// Compares two versions of a program and reports breaking changes
+    Diff {
+        /// The relative URL to the previous main program
+        old: String,
+
+        /// The relative URL to the new main program, the configured one by default
+        new: Option<String>,
+
+        /// Print the report in JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Imports an OpenAPI description into an Oxlip program
+    Import {
+        /// The relative URL to the OpenAPI description to import
+        source: String,
+
+        /// The relative URL to the generated program, the standard output by default
+        program: Option<String>,
+
+        /// The relative URL to write the rest of the description to, for use as base document
+        base: Option<String>,
+
+        /// Fail if some constructs are kept as annotations but not compiled back
+        #[arg(long)]
+        strict: bool,
+    },
+    /// Serves a mock of the API, reloaded when the program changes
+    Mock {
+        /// The address to listen on
+        #[arg(short = 'a', long, default_value = "127.0.0.1:8080")]
+        address: String,
+    },
+    /// Validates a request or a response payload against an operation
+    Validate(Validate),
+}
+
+/// The arguments of the `validate` command.
+#[derive(clap::Args, Clone, Debug, PartialEq, Eq)]
+pub struct Validate {
+    /// The HTTP method of the operation
+    pub method: String,
+
+    /// The concrete path of the operation, with the query string if any, e.g. /items/1?q=a
+    pub path: String,
+
+    /// The relative URL to the JSON body, `-` for the standard input, no body by default
+    #[arg(short = 'd', long)]
+    pub body: Option<String>,
+
+    /// The status of the response to validate, a request is validated by default
+    #[arg(short = 's', long)]
+    pub status: Option<u16>,
+
+    /// A header of the payload, e.g. "content-type: application/json"
+    #[arg(short = 'H', long = "header")]
+    pub headers: Vec<String>,
+
+    /// Accept properties not declared by object schemas
+    #[arg(long)]
+    pub allow_unknown: bool,
+
+    /// Print the report in JSON
+    #[arg(long)]
+    pub json: bool,
+}
+
+/// The output format of a target, i.e. the serialization of an OpenAPI description or generated code.
+#[derive(clap::ValueEnum, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum Format {
+    /// YAML
+    Yaml,
+    /// Pretty-printed JSON
+    Json,
+    /// JSON without whitespace
+    JsonCompact,
+    /// TypeScript declarations
+    Typescript,
+    /// Rust types
+    Rust,
+    /// Protocol Buffers definitions
+    Protobuf,
+    /// Markdown API reference
+    Markdown,
+    /// Static HTML API reference
+    Html,
+}
+
+fn not_serialization(format: &Format) -> anyhow::Error {
+    anyhow::Error::msg(format!("{format:?} is not a serialization format"))
+}
+
+impl Format {
+    /// Returns the format implied by the extension of a locator, if any.
+    pub fn from_locator(loc: &Locator) -> Option<Format> {
+        let path = loc.url().path();
+        if path.ends_with(".json") {
+            Some(Format::Json)
+        } else if path.ends_with(".yaml") || path.ends_with(".yml") {
+            Some(Format::Yaml)
+        } else if path.ends_with(".ts") {
+            Some(Format::Typescript)
+        } else if path.ends_with(".rs") {
+            Some(Format::Rust)
+        } else if path.ends_with(".proto") {
+            Some(Format::Protobuf)
+        } else if path.ends_with(".md") {
+            Some(Format::Markdown)
+        } else if path.ends_with(".html") {
+            Some(Format::Html)
+        } else {
+            None
+        }
+    }
+
+    /// Serializes a value in this format.
+    pub fn serialize<T: serde::Serialize>(&self, value: &T) -> anyhow::Result<String> {
+        let text = match self {
+            Format::Yaml => serde_yaml::to_string(value)?,
+            Format::Json => serde_json::to_string_pretty(value)? + "\n",
+            Format::JsonCompact => serde_json::to_string(value)?,
+            Format::Typescript
+            | Format::Rust
+            | Format::Protobuf
+            | Format::Markdown
+            | Format::Html => return Err(not_serialization(self)),
+        };
+        Ok(text)
+    }
+
+    /// Deserializes a value from a reader in this format.
+    pub fn deserialize<T, R>(&self, reader: R) -> anyhow::Result<T>
+    where
+        T: serde::de::DeserializeOwned,
+        R: std::io::Read,
+    {
+        let value = match self {
+            Format::Yaml => serde_yaml::from_reader(reader)?,
+            Format::Json | Format::JsonCompact => serde_json::from_reader(reader)?,
+            Format::Typescript
+            | Format::Rust
+            | Format::Protobuf
+            | Format::Markdown
+            | Format::Html => return Err(not_serialization(self)),
+        };
+        Ok(value)
+    }
+}
+
+/// The destination of the generated OpenAPI description.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Target {
+    /// The standard output.
+    Stdout,
+    /// A file.
+    File(Locator),
+}
+
 #[derive(Deserialize, Default, Debug)]
 struct File {
+    #[serde(default)]
     api: Api,
+    #[serde(default)]
+    targets: BTreeMap<String, Api>,
+    #[serde(default)]
+    lint: HashMap<lint::Rule, lint::Level>,
 }
 
-#[derive(Deserialize, Default, Debug)]
+#[derive(Deserialize, Default, Clone, Debug)]
 struct Api {
     main: Option<String>,
     target: Option<String>,
     base: Option<String>,
+    #[serde(rename = "operation-id")]
+    operation_id: Option<String>,
+    merge: Option<String>,
+    format: Option<Format>,
+    #[serde(rename = "media-type")]
+    media_type: Option<String>,
+    inlining: Option<String>,
+    #[serde(rename = "inline-size")]
+    inline_size: Option<usize>,
+    audience: Option<Vec<String>>,
+    #[serde(rename = "api-version")]
+    api_version: Option<String>,
+}
+
+impl Api {
+    /// Fills the missing options with the given defaults.
+    fn or(self, defaults: &Api) -> Api {
+        Api {
+            main: self.main.or_else(|| defaults.main.clone()),
+            target: self.target.or_else(|| defaults.target.clone()),
+            base: self.base.or_else(|| defaults.base.clone()),
+            operation_id: self.operation_id.or_else(|| defaults.operation_id.clone()),
+            merge: self.merge.or_else(|| defaults.merge.clone()),
+            format: self.format.or(defaults.format),
+            media_type: self.media_type.or_else(|| defaults.media_type.clone()),
+            inlining: self.inlining.or_else(|| defaults.inlining.clone()),
+            inline_size: self.inline_size.or(defaults.inline_size),
+            audience: self.audience.or_else(|| defaults.audience.clone()),
+            api_version: self.api_version.or_else(|| defaults.api_version.clone()),
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -79,32 +297,329 @@ impl Config {
         Ok(Config { args, file, root })
     }
 
+    /// Returns the builds to run, i.e. every configured target or the one selected by name.
+    ///
+    /// Without named targets, the single build is configured by the `api` section.
+    pub fn builds(&self) -> anyhow::Result<Vec<Build<'_>>> {
+        let name = self.args.name.as_deref();
+        let builds: Vec<_> = if self.file.targets.is_empty() {
+            name.is_none()
+                .then(|| self.build(None, self.file.api.clone()))
+                .into_iter()
+                .collect()
+        } else {
+            self.file
+                .targets
+                .iter()
+                .filter(|(n, _)| name.is_none_or(|s| s == n.as_str()))
+                .map(|(n, api)| self.build(Some(n), api.clone().or(&self.file.api)))
+                .collect()
+        };
+        match name {
+            Some(name) if builds.is_empty() => {
+                Err(anyhow::Error::msg(format!("unknown target '{name}'")))
+            }
+            _ if builds.len() > 1 && self.args.target.is_some() => Err(anyhow::Error::msg(
+                "the target can only be overridden when building a single target",
+            )),
+            _ => Ok(builds),
+        }
+    }
+
+    fn build<'a>(&'a self, name: Option<&'a str>, api: Api) -> Build<'a> {
+        Build {
+            name,
+            args: &self.args,
+            api,
+            root: &self.root,
+        }
+    }
+
+    /// Returns the lint configuration, i.e. the default rule levels overridden by the configuration file.
+    pub fn lint(&self) -> lint::Config {
+        lint::Config::from(self.file.lint.iter().map(|(r, l)| (*r, *l)))
+    }
+
+    /// Resolves a URL relative to the configuration root.
+    pub fn locator(&self, path: &str) -> anyhow::Result<Locator> {
+        Ok(self.root.join(path)?)
+    }
+
+    /// Returns the command to run instead of building the targets, if any.
+    pub fn command(&self) -> Option<&Command> {
+        self.args.command.as_ref()
+    }
+
+    pub fn is_quiet(&self) -> bool {
+        self.args.quiet
+    }
+
+    pub fn verbosity(&self) -> usize {
+        self.args.verbose as usize
+    }
+}
+
+/// The configuration of a single build target.
+///
+/// Command-line options take precedence over the options of the target,
+/// which take precedence over those of the `api` section.
+#[derive(Debug)]
+pub struct Build<'a> {
+    name: Option<&'a str>,
+    args: &'a Args,
+    api: Api,
+    root: &'a Locator,
+}
+
+impl<'a> Build<'a> {
+    /// Returns the name of the target, if any.
+    pub fn name(&self) -> Option<&str> {
+        self.name
+    }
+
+    /// Resolves a URL relative to the configuration root.
+    pub fn locator(&self, path: &str) -> anyhow::Result<Locator> {
+        Ok(self.root.join(path)?)
+    }
+
     pub fn main(&self) -> anyhow::Result<Locator> {
-        match self.args.main.as_ref().or(self.file.api.main.as_ref()) {
-            Some(p) => Ok(self.root.join(p)?),
+        match self.args.main.as_ref().or(self.api.main.as_ref()) {
+            Some(p) => self.locator(p),
             None => Err(anyhow::Error::msg("main module not specified")),
         }
     }
 
-    pub fn target(&self) -> anyhow::Result<Locator> {
-        match self.args.target.as_ref().or(self.file.api.target.as_ref()) {
-            Some(p) => Ok(self.root.join(p)?),
+    /// Returns the target, which is the standard output if specified as `-`.
+    pub fn target(&self) -> anyhow::Result<Target> {
+        match self.args.target.as_ref().or(self.api.target.as_ref()) {
+            Some(p) if p == "-" => Ok(Target::Stdout),
+            Some(p) => Ok(Target::File(self.root.join(p)?)),
             None => Err(anyhow::Error::msg("target not specified")),
         }
     }
 
+    /// Returns the output format, either explicit or inferred from the target extension.
+    pub fn format(&self) -> anyhow::Result<Format> {
+        if let Some(f) = self.args.format.or(self.api.format) {
+            return Ok(f);
+        }
+        match self.target()? {
+            Target::File(loc) => Ok(Format::from_locator(&loc).unwrap_or(Format::Yaml)),
+            Target::Stdout => Ok(Format::Yaml),
+        }
+    }
+
     pub fn base(&self) -> anyhow::Result<Option<Locator>> {
-        match self.args.base.as_ref().or(self.file.api.base.as_ref()) {
+        match self.args.base.as_ref().or(self.api.base.as_ref()) {
             Some(p) => Ok(Some(self.root.join(p)?)),
             None => Ok(None),
         }
     }
 
-    pub fn is_quiet(&self) -> bool {
-        self.args.quiet
+    /// Returns the naming strategy for operation identifiers.
+    pub fn naming(&self) -> anyhow::Result<Naming> {
+        match self.api.operation_id.as_deref() {
+            Some(s) => s.parse().map_err(anyhow::Error::msg),
+            None => Ok(Naming::default()),
+        }
     }
 
-    pub fn verbosity(&self) -> usize {
-        self.args.verbose as usize
+    /// Returns the strategy for combining the generated definition with the base document.
+    pub fn merge(&self) -> anyhow::Result<Merge> {
+        match self.api.merge.as_deref() {
+            Some(s) => s.parse().map_err(anyhow::Error::msg),
+            None => Ok(Merge::default()),
+        }
+    }
+
+    /// Returns the default media type, if any.
+    pub fn media_type(&self) -> Option<&str> {
+        self.api.media_type.as_deref()
+    }
+
+    /// Returns the policy for inlining references to schemas.
+    pub fn inlining(&self) -> anyhow::Result<Inlining> {
+        const DEFAULT_INLINE_SIZE: usize = 4;
+        match self.api.inlining.as_deref() {
+            None | Some("always-ref") => Ok(Inlining::AlwaysRef),
+            Some("never-inline") => Ok(Inlining::NeverInline),
+            Some("inline-below-size") => {
+                let size = self.api.inline_size.unwrap_or(DEFAULT_INLINE_SIZE);
+                Ok(Inlining::BelowSize(size))
+            }
+            Some(s) => Err(anyhow::Error::msg(format!(
+                "unknown inlining policy '{s}', expected always-ref, inline-below-size or never-inline"
+            ))),
+        }
+    }
+
+    /// Returns the selection of audiences, if the target is restricted to some.
+    pub fn audience(&self) -> Option<Selector> {
+        self.api.audience.as_ref().map(Selector::from)
+    }
+
+    /// Returns the API version to build, if the target is restricted to one.
+    pub fn version(&self) -> Option<Version> {
+        let version = self
+            .args
+            .api_version
+            .as_ref()
+            .or(self.api.api_version.as_ref());
+        version.map(|v| Version::from(v.as_str()))
+    }
+}
+
+#[test]
+fn format_from_locator() {
+    let format = |s| Format::from_locator(&Locator::try_from(s).unwrap());
+    assert_eq!(format("file:///api.json"), Some(Format::Json));
+    assert_eq!(format("file:///api.yaml"), Some(Format::Yaml));
+    assert_eq!(format("file:///api.yml"), Some(Format::Yaml));
+    assert_eq!(format("file:///api.ts"), Some(Format::Typescript));
+    assert_eq!(format("file:///api.rs"), Some(Format::Rust));
+    assert_eq!(format("file:///api.proto"), Some(Format::Protobuf));
+    assert_eq!(format("file:///docs/index.md"), Some(Format::Markdown));
+    assert_eq!(format("file:///docs/index.html"), Some(Format::Html));
+    assert_eq!(format("file:///api"), None);
+}
+
+#[test]
+fn format_serialize() -> anyhow::Result<()> {
+    let value = serde_json::json!({ "a": [1] });
+    assert_eq!(Format::Yaml.serialize(&value)?, "a:\n- 1\n");
+    assert_eq!(
+        Format::Json.serialize(&value)?,
+        "{\n  \"a\": [\n    1\n  ]\n}\n"
+    );
+    assert_eq!(Format::JsonCompact.serialize(&value)?, r#"{"a":[1]}"#);
+    let parsed: serde_json::Value = Format::Json.deserialize(r#"{"a":[1]}"#.as_bytes())?;
+    assert_eq!(parsed, value);
+    Ok(())
+}
+
+#[test]
+fn config_builds() -> anyhow::Result<()> {
+    let file = r#"
+        [api]
+        base = "base.yaml"
+        operation-id = "camelCase"
+
+        [targets.public]
+        main = "public.oal"
+        target = "public.json"
+        audience = ["public"]
+        api-version = "2"
+
+        [targets.partner]
+        main = "partner.oal"
+        target = "partner.yaml"
+        base = "partner-base.yaml"
+    "#;
+    let config = |args: &[&str]| -> anyhow::Result<Config> {
+        Ok(Config {
+            args: Args::try_parse_from(args)?,
+            file: toml::from_str(file)?,
+            root: Locator::try_from("file:///api/")?,
+        })
+    };
+
+    let all = config(&["oal-cli"])?;
+    let builds = all.builds()?;
+    let names: Vec<_> = builds.iter().map(|b| b.name()).collect();
+    assert_eq!(names, [Some("partner"), Some("public")]);
+    let base = builds[0].base()?.unwrap();
+    assert_eq!(base.url().as_str(), "file:///api/partner-base.yaml");
+    assert_eq!(
+        builds[1].base()?.unwrap().url().as_str(),
+        "file:///api/base.yaml"
+    );
+    assert_eq!(builds[1].format()?, Format::Json);
+    assert_eq!(builds[1].naming()?, Naming::Camel);
+    assert_eq!(builds[0].audience(), None);
+    assert_eq!(builds[1].audience(), Some(Selector::from(["public"])));
+    assert_eq!(builds[0].version(), None);
+    assert_eq!(builds[1].version(), Some(Version::from("2")));
+
+    let one = config(&["oal-cli", "--name", "public", "--target", "-"])?;
+    let builds = one.builds()?;
+    assert_eq!(builds.len(), 1);
+    assert_eq!(builds[0].target()?, Target::Stdout);
+
+    assert!(config(&["oal-cli", "--target", "-"])?.builds().is_err());
+    assert!(config(&["oal-cli", "--name", "other"])?.builds().is_err());
+
+    Ok(())
+}
+
+#[test]
+fn config_command() -> anyhow::Result<()> {
+    let args = Args::try_parse_from(["oal-cli", "-c", "oal.toml", "diff", "v1.oal", "--json"])?;
+    let config = Config {
+        args,
+        file: File::default(),
+        root: Locator::try_from("file:///api/")?,
+    };
+    assert_eq!(
+        config.command(),
+        Some(&Command::Diff {
+            old: "v1.oal".to_owned(),
+            new: None,
+            json: true
+        })
+    );
+    let args = Args::try_parse_from(["oal-cli", "import", "api.yaml", "api.oal"])?;
+    assert_eq!(
+        args.command,
+        Some(Command::Import {
+            source: "api.yaml".to_owned(),
+            program: Some("api.oal".to_owned()),
+            base: None,
+            strict: false
+        })
+    );
+    let args = Args::try_parse_from(["oal-cli", "mock", "--address", "0.0.0.0:9000"])?;
+    assert_eq!(
+        args.command,
+        Some(Command::Mock {
+            address: "0.0.0.0:9000".to_owned()
+        })
+    );
+    let args = Args::try_parse_from([
+        "oal-cli",
+        "validate",
+        "put",
+        "/items/1",
+        "--body",
+        "item.json",
+        "--status",
+        "200",
+        "-H",
+        "etag: abc",
+    ])?;
+    assert_eq!(
+        args.command,
+        Some(Command::Validate(Validate {
+            method: "put".to_owned(),
+            path: "/items/1".to_owned(),
+            body: Some("item.json".to_owned()),
+            status: Some(200),
+            headers: vec!["etag: abc".to_owned()],
+            allow_unknown: false,
+            json: false,
+        }))
+    );
+    let args = Args::try_parse_from(["oal-cli", "mock"])?;
+    assert_eq!(
+        args.command,
+        Some(Command::Mock {
+            address: "127.0.0.1:8080".to_owned()
+        })
+    );
+    assert!(Config {
+        args: Args::try_parse_from(["oal-cli"])?,
+        ..config
     }
+    .command()
+    .is_none());
+    Ok(())
 }
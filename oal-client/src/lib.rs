@@ -5,6 +5,7 @@ pub mod lsp;
 use oal_model::locator::Locator;
 use std::io;
 use std::path::PathBuf;
+use std::time::SystemTime;
 
 #[derive(thiserror::Error, Debug)]
 pub enum Error {
@@ -19,6 +20,7 @@ pub trait FileSystem {
     fn open_file(&self, loc: &Locator) -> Result<Box<dyn io::Read>, Error>;
     fn read_file(&self, loc: &Locator) -> Result<String, Error>;
     fn write_file(&self, loc: &Locator, buf: String) -> Result<(), Error>;
+    fn modified(&self, loc: &Locator) -> Result<SystemTime, Error>;
 }
 
 pub struct DefaultFileSystem;
@@ -55,7 +57,16 @@ impl FileSystem for DefaultFileSystem {
 
     fn write_file(&self, loc: &Locator, buf: String) -> Result<(), Error> {
         let path = locator_path(loc)?;
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
         std::fs::write(path, buf)?;
         Ok(())
     }
+
+    fn modified(&self, loc: &Locator) -> Result<SystemTime, Error> {
+        let path = locator_path(loc)?;
+        let time = std::fs::metadata(path)?.modified()?;
+        Ok(time)
+    }
 }
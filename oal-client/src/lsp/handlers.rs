@@ -6,11 +6,11 @@ use lsp_types::{
     TextDocumentPositionParams, TextEdit, WorkspaceEdit,
 };
 use oal_compiler::definition::{Definition, External};
+use oal_compiler::module::ModuleSet;
 use oal_compiler::tree::{Core, NRef, Tree};
 use oal_model::grammar::AbstractSyntaxNode;
 use oal_model::locator::Locator;
 use oal_syntax::parser::{Declaration, Gram, Identifier, Qualifier, Variable};
-use std::collections::hash_map::Entry;
 use std::collections::HashMap;
 use url::Url;
 
@@ -60,11 +60,11 @@ fn find_qualifier(tree: &Tree, index: usize) -> Option<Qualifier<Core>> {
 /// Finds all references to the given definition.
 fn find_references(
     workspace: &mut Workspace,
-    folder: &Folder,
+    mods: &ModuleSet,
     definition: &Definition,
 ) -> anyhow::Result<Vec<Location>> {
     let mut refs = Vec::new();
-    for module in folder.modules().unwrap().modules() {
+    for module in mods.modules() {
         for var in module.root().descendants().filter_map(Variable::cast) {
             if definition == var.node().syntax().core_ref().definition().unwrap() {
                 let location = node_location(workspace, var.identifier().node())?;
@@ -75,14 +75,23 @@ fn find_references(
     Ok(refs)
 }
 
-/// Finds the folders containing the given locator.
-fn find_folders<'a>(
+/// Finds the compiled modules of all folder targets containing the given locator.
+fn find_modules<'a>(
     folders: &'a HashMap<Url, Folder>,
     loc: &'a Locator,
-) -> impl Iterator<Item = &'a Folder> + 'a {
+) -> impl Iterator<Item = &'a ModuleSet> + 'a {
     folders
-        .iter()
-        .filter_map(|(_, f)| if f.contains(loc) { Some(f) } else { None })
+        .values()
+        .flat_map(|f| f.modules())
+        .filter(|m| m.get(loc).is_some())
+}
+
+/// Appends a text edit to the changes of a document, unless already present.
+fn push_edit(changes: &mut HashMap<Url, Vec<TextEdit>>, uri: Url, edit: TextEdit) {
+    let edits = changes.entry(uri).or_default();
+    if !edits.contains(&edit) {
+        edits.push(edit);
+    }
 }
 
 /// Implements the go-to-definition capability.
@@ -95,11 +104,11 @@ pub fn go_to_definition(
     let text = state.workspace.read_file(&loc)?;
     let index = position_to_utf8(&text, pos);
 
-    for folder in find_folders(&state.folders, &loc) {
-        let tree = folder.module(&loc).unwrap();
+    for mods in find_modules(&state.folders, &loc) {
+        let tree = mods.get(&loc).unwrap();
         if let Some(v) = syntax_at::<Variable<_>>(tree, index) {
             if let Some(Definition::External(ext)) = v.node().syntax().core_ref().definition() {
-                let definition = ext.node(folder.modules().unwrap());
+                let definition = ext.node(mods);
                 let location = node_location(&mut state.workspace, definition)?;
                 return Ok(Some(GotoDefinitionResponse::Scalar(location)));
             }
@@ -121,11 +130,14 @@ pub fn references(
 
     let mut refs = Vec::new();
 
-    for folder in find_folders(&state.folders, &loc) {
-        let tree = folder.module(&loc).unwrap();
+    for mods in find_modules(&state.folders, &loc) {
+        let tree = mods.get(&loc).unwrap();
         if let Some(definition) = find_definition(tree, index) {
-            let r = &mut find_references(&mut state.workspace, folder, &definition)?;
-            refs.append(r);
+            for r in find_references(&mut state.workspace, mods, &definition)? {
+                if !refs.contains(&r) {
+                    refs.push(r);
+                }
+            }
         }
     }
 
@@ -142,8 +154,8 @@ pub fn prepare_rename(
     let text = state.workspace.read_file(&loc)?;
     let index = position_to_utf8(&text, pos);
 
-    for folder in find_folders(&state.folders, &loc) {
-        let tree = folder.module(&loc).unwrap();
+    for mods in find_modules(&state.folders, &loc) {
+        let tree = mods.get(&loc).unwrap();
         if let Some(ident) = syntax_at::<Identifier<_>>(tree, index) {
             let parent = ident.node().ancestors().nth(1).unwrap();
             let node = if let Some(decl) = Declaration::cast(parent) {
@@ -177,12 +189,12 @@ pub fn rename(
 
     let mut changes = HashMap::new();
 
-    for folder in find_folders(&state.folders, &loc) {
-        let tree = folder.module(&loc).unwrap();
+    for mods in find_modules(&state.folders, &loc) {
+        let tree = mods.get(&loc).unwrap();
         if let Some(definition) = find_definition(tree, index) {
             rename_variable(
                 &mut state.workspace,
-                folder,
+                mods,
                 &new_name,
                 definition,
                 &mut changes,
@@ -190,7 +202,7 @@ pub fn rename(
         } else if let Some(qualifier) = find_qualifier(tree, index) {
             rename_qualifier(
                 &mut state.workspace,
-                folder,
+                mods,
                 &new_name,
                 qualifier,
                 &mut changes,
@@ -209,7 +221,7 @@ pub fn rename(
 /// Renames an import qualifier and all references.
 fn rename_qualifier<'a>(
     workspace: &mut Workspace,
-    folder: &'a Folder,
+    mods: &'a ModuleSet,
     new_name: &str,
     qualifier: Qualifier<'a, Core>,
     changes: &mut HashMap<Url, Vec<TextEdit>>,
@@ -222,24 +234,17 @@ fn rename_qualifier<'a>(
     // Rename the qualifier definition
     let def_location = node_location(workspace, definition.node())?;
     let def_edit = TextEdit::new(def_location.range, new_name.into());
-    changes.insert(def_location.uri, vec![def_edit]);
+    push_edit(changes, def_location.uri, def_edit);
 
     // Rename all references to the qualifier
     let loc = definition.node().span().unwrap().locator().clone();
-    let module = folder.module(&loc).unwrap();
+    let module = mods.get(&loc).unwrap();
     for var in module.root().descendants().filter_map(Variable::cast) {
         match (var.qualifier(), qualifier.identifier()) {
             (Some(reference), Some(definition)) if reference == definition => {
                 let location = node_location(workspace, reference.node())?;
                 let edit = TextEdit::new(location.range, new_name.into());
-                match changes.entry(location.uri) {
-                    Entry::Occupied(mut e) => {
-                        e.get_mut().push(edit);
-                    }
-                    Entry::Vacant(e) => {
-                        e.insert(vec![edit]);
-                    }
-                }
+                push_edit(changes, location.uri, edit);
             }
             _ => {}
         }
@@ -251,7 +256,7 @@ fn rename_qualifier<'a>(
 /// Renames a variable definition and all references.
 fn rename_variable(
     workspace: &mut Workspace,
-    folder: &Folder,
+    mods: &ModuleSet,
     new_name: &str,
     definition: Definition,
     changes: &mut HashMap<Url, Vec<TextEdit>>,
@@ -262,22 +267,15 @@ fn rename_variable(
     };
 
     // Rename the variable declaration.
-    let decl = Declaration::cast(external.node(folder.modules().unwrap())).unwrap();
+    let decl = Declaration::cast(external.node(mods)).unwrap();
     let decl_location = node_location(workspace, decl.identifier().node())?;
     let decl_edit = TextEdit::new(decl_location.range, new_name.into());
-    changes.insert(decl_location.uri, vec![decl_edit]);
+    push_edit(changes, decl_location.uri, decl_edit);
 
     // Rename all references to the variable.
-    for r in find_references(workspace, folder, &definition)? {
+    for r in find_references(workspace, mods, &definition)? {
         let edit = TextEdit::new(r.range, new_name.into());
-        match changes.entry(r.uri) {
-            Entry::Occupied(mut e) => {
-                e.get_mut().push(edit);
-            }
-            Entry::Vacant(e) => {
-                e.insert(vec![edit]);
-            }
-        }
+        push_edit(changes, r.uri, edit);
     }
 
     Ok(())
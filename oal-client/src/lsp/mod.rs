@@ -11,11 +11,15 @@ use crate::{DefaultFileSystem, FileSystem};
 use anyhow::anyhow;
 use log::debug;
 use lsp_types::{
-    Diagnostic, DidChangeTextDocumentParams, DidCloseTextDocumentParams, DidOpenTextDocumentParams,
+    Diagnostic, DiagnosticRelatedInformation, DiagnosticSeverity, DidChangeTextDocumentParams,
+    DidCloseTextDocumentParams, DidOpenTextDocumentParams, Location,
 };
+use oal_compiler::audience::{self, Selector};
+use oal_compiler::lint;
 use oal_compiler::module::{Loader, ModuleSet};
 use oal_compiler::spec::Spec;
 use oal_compiler::tree::Tree;
+use oal_compiler::versioning::{self, Version};
 use oal_model::{locator::Locator, span::Span};
 use std::collections::hash_map::Entry;
 use std::collections::HashMap;
@@ -25,8 +29,7 @@ use unicode::{position_to_utf8, utf8_range_to_position};
 #[derive(Debug)]
 pub struct Folder {
     config: Config,
-    mods: Option<ModuleSet>,
-    spec: Option<Spec>,
+    mods: Vec<ModuleSet>,
 }
 
 impl Folder {
@@ -43,36 +46,47 @@ impl Folder {
             let config = Config::new(Some(path.as_path()))?;
             Ok(Folder {
                 config,
-                mods: None,
-                spec: None,
+                mods: Vec::new(),
             })
         }
     }
 
-    /// Returns the compiled modules for the folder, if any.
-    pub fn modules(&self) -> Option<&ModuleSet> {
-        self.mods.as_ref()
+    /// Returns the compiled modules for each target of the folder.
+    pub fn modules(&self) -> impl Iterator<Item = &ModuleSet> {
+        self.mods.iter()
     }
 
     /// Returns the module identified by the given locator.
     pub fn module(&self, loc: &Locator) -> Option<&Tree> {
-        self.mods.as_ref().and_then(|m| m.get(loc))
+        self.mods.iter().find_map(|m| m.get(loc))
     }
 
     /// Checks whether the given locator belongs to the folder.
     pub fn contains(&self, loc: &Locator) -> bool {
-        self.mods.as_ref().and_then(|m| m.get(loc)).is_some()
+        self.module(loc).is_some()
     }
 
-    /// Evaluates a workspace folder.
+    /// Evaluates every target of a workspace folder.
     pub fn eval(&mut self, ws: &mut Workspace) {
-        self.mods = None;
-        self.spec = None;
-        if let Ok(main) = self.config.main() {
+        self.mods.clear();
+        let Ok(builds) = self.config.builds() else {
+            return;
+        };
+        let lint = self.config.lint();
+        for build in builds.iter() {
+            let Ok(main) = build.main() else { continue };
             debug!("evaluating {}", main);
             if let Ok(mods) = ws.load(&main) {
-                self.spec = ws.eval(&mods).ok();
-                self.mods = Some(mods);
+                if let Ok(mut spec) = ws.eval(&mods) {
+                    ws.lint(&mods, &spec, &lint);
+                    if let Some(selector) = build.audience() {
+                        ws.select(&mods, &mut spec, &selector);
+                    }
+                    if let Some(version) = build.version() {
+                        ws.select_version(&mods, &mut spec, &version);
+                    }
+                }
+                self.mods.push(mods);
             }
         }
     }
@@ -84,7 +98,16 @@ pub type Diagnostics = HashMap<Locator, Vec<Diagnostic>>;
 #[derive(Default)]
 pub struct Workspace {
     docs: HashMap<Locator, String>,
-    errors: Option<Vec<(Span, String)>>,
+    errors: Option<Vec<LoggedError>>,
+}
+
+/// An error logged for diagnostics, with secondary labelled spans.
+#[derive(Debug)]
+struct LoggedError {
+    span: Span,
+    msg: String,
+    labels: Vec<(Span, String)>,
+    severity: DiagnosticSeverity,
 }
 
 impl Workspace {
@@ -123,8 +146,8 @@ impl Workspace {
     pub fn load(&mut self, loc: &Locator) -> anyhow::Result<ModuleSet> {
         let loader = &mut WorkspaceLoader(self);
         let mods = oal_compiler::module::load(loader, loc).map_err(|err| {
-            if let Ok(err) = err.downcast::<oal_compiler::errors::Error>() {
-                self.log_compiler_error(loc, &err)
+            if let Some(err) = err.downcast_ref::<oal_compiler::errors::Error>() {
+                self.log_compiler_error(loc, err)
             }
             anyhow!("loading failed")
         })?;
@@ -134,23 +157,56 @@ impl Workspace {
     /// Evaluates a program.
     pub fn eval(&mut self, mods: &ModuleSet) -> anyhow::Result<Spec> {
         match oal_compiler::eval::eval(mods) {
-            Err(err) => {
-                let loc = match err.span() {
-                    Some(s) => s.locator().clone(),
-                    None => mods.base().clone(),
-                };
-                self.log_compiler_error(&loc, &err);
+            Err(errs) => {
+                self.log_compiler_errors(mods.base(), errs);
                 Err(anyhow!("evaluation failed"))
             }
             Ok(spec) => Ok(spec),
         }
     }
 
+    /// Checks a program against the lint rules.
+    pub fn lint(&mut self, mods: &ModuleSet, spec: &Spec, config: &lint::Config) {
+        for diag in lint::lint(mods, spec, config) {
+            self.log_compiler_error(mods.base(), &diag)
+        }
+    }
+
+    /// Checks that the elements visible to the selected audiences do not depend on pruned ones.
+    pub fn select(&mut self, mods: &ModuleSet, spec: &mut Spec, selector: &Selector) {
+        if let Err(errs) = audience::select(spec, selector) {
+            self.log_compiler_errors(mods.base(), errs)
+        }
+    }
+
+    /// Checks that the elements available in the given version do not depend on pruned ones.
+    pub fn select_version(&mut self, mods: &ModuleSet, spec: &mut Spec, version: &Version) {
+        if let Err(errs) = versioning::select(spec, version) {
+            self.log_compiler_errors(mods.base(), errs)
+        }
+    }
+
     /// Logs an error.
-    fn log_error(&mut self, span: Span, err: String) {
+    fn log_error(&mut self, span: Span, msg: String, labels: Vec<(Span, String)>) {
+        self.log(span, msg, labels, DiagnosticSeverity::ERROR)
+    }
+
+    /// Logs a diagnostic of the given severity.
+    fn log(
+        &mut self,
+        span: Span,
+        msg: String,
+        labels: Vec<(Span, String)>,
+        severity: DiagnosticSeverity,
+    ) {
         self.errors
             .get_or_insert_with(Default::default)
-            .push((span, err));
+            .push(LoggedError {
+                span,
+                msg,
+                labels,
+                severity,
+            });
     }
 
     /// Logs a collection of syntax errors.
@@ -161,7 +217,7 @@ impl Workspace {
                 oal_syntax::errors::Error::Lexicon(ref err) => err.span(),
                 _ => Span::new(loc.clone(), 0..0),
             };
-            self.log_error(span, err.to_string())
+            self.log_error(span, err.to_string(), Vec::new())
         }
     }
 
@@ -171,16 +227,39 @@ impl Workspace {
             .span()
             .cloned()
             .unwrap_or_else(|| Span::new(loc.clone(), 0..0));
-        self.log_error(span, err.to_string())
+        let severity = if err.is_warning() {
+            DiagnosticSeverity::WARNING
+        } else {
+            DiagnosticSeverity::ERROR
+        };
+        self.log(span, err.to_string(), err.labels().to_vec(), severity)
     }
 
-    /// Creates an LSP diagnostic from the given span and error.
-    fn diagnostic<E: ToString>(&mut self, span: &Span, err: E) -> anyhow::Result<Diagnostic> {
-        let text = self.read_file(span.locator())?;
-        let range = utf8_range_to_position(&text, span.range());
+    /// Logs a collection of compiler errors.
+    fn log_compiler_errors(&mut self, loc: &Locator, errs: oal_compiler::errors::Errors) {
+        for err in errs.iter() {
+            self.log_compiler_error(loc, err)
+        }
+    }
+
+    /// Creates an LSP diagnostic from the given logged error.
+    fn diagnostic(&mut self, err: &LoggedError) -> anyhow::Result<Diagnostic> {
+        let text = self.read_file(err.span.locator())?;
+        let range = utf8_range_to_position(&text, err.span.range());
+        let mut related = Vec::new();
+        for (span, msg) in err.labels.iter() {
+            let text = self.read_file(span.locator())?;
+            let range = utf8_range_to_position(&text, span.range());
+            related.push(DiagnosticRelatedInformation {
+                location: Location::new(span.locator().url().clone(), range),
+                message: msg.clone(),
+            });
+        }
         Ok(Diagnostic {
-            message: err.to_string(),
+            message: err.msg.clone(),
+            severity: Some(err.severity),
             range,
+            related_information: (!related.is_empty()).then_some(related),
             ..Default::default()
         })
     }
@@ -195,12 +274,15 @@ impl Workspace {
             .map(|loc| (loc.clone(), Default::default()))
             .collect::<Diagnostics>();
         let errs = self.errors.take().unwrap_or_default();
-        for (span, msg) in errs {
-            let diag = self.diagnostic(&span, msg)?;
-            let loc = span.locator().clone();
+        for err in errs {
+            let diag = self.diagnostic(&err)?;
+            let loc = err.span.locator().clone();
             match diags.entry(loc) {
+                // Modules shared by several targets report the same errors.
                 Entry::Occupied(mut e) => {
-                    e.get_mut().push(diag);
+                    if !e.get().contains(&diag) {
+                        e.get_mut().push(diag);
+                    }
                 }
                 Entry::Vacant(e) => {
                     e.insert(vec![diag]);
@@ -245,15 +327,16 @@ impl<'a> Loader<anyhow::Error> for WorkspaceLoader<'a> {
 
     /// Compiles a program.
     fn compile(&mut self, mods: &ModuleSet, loc: &Locator) -> anyhow::Result<()> {
-        if let Err(err) = oal_compiler::compile::compile(mods, loc) {
-            let loc = match err.span() {
-                Some(s) => s.locator().clone(),
-                None => loc.clone(),
-            };
-            self.0.log_compiler_error(&loc, &err);
+        if let Err(errs) = oal_compiler::compile::compile(mods, loc) {
+            self.0.log_compiler_errors(loc, errs);
             Err(anyhow!("compilation failed"))
         } else {
             Ok(())
         }
     }
+    /// Logs a warning raised while loading a module.
+    fn warn(&mut self, loc: &Locator, warning: oal_compiler::errors::Error) -> anyhow::Result<()> {
+        self.0.log_compiler_error(loc, &warning);
+        Ok(())
+    }
 }
@@ -49,6 +49,13 @@ fn test_deep_extend() {
 }
 
 impl Annotation {
+    /// Creates a set with a single description.
+    pub fn from_description(desc: String) -> Self {
+        let mut props = Mapping::new();
+        props.insert(Value::String("description".to_owned()), Value::String(desc));
+        Annotation { props }
+    }
+
     /// Extends the set by consuming annotations from the other set.
     pub fn extend(&mut self, other: Self) {
         deep_extend_mapping(&mut self.props, other.props);
@@ -64,6 +71,15 @@ impl Annotation {
         self.get_str(s).map(ToOwned::to_owned)
     }
 
+    /// Returns a string or number as a string, e.g. a version.
+    pub fn get_scalar(&self, s: &str) -> Option<String> {
+        match self.props.get(Value::String(s.to_owned()))? {
+            Value::String(v) => Some(v.clone()),
+            Value::Number(n) => Some(n.to_string()),
+            _ => None,
+        }
+    }
+
     pub fn get_bool(&self, s: &str) -> Option<bool> {
         self.props
             .get(&Value::String(s.to_owned()))
@@ -125,3 +141,244 @@ impl TryFrom<&str> for Annotation {
         Ok(Annotation { props })
     }
 }
+
+/// The syntax context an annotation applies to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Context {
+    Schema,
+    Primitive,
+    Property,
+    Content,
+    Transfer,
+    Relation,
+}
+
+impl std::fmt::Display for Context {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Context::Schema => write!(f, "schema"),
+            Context::Primitive => write!(f, "primitive"),
+            Context::Property => write!(f, "property"),
+            Context::Content => write!(f, "content"),
+            Context::Transfer => write!(f, "transfer"),
+            Context::Relation => write!(f, "relation"),
+        }
+    }
+}
+
+/// The expected YAML type of an annotation value.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ValueType {
+    String,
+    Boolean,
+    Number,
+    Integer,
+    Scalar,
+    Sequence,
+    Mapping,
+}
+
+impl ValueType {
+    fn accepts(&self, v: &Value) -> bool {
+        match self {
+            ValueType::String => v.is_string(),
+            ValueType::Boolean => v.is_bool(),
+            ValueType::Number => v.is_number(),
+            ValueType::Integer => v.is_i64() || v.is_u64(),
+            ValueType::Scalar => v.is_string() || v.is_bool() || v.is_number(),
+            ValueType::Sequence => v.is_sequence(),
+            ValueType::Mapping => v.is_mapping(),
+        }
+    }
+}
+
+impl std::fmt::Display for ValueType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ValueType::String => write!(f, "a string"),
+            ValueType::Boolean => write!(f, "a boolean"),
+            ValueType::Number => write!(f, "a number"),
+            ValueType::Integer => write!(f, "an integer"),
+            ValueType::Scalar => write!(f, "a scalar"),
+            ValueType::Sequence => write!(f, "a sequence"),
+            ValueType::Mapping => write!(f, "a mapping"),
+        }
+    }
+}
+
+/// A known annotation key with the expected type of its value.
+type KeyDef = (&'static str, ValueType);
+
+const SCHEMA_KEYS: &[KeyDef] = &[
+    ("description", ValueType::String),
+    ("title", ValueType::String),
+    ("required", ValueType::Boolean),
+    ("examples", ValueType::Mapping),
+    ("audience", ValueType::Sequence),
+    ("since", ValueType::Scalar),
+    ("until", ValueType::Scalar),
+    ("deprecatedSince", ValueType::Scalar),
+];
+
+const PRIMITIVE_KEYS: &[KeyDef] = &[
+    ("minimum", ValueType::Number),
+    ("maximum", ValueType::Number),
+    ("multipleOf", ValueType::Number),
+    ("example", ValueType::Scalar),
+    ("pattern", ValueType::String),
+    ("enum", ValueType::Sequence),
+    ("format", ValueType::String),
+    ("minLength", ValueType::Integer),
+    ("maxLength", ValueType::Integer),
+];
+
+const PROPERTY_KEYS: &[KeyDef] = &[
+    ("description", ValueType::String),
+    ("required", ValueType::Boolean),
+    ("audience", ValueType::Sequence),
+    ("since", ValueType::Scalar),
+    ("until", ValueType::Scalar),
+    ("deprecatedSince", ValueType::Scalar),
+];
+
+const CONTENT_KEYS: &[KeyDef] = &[
+    ("description", ValueType::String),
+    ("examples", ValueType::Mapping),
+];
+
+const TRANSFER_KEYS: &[KeyDef] = &[
+    ("description", ValueType::String),
+    ("summary", ValueType::String),
+    ("tags", ValueType::Sequence),
+    ("operationId", ValueType::String),
+    ("audience", ValueType::Sequence),
+    ("since", ValueType::Scalar),
+    ("until", ValueType::Scalar),
+    ("deprecatedSince", ValueType::Scalar),
+];
+
+const RELATION_KEYS: &[KeyDef] = &[("example", ValueType::String)];
+
+impl Context {
+    const ALL: [Context; 6] = [
+        Context::Schema,
+        Context::Primitive,
+        Context::Property,
+        Context::Content,
+        Context::Transfer,
+        Context::Relation,
+    ];
+
+    /// Returns the registry of annotation keys known in this context.
+    pub fn keys(&self) -> impl Iterator<Item = &'static KeyDef> {
+        let (own, schema): (&[KeyDef], &[KeyDef]) = match self {
+            Context::Schema => (SCHEMA_KEYS, &[]),
+            Context::Primitive => (PRIMITIVE_KEYS, SCHEMA_KEYS),
+            Context::Property => (PROPERTY_KEYS, &[]),
+            Context::Content => (CONTENT_KEYS, &[]),
+            Context::Transfer => (TRANSFER_KEYS, &[]),
+            Context::Relation => (RELATION_KEYS, SCHEMA_KEYS),
+        };
+        own.iter().chain(schema.iter())
+    }
+}
+
+/// An annotation that does not conform to the registry.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Invalid {
+    UnknownKey {
+        key: String,
+        suggestion: Option<&'static str>,
+    },
+    UnexpectedValue {
+        key: String,
+        expected: ValueType,
+    },
+}
+
+impl Invalid {
+    /// Returns the offending annotation key.
+    pub fn key(&self) -> &str {
+        match self {
+            Invalid::UnknownKey { key, .. } => key,
+            Invalid::UnexpectedValue { key, .. } => key,
+        }
+    }
+}
+
+/// Computes the Levenshtein distance between two strings.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut curr = vec![i + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let cost = usize::from(ca != *cb);
+            curr.push((prev[j] + cost).min(prev[j + 1] + 1).min(curr[j] + 1));
+        }
+        prev = curr;
+    }
+    prev[b.len()]
+}
+
+/// Returns the closest known key, if close enough to be a plausible misspelling.
+fn suggest<'a, I>(key: &str, known: I) -> Option<&'static str>
+where
+    I: Iterator<Item = &'a KeyDef>,
+{
+    let lower = key.to_lowercase();
+    known
+        .map(|(k, _)| (*k, edit_distance(&lower, &k.to_lowercase())))
+        .filter(|(k, d)| *d <= 2.max(k.len() / 4))
+        .min_by_key(|(_, d)| *d)
+        .map(|(k, _)| k)
+}
+
+#[test]
+fn test_suggest() {
+    let keys = Context::Primitive.keys();
+    assert_eq!(suggest("minLenght", keys), Some("minLength"));
+    let keys = Context::Transfer.keys();
+    assert_eq!(suggest("operationID", keys), Some("operationId"));
+    let keys = Context::Transfer.keys();
+    assert_eq!(suggest("foo", keys), None);
+}
+
+impl Annotation {
+    /// Checks the top-level keys of the set against the registry.
+    ///
+    /// Without a known context, keys are checked against all contexts.
+    /// Extension keys, prefixed with `x-`, are accepted in any context.
+    pub fn validate(&self, ctx: Option<Context>) -> Vec<Invalid> {
+        let contexts = match ctx {
+            Some(c) => vec![c],
+            None => Context::ALL.to_vec(),
+        };
+        let known = || contexts.iter().flat_map(Context::keys);
+        let mut invalid = Vec::new();
+        for (k, v) in self.props.iter() {
+            let key = match k {
+                Value::String(s) => s.clone(),
+                _ => serde_yaml::to_string(k)
+                    .unwrap_or_default()
+                    .trim()
+                    .to_owned(),
+            };
+            if key.starts_with("x-") {
+                continue;
+            }
+            let types: Vec<_> = known()
+                .filter(|(n, _)| *n == key)
+                .map(|(_, t)| *t)
+                .collect();
+            if types.is_empty() {
+                let suggestion = suggest(&key, known());
+                invalid.push(Invalid::UnknownKey { key, suggestion });
+            } else if !types.iter().any(|t| t.accepts(v)) {
+                let expected = types[0];
+                invalid.push(Invalid::UnexpectedValue { key, expected });
+            }
+        }
+        invalid
+    }
+}
@@ -0,0 +1,54 @@
+use crate::errors::Errors;
+use crate::filter::{self, Filter};
+use crate::spec::{Property, Relation, Schema, Spec, Transfer};
+use std::collections::HashSet;
+
+/// A selection of audiences.
+///
+/// Elements restricted to audiences are kept only if one of them is selected,
+/// while unrestricted elements are always kept.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Selector(HashSet<String>);
+
+impl<S: Into<String>, I: IntoIterator<Item = S>> From<I> for Selector {
+    fn from(audiences: I) -> Self {
+        Selector(audiences.into_iter().map(Into::into).collect())
+    }
+}
+
+impl Selector {
+    /// Checks whether an element restricted to the given audiences is selected.
+    pub fn includes(&self, audience: &[String]) -> bool {
+        audience.is_empty() || audience.iter().any(|a| self.0.contains(a))
+    }
+}
+
+impl Filter for Selector {
+    fn schema(&self, schema: &Schema) -> bool {
+        self.includes(&schema.audience)
+    }
+
+    fn property(&self, prop: &Property) -> bool {
+        self.includes(&prop.audience)
+    }
+
+    fn transfer(&self, xfer: &Transfer) -> bool {
+        self.includes(&xfer.audience)
+    }
+
+    fn relation(&self, rel: &Relation) -> bool {
+        self.includes(&rel.audience)
+    }
+
+    fn exclusion(&self) -> String {
+        "not visible to the selected audiences".to_owned()
+    }
+}
+
+/// Prunes the elements of a specification that are not visible to the selected audiences.
+///
+/// References that are no longer reachable from any relation are removed.
+/// Fails if a kept element depends on a pruned element.
+pub fn select(spec: &mut Spec, selector: &Selector) -> std::result::Result<(), Errors> {
+    filter::apply(spec, selector)
+}
@@ -0,0 +1,104 @@
+use crate::audience::{select, Selector};
+use crate::errors::Kind;
+use crate::spec::SchemaExpr;
+use crate::tests::eval;
+use oal_syntax::atom::Method;
+
+#[test]
+fn audience_prune() -> anyhow::Result<()> {
+    let code = r#"
+        # audience: [internal]
+        let @secret = { 'key str };
+        let @item = {
+          'id int
+        , 'owner str `audience: [internal, partner]`
+        , 'debug str `audience: [internal]`
+        };
+        # audience: [internal]
+        let admin = delete -> <>;
+        # audience: [partner]
+        let partners = /partners on get -> @item;
+        res /items on get -> @item, admin;
+        # audience: [internal]
+        let secrets = /secrets on get -> @secret;
+        res secrets;
+        res partners;
+    "#;
+
+    let mut spec = eval(code)?;
+    select(&mut spec, &Selector::from(["partner"])).map_err(|errs| anyhow::anyhow!("{errs:?}"))?;
+
+    let paths: Vec<_> = spec.rels.iter().map(|r| r.uri.pattern()).collect();
+    assert_eq!(paths, ["/items", "/partners"]);
+    assert!(spec.rels[0].xfers[Method::Get].is_some());
+    assert!(spec.rels[0].xfers[Method::Delete].is_none());
+
+    let idents: Vec<_> = spec.refs.keys().map(|i| i.to_string()).collect();
+    assert_eq!(idents, ["@item"]);
+    let crate::spec::Reference::Schema(item) = spec.refs.values().next().unwrap() else {
+        panic!("expected a schema reference")
+    };
+    let SchemaExpr::Object(obj) = &item.expr else {
+        panic!("expected an object")
+    };
+    let names: Vec<_> = obj.props.iter().map(|p| p.name.as_ref()).collect();
+    assert_eq!(names, ["id", "owner"]);
+
+    let mut spec = eval(code)?;
+    select(&mut spec, &Selector::default()).map_err(|errs| anyhow::anyhow!("{errs:?}"))?;
+    assert_eq!(spec.rels.len(), 1);
+    assert_eq!(spec.refs.len(), 1);
+
+    Ok(())
+}
+
+#[test]
+fn audience_excluded_dependency() -> anyhow::Result<()> {
+    let code = r#"
+        # audience: [internal]
+        let @secret = { 'key str };
+        res /items on get -> @secret;
+        res /items/{ 'id str `audience: [internal]` } on get -> {};
+    "#;
+
+    let mut spec = eval(code)?;
+    let errs = select(&mut spec, &Selector::from(["public"])).expect_err("expected errors");
+
+    assert_eq!(errs.len(), 2);
+    assert!(errs
+        .iter()
+        .all(|e| matches!(e.kind, Kind::ExcludedDependency)));
+    assert_eq!(
+        errs[0].to_string(),
+        "excluded dependency: '@secret' is not visible to the selected audiences but 'GET /items' depends on it"
+    );
+    assert_eq!(
+        errs[1].to_string(),
+        "excluded dependency: property 'id' is not visible to the selected audiences but '/items/{id}' depends on it"
+    );
+
+    let mut spec = eval(code)?;
+    assert!(select(&mut spec, &Selector::from(["internal"])).is_ok());
+
+    Ok(())
+}
+
+#[test]
+fn audience_excluded_property_schema() -> anyhow::Result<()> {
+    let code = r#"
+        # audience: [internal]
+        let @secret = { 'key str };
+        res /items on get -> { 'id int, 'secret @secret };
+    "#;
+
+    let mut spec = eval(code)?;
+    let errs = select(&mut spec, &Selector::from(["public"])).expect_err("expected errors");
+
+    assert_eq!(errs.len(), 1);
+    assert_eq!(
+        errs[0].to_string(),
+        "excluded dependency: '@secret' is not visible to the selected audiences but 'GET /items' depends on it"
+    );
+
+    Ok(())
+}
@@ -1,4 +1,4 @@
-use crate::errors::Result;
+use crate::errors::Errors;
 use crate::inference::{constrain, substitute, tag};
 use crate::module::ModuleSet;
 use crate::resolve::resolve;
@@ -6,7 +6,9 @@ use crate::typecheck::{cycles_check, type_check};
 use oal_model::locator::Locator;
 
 /// Runs all compilation phases.
-pub fn compile(mods: &ModuleSet, loc: &Locator) -> Result<()> {
+///
+/// Errors are accumulated within each phase, but a failing phase stops the compilation.
+pub fn compile(mods: &ModuleSet, loc: &Locator) -> std::result::Result<(), Errors> {
     // Resolve variable and function references. Returns the graph of definitions.
     let graph = resolve(mods, loc)?;
     // Tag expressions with concrete and variable types.
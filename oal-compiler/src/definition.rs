@@ -3,8 +3,10 @@ use crate::eval;
 use crate::inference::tag::{Seq, Tag};
 use crate::module::ModuleSet;
 use crate::tree::NRef;
-use oal_model::grammar::NodeIdx;
+use oal_model::grammar::{AbstractSyntaxNode, NodeIdx};
 use oal_model::locator::Locator;
+use oal_model::span::Span;
+use oal_syntax::parser as syn;
 use sha2::{Digest, Sha256};
 use std::fmt::{Debug, Formatter, LowerHex};
 use std::rc::Rc;
@@ -42,6 +44,11 @@ impl External {
         }
     }
 
+    /// Returns the locator of the module holding the definition.
+    pub fn locator(&self) -> &Locator {
+        &self.loc
+    }
+
     pub fn node<'a>(&self, mods: &'a ModuleSet) -> NRef<'a> {
         if let Some(module) = mods.get(&self.loc) {
             NRef::from(module, self.index)
@@ -95,3 +102,26 @@ impl PartialEq for Definition {
 }
 
 impl Eq for Definition {}
+
+/// Returns a label for the definition of the variable at the given node, if any.
+///
+/// Terminals and sub-expressions are looked through to find the variable.
+pub fn definition_label(mods: &ModuleSet, node: NRef) -> Option<(Span, String)> {
+    if let Some(term) = syn::Terminal::cast(node) {
+        return definition_label(mods, term.inner());
+    }
+    if let Some(expr) = syn::SubExpression::cast(node) {
+        return definition_label(mods, expr.inner());
+    }
+    let var = syn::Variable::cast(node)?;
+    let core = var.node().syntax().core_ref();
+    let Some(Definition::External(ext)) = core.definition() else {
+        return None;
+    };
+    let defn = ext.node(mods);
+    let span = match syn::Declaration::cast(defn) {
+        Some(decl) => decl.identifier().node().span(),
+        None => defn.span(),
+    }?;
+    Some((span, format!("'{}' is defined here", var.ident())))
+}
@@ -0,0 +1,576 @@
+use crate::spec::{
+    status_label, Content, MediaType, Object, PrimInteger, PrimNumber, PrimString, Property,
+    Reference, Relation, Schema, SchemaExpr, Spec, Transfer, Uri, UriSegment,
+};
+use oal_syntax::atom;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::fmt::{Display, Formatter};
+
+/// The impact of a change on existing clients.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Impact {
+    Breaking,
+    NonBreaking,
+}
+
+impl Display for Impact {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Impact::Breaking => f.write_str("breaking"),
+            Impact::NonBreaking => f.write_str("non-breaking"),
+        }
+    }
+}
+
+/// A change between two versions of a specification.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct Change {
+    pub impact: Impact,
+    /// The path or operation affected, e.g. `GET /items`.
+    pub subject: String,
+    /// The location of the change within the subject, e.g. `request.body.name`.
+    pub location: String,
+    pub message: String,
+}
+
+impl Change {
+    pub fn is_breaking(&self) -> bool {
+        self.impact == Impact::Breaking
+    }
+}
+
+impl Display for Change {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if self.location.is_empty() {
+            write!(f, "{}: {}", self.subject, self.message)
+        } else {
+            write!(f, "{} {}: {}", self.subject, self.location, self.message)
+        }
+    }
+}
+
+/// Whether a schema is sent by clients or received by them.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+enum Direction {
+    Request,
+    Response,
+}
+
+impl Direction {
+    /// Returns the impact of a constraint being tightened.
+    fn tightened(self) -> Impact {
+        match self {
+            Direction::Request => Impact::Breaking,
+            Direction::Response => Impact::NonBreaking,
+        }
+    }
+
+    /// Returns the impact of a constraint being loosened.
+    fn loosened(self) -> Impact {
+        match self {
+            Direction::Request => Impact::NonBreaking,
+            Direction::Response => Impact::Breaking,
+        }
+    }
+}
+
+/// The kind of constraint on a numeric value.
+#[derive(Clone, Copy)]
+enum Bound {
+    Lower,
+    Upper,
+    /// A constraint that is tightened by any change, e.g. a multiple.
+    Exact,
+}
+
+/// The location of a parameter.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum ParamIn {
+    Query,
+    Header,
+}
+
+impl Display for ParamIn {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParamIn::Query => f.write_str("query"),
+            ParamIn::Header => f.write_str("header"),
+        }
+    }
+}
+
+fn join(location: &str, item: &str) -> String {
+    if location.is_empty() {
+        item.to_owned()
+    } else {
+        format!("{location}.{item}")
+    }
+}
+
+fn describe(expr: &SchemaExpr) -> &'static str {
+    match expr {
+        SchemaExpr::Num(_) => "number",
+        SchemaExpr::Str(_) => "string",
+        SchemaExpr::Bool(_) => "boolean",
+        SchemaExpr::Int(_) => "integer",
+        SchemaExpr::Rel(_) => "relation",
+        SchemaExpr::Uri(_) => "URI",
+        SchemaExpr::Array(_) => "array",
+        SchemaExpr::Object(_) => "object",
+        SchemaExpr::Op(op) => match op.op {
+            atom::VariadicOperator::Join => "join",
+            atom::VariadicOperator::Sum => "sum",
+            atom::VariadicOperator::Any => "any",
+            atom::VariadicOperator::Range => "range",
+        },
+        SchemaExpr::Ref(_) => "reference",
+    }
+}
+
+/// Follows schema references in the given specification.
+fn resolve<'a>(spec: &'a Spec, mut schema: &'a Schema) -> &'a Schema {
+    let mut seen = HashSet::new();
+    while let SchemaExpr::Ref(ident) = &schema.expr {
+        match spec.refs.get(ident) {
+            Some(Reference::Schema(s)) if seen.insert(ident) => schema = s,
+            _ => break,
+        }
+    }
+    schema
+}
+
+/// Describes the types of schemas, e.g. the operands of a composition.
+fn kinds(spec: &Spec, schemas: &[&Schema]) -> String {
+    schemas
+        .iter()
+        .map(|s| describe(&resolve(spec, s).expr))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn variables(uri: &Uri) -> impl Iterator<Item = &Property> {
+    uri.path.iter().filter_map(|s| match s {
+        UriSegment::Variable(p) => Some(p.as_ref()),
+        UriSegment::Literal(_) => None,
+    })
+}
+
+/// Returns the query and header parameters of an operation with their location.
+fn parameters<'a>(uri: &'a Uri, xfer: &'a Transfer) -> Vec<(ParamIn, &'a Property)> {
+    let query = uri
+        .params
+        .iter()
+        .chain(xfer.params.iter())
+        .flat_map(|o| o.props.iter().map(|p| (ParamIn::Query, p)));
+    let header = xfer
+        .domain
+        .headers
+        .iter()
+        .flat_map(|o| o.props.iter().map(|p| (ParamIn::Header, p)));
+    query.chain(header).collect()
+}
+
+fn range_label(status: Option<atom::HttpStatus>, media: &Option<MediaType>) -> String {
+    match media {
+        Some(m) => format!("{}[{m}]", status_label(status)),
+        None => status_label(status),
+    }
+}
+
+struct Differ<'a> {
+    old: &'a Spec,
+    new: &'a Spec,
+    subject: String,
+    /// The pairs of references already compared, to avoid infinite recursion.
+    visited: HashSet<(Direction, atom::Ident, atom::Ident)>,
+    changes: Vec<Change>,
+}
+
+impl<'a> Differ<'a> {
+    fn push<S: Into<String>>(&mut self, impact: Impact, location: &str, message: S) {
+        self.changes.push(Change {
+            impact,
+            subject: self.subject.clone(),
+            location: location.to_owned(),
+            message: message.into(),
+        })
+    }
+
+    fn bound<T: PartialOrd + Display>(
+        &mut self,
+        dir: Direction,
+        loc: &str,
+        name: &str,
+        bound: Bound,
+        old: Option<T>,
+        new: Option<T>,
+    ) {
+        let tightened = match (&old, &new) {
+            (None, None) => return,
+            (None, Some(_)) => true,
+            (Some(_), None) => false,
+            (Some(a), Some(b)) if a == b => return,
+            (Some(a), Some(b)) => match bound {
+                Bound::Lower => b > a,
+                Bound::Upper => b < a,
+                Bound::Exact => true,
+            },
+        };
+        let show = |v: &Option<T>| v.as_ref().map_or("none".to_owned(), T::to_string);
+        let msg = format!("{name} changed from {} to {}", show(&old), show(&new));
+        let impact = if tightened {
+            dir.tightened()
+        } else {
+            dir.loosened()
+        };
+        self.push(impact, loc, msg)
+    }
+
+    fn enumeration(&mut self, dir: Direction, loc: &str, old: &[String], new: &[String]) {
+        // An empty enumeration allows any value.
+        let removed: Vec<_> = old.iter().filter(|v| !new.contains(v)).cloned().collect();
+        let added: Vec<_> = new.iter().filter(|v| !old.contains(v)).cloned().collect();
+        if !new.is_empty() && (old.is_empty() || !removed.is_empty()) {
+            let msg = if old.is_empty() {
+                "enumeration added".to_owned()
+            } else {
+                format!("enumeration values removed: {}", removed.join(", "))
+            };
+            self.push(dir.tightened(), loc, msg);
+        }
+        if !old.is_empty() && (new.is_empty() || !added.is_empty()) {
+            let msg = if new.is_empty() {
+                "enumeration removed".to_owned()
+            } else {
+                format!("enumeration values added: {}", added.join(", "))
+            };
+            self.push(dir.loosened(), loc, msg);
+        }
+    }
+
+    fn text(
+        &mut self,
+        dir: Direction,
+        loc: &str,
+        name: &str,
+        old: &Option<String>,
+        new: &Option<String>,
+    ) {
+        if old != new {
+            let impact = if new.is_none() {
+                dir.loosened()
+            } else {
+                dir.tightened()
+            };
+            self.push(impact, loc, format!("{name} changed"));
+        }
+    }
+
+    fn string(&mut self, dir: Direction, loc: &str, old: &PrimString, new: &PrimString) {
+        self.enumeration(dir, loc, &old.enumeration, &new.enumeration);
+        self.text(dir, loc, "pattern", &old.pattern, &new.pattern);
+        self.text(dir, loc, "format", &old.format, &new.format);
+        self.bound(
+            dir,
+            loc,
+            "minLength",
+            Bound::Lower,
+            old.min_length,
+            new.min_length,
+        );
+        self.bound(
+            dir,
+            loc,
+            "maxLength",
+            Bound::Upper,
+            old.max_length,
+            new.max_length,
+        );
+    }
+
+    fn number(&mut self, dir: Direction, loc: &str, old: &PrimNumber, new: &PrimNumber) {
+        self.bound(dir, loc, "minimum", Bound::Lower, old.minimum, new.minimum);
+        self.bound(dir, loc, "maximum", Bound::Upper, old.maximum, new.maximum);
+        self.bound(
+            dir,
+            loc,
+            "multipleOf",
+            Bound::Exact,
+            old.multiple_of,
+            new.multiple_of,
+        );
+    }
+
+    fn integer(&mut self, dir: Direction, loc: &str, old: &PrimInteger, new: &PrimInteger) {
+        self.bound(dir, loc, "minimum", Bound::Lower, old.minimum, new.minimum);
+        self.bound(dir, loc, "maximum", Bound::Upper, old.maximum, new.maximum);
+        self.bound(
+            dir,
+            loc,
+            "multipleOf",
+            Bound::Exact,
+            old.multiple_of,
+            new.multiple_of,
+        );
+    }
+
+    fn schema(&mut self, dir: Direction, loc: &str, old: &Schema, new: &Schema) {
+        if let (SchemaExpr::Ref(o), SchemaExpr::Ref(n)) = (&old.expr, &new.expr) {
+            if !self.visited.insert((dir, o.clone(), n.clone())) {
+                return;
+            }
+        }
+        let (old, new) = (resolve(self.old, old), resolve(self.new, new));
+        match (&old.expr, &new.expr) {
+            (SchemaExpr::Object(o), SchemaExpr::Object(n)) => self.object(dir, loc, o, n),
+            (SchemaExpr::Array(o), SchemaExpr::Array(n)) => {
+                self.schema(dir, &format!("{loc}[]"), &o.item, &n.item)
+            }
+            (SchemaExpr::Str(o), SchemaExpr::Str(n)) => self.string(dir, loc, o, n),
+            (SchemaExpr::Num(o), SchemaExpr::Num(n)) => self.number(dir, loc, o, n),
+            (SchemaExpr::Int(o), SchemaExpr::Int(n)) => self.integer(dir, loc, o, n),
+            (SchemaExpr::Op(o), SchemaExpr::Op(n)) if o.op == n.op => {
+                self.operands(dir, loc, o.op, &o.schemas, &n.schemas)
+            }
+            (SchemaExpr::Bool(_), SchemaExpr::Bool(_))
+            | (SchemaExpr::Uri(_), SchemaExpr::Uri(_))
+            | (SchemaExpr::Rel(_), SchemaExpr::Rel(_))
+            | (SchemaExpr::Ref(_), SchemaExpr::Ref(_)) => {}
+            (o, n) => {
+                let msg = format!("type changed from {} to {}", describe(o), describe(n));
+                self.push(Impact::Breaking, loc, msg)
+            }
+        }
+    }
+
+    /// Checks whether two operands correspond, either exactly or by their type only.
+    fn similar(&self, exact: bool, old: &Schema, new: &Schema) -> bool {
+        if exact {
+            old == new
+        } else {
+            describe(&resolve(self.old, old).expr) == describe(&resolve(self.new, new).expr)
+        }
+    }
+
+    /// Compares the operands of a composition, pairing them regardless of their order.
+    fn operands(
+        &mut self,
+        dir: Direction,
+        loc: &str,
+        op: atom::VariadicOperator,
+        old: &[Schema],
+        new: &[Schema],
+    ) {
+        let mut removed: Vec<_> = old.iter().collect();
+        let mut added: Vec<_> = new.iter().collect();
+        let mut pairs = Vec::new();
+        // Identical operands, e.g. the same reference, are paired before operands of the same type.
+        for exact in [true, false] {
+            removed.retain(
+                |o| match added.iter().position(|n| self.similar(exact, o, n)) {
+                    Some(i) => {
+                        pairs.push((*o, added.remove(i)));
+                        false
+                    }
+                    None => true,
+                },
+            );
+        }
+        for (o, n) in pairs {
+            self.schema(dir, loc, o, n);
+        }
+        if op == atom::VariadicOperator::Join {
+            // Each operand of an intersection restricts the values.
+            if !added.is_empty() {
+                let msg = format!("operands added: {}", kinds(self.new, &added));
+                self.push(dir.tightened(), loc, msg);
+            }
+            if !removed.is_empty() {
+                let msg = format!("operands removed: {}", kinds(self.old, &removed));
+                self.push(dir.loosened(), loc, msg);
+            }
+        } else {
+            if !removed.is_empty() {
+                let msg = format!("alternatives removed: {}", kinds(self.old, &removed));
+                self.push(dir.tightened(), loc, msg);
+            }
+            if !added.is_empty() {
+                let msg = format!("alternatives added: {}", kinds(self.new, &added));
+                self.push(dir.loosened(), loc, msg);
+            }
+        }
+    }
+
+    fn property(&mut self, dir: Direction, loc: &str, old: &Property, new: &Property) {
+        match (old.is_required(), new.is_required()) {
+            (false, true) => self.push(dir.tightened(), loc, "property became required"),
+            (true, false) => self.push(dir.loosened(), loc, "property became optional"),
+            _ => {}
+        }
+        self.schema(dir, loc, &old.schema, &new.schema);
+    }
+
+    fn object(&mut self, dir: Direction, loc: &str, old: &Object, new: &Object) {
+        for o in old.props.iter() {
+            let prop_loc = join(loc, o.name.as_ref());
+            match new.props.iter().find(|n| n.name == o.name) {
+                Some(n) => self.property(dir, &prop_loc, o, n),
+                None => self.push(dir.loosened(), &prop_loc, "property removed"),
+            }
+        }
+        for n in new.props.iter() {
+            if !old.props.iter().any(|o| o.name == n.name) {
+                let prop_loc = join(loc, n.name.as_ref());
+                if dir == Direction::Request && n.is_required() {
+                    self.push(Impact::Breaking, &prop_loc, "required property added");
+                } else {
+                    self.push(Impact::NonBreaking, &prop_loc, "property added");
+                }
+            }
+        }
+    }
+
+    fn content(&mut self, dir: Direction, loc: &str, old: &Content, new: &Content) {
+        let body = join(loc, "body");
+        match (&old.schema, &new.schema) {
+            (Some(o), Some(n)) => self.schema(dir, &body, o, n),
+            (None, Some(_)) => self.push(dir.tightened(), &body, "body added"),
+            (Some(_), None) => self.push(dir.loosened(), &body, "body removed"),
+            (None, None) => {}
+        }
+        // Request headers are compared as parameters.
+        if dir == Direction::Response {
+            let empty = Object::default();
+            let old_headers = old.headers.as_ref().unwrap_or(&empty);
+            let new_headers = new.headers.as_ref().unwrap_or(&empty);
+            self.object(dir, &join(loc, "headers"), old_headers, new_headers);
+        }
+    }
+
+    /// Compares path variables by position, as their names are not part of the path.
+    fn variables(&mut self, old: &Uri, new: &Uri) {
+        for (o, n) in variables(old).zip(variables(new)) {
+            let loc = format!("parameters.path.{}", o.name.as_ref());
+            if o.name != n.name {
+                let msg = format!("parameter renamed to {}", n.name.as_ref());
+                self.push(Impact::NonBreaking, &loc, msg);
+            }
+            self.schema(Direction::Request, &loc, &o.schema, &n.schema);
+        }
+    }
+
+    fn parameters(&mut self, old: Vec<(ParamIn, &Property)>, new: Vec<(ParamIn, &Property)>) {
+        for (old_in, o) in old.iter() {
+            let loc = format!("parameters.{old_in}.{}", o.name.as_ref());
+            match new.iter().find(|(_, n)| n.name == o.name) {
+                None => self.push(Impact::NonBreaking, &loc, "parameter removed"),
+                Some((new_in, _)) if new_in != old_in => {
+                    let msg = format!("parameter moved from {old_in} to {new_in}");
+                    self.push(Impact::Breaking, &loc, msg)
+                }
+                Some((_, n)) => {
+                    match (o.is_required_param(), n.is_required_param()) {
+                        (false, true) => {
+                            self.push(Impact::Breaking, &loc, "parameter became required")
+                        }
+                        (true, false) => {
+                            self.push(Impact::NonBreaking, &loc, "parameter became optional")
+                        }
+                        _ => {}
+                    }
+                    self.schema(Direction::Request, &loc, &o.schema, &n.schema);
+                }
+            }
+        }
+        for (new_in, n) in new.iter() {
+            if !old.iter().any(|(_, o)| o.name == n.name) {
+                let loc = format!("parameters.{new_in}.{}", n.name.as_ref());
+                if n.is_required_param() {
+                    self.push(Impact::Breaking, &loc, "required parameter added");
+                } else {
+                    self.push(Impact::NonBreaking, &loc, "parameter added");
+                }
+            }
+        }
+    }
+
+    fn transfer(&mut self, old_rel: &Relation, new_rel: &Relation, old: &Transfer, new: &Transfer) {
+        self.variables(&old_rel.uri, &new_rel.uri);
+        self.parameters(parameters(&old_rel.uri, old), parameters(&new_rel.uri, new));
+
+        if old.domain.media != new.domain.media {
+            let show = |m: &Option<MediaType>| m.clone().unwrap_or_else(|| "default".to_owned());
+            let msg = format!(
+                "media type changed from {} to {}",
+                show(&old.domain.media),
+                show(&new.domain.media)
+            );
+            self.push(Impact::Breaking, "request", msg);
+        }
+        self.content(Direction::Request, "request", &old.domain, &new.domain);
+
+        for ((status, media), o) in old.ranges.iter() {
+            let loc = format!("responses.{}", range_label(*status, media));
+            match new.ranges.get(&(*status, media.clone())) {
+                Some(n) => self.content(Direction::Response, &loc, o, n),
+                None => self.push(Impact::Breaking, &loc, "response removed"),
+            }
+        }
+        for (status, media) in new.ranges.keys() {
+            if !old.ranges.contains_key(&(*status, media.clone())) {
+                let loc = format!("responses.{}", range_label(*status, media));
+                self.push(Impact::NonBreaking, &loc, "response added");
+            }
+        }
+    }
+
+    fn relation(&mut self, old: &Relation, new: &Relation) {
+        let path = new.uri.pattern();
+        for (method, o) in old.xfers.iter() {
+            self.subject = format!("{} {path}", format!("{method:?}").to_uppercase());
+            match (o, &new.xfers[method]) {
+                (Some(o), Some(n)) => {
+                    // Changes to shared schemas are reported for each operation.
+                    self.visited.clear();
+                    self.transfer(old, new, o, n)
+                }
+                (Some(_), None) => self.push(Impact::Breaking, "", "operation removed"),
+                (None, Some(_)) => self.push(Impact::NonBreaking, "", "operation added"),
+                (None, None) => {}
+            }
+        }
+    }
+}
+
+/// Compares two versions of a specification and classifies the changes by impact on clients.
+///
+/// Relations are matched by path, regardless of the names of path variables.
+pub fn diff(old: &Spec, new: &Spec) -> Vec<Change> {
+    let key = |r: &Relation| r.uri.pattern_with(|_| "{}".to_owned());
+    let new_rels: HashMap<_, _> = new.rels.iter().map(|r| (key(r), r)).collect();
+    let old_keys: HashSet<_> = old.rels.iter().map(key).collect();
+
+    let mut differ = Differ {
+        old,
+        new,
+        subject: String::new(),
+        visited: HashSet::new(),
+        changes: Vec::new(),
+    };
+    for o in old.rels.iter() {
+        match new_rels.get(&key(o)) {
+            Some(n) => differ.relation(o, n),
+            None => {
+                differ.subject = o.uri.pattern();
+                differ.push(Impact::Breaking, "", "path removed");
+            }
+        }
+    }
+    for n in new.rels.iter().filter(|n| !old_keys.contains(&key(n))) {
+        differ.subject = n.uri.pattern();
+        differ.push(Impact::NonBreaking, "", "path added");
+    }
+    differ.changes
+}
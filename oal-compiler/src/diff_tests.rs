@@ -0,0 +1,161 @@
+use crate::diff::{diff, Change, Impact};
+use crate::tests::eval;
+
+fn changes(old: &str, new: &str) -> anyhow::Result<Vec<Change>> {
+    Ok(diff(&eval(old)?, &eval(new)?))
+}
+
+fn summary(changes: &[Change]) -> Vec<String> {
+    changes
+        .iter()
+        .map(|c| format!("{}: {c}", c.impact))
+        .collect()
+}
+
+#[test]
+fn diff_identical() -> anyhow::Result<()> {
+    let code = r#"
+        let @item = { 'id! int, 'tags [str] };
+        res /items/{ 'id str } ? { 'q str } on get -> @item, put : @item -> <status=204>;
+    "#;
+
+    assert!(changes(code, code)?.is_empty());
+
+    Ok(())
+}
+
+#[test]
+fn diff_operations() -> anyhow::Result<()> {
+    let old = r#"
+        res /items on get -> {}, delete -> <>;
+        res /users on get -> {};
+    "#;
+    let new = r#"
+        res /items on get -> {}, post {} -> <>;
+        res /groups on get -> {};
+    "#;
+
+    assert_eq!(
+        summary(&changes(old, new)?),
+        [
+            "non-breaking: POST /items: operation added",
+            "breaking: DELETE /items: operation removed",
+            "breaking: /users: path removed",
+            "non-breaking: /groups: path added",
+        ]
+    );
+
+    Ok(())
+}
+
+#[test]
+fn diff_request() -> anyhow::Result<()> {
+    let old = r#"
+        let @kind = str `enum: [a, b]`;
+        res /items/{ 'id str } ? { 'q str } on post : {
+          'name! str
+        , 'note str
+        , 'kind @kind
+        , 'size int `minimum: 0`
+        } -> <>;
+    "#;
+    let new = r#"
+        let @kind = str `enum: [a]`;
+        res /items/{ 'key str } on post { 'q! str } : {
+          'name! str
+        , 'kind @kind
+        , 'size int `minimum: 1`
+        , 'color! str
+        } -> <>;
+    "#;
+
+    assert_eq!(
+        summary(&changes(old, new)?),
+        [
+            "non-breaking: POST /items/{key} parameters.path.id: parameter renamed to key",
+            "breaking: POST /items/{key} parameters.query.q: parameter became required",
+            "non-breaking: POST /items/{key} request.body.note: property removed",
+            "breaking: POST /items/{key} request.body.kind: enumeration values removed: b",
+            "breaking: POST /items/{key} request.body.size: minimum changed from 0 to 1",
+            "breaking: POST /items/{key} request.body.color: required property added",
+        ]
+    );
+
+    Ok(())
+}
+
+#[test]
+fn diff_response() -> anyhow::Result<()> {
+    let old = r#"
+        let @item = {
+          'id! int
+        , 'name str
+        , 'kind str `enum: [a, b]`
+        , 'size int `maximum: 10`
+        };
+        res /items on get { 'q str } -> <headers={'etag! str}, @item> :: <status=404, {}>;
+    "#;
+    let new = r#"
+        let @item = {
+          'id int
+        , 'kind str `enum: [a, b, c]`
+        , 'size int `maximum: 5`
+        , 'color str
+        };
+        let h = <headers={ 'q str }>;
+        res /items on get : h -> <@item> :: <status=500, {}>;
+    "#;
+
+    let changes = changes(old, new)?;
+    assert_eq!(
+        summary(&changes),
+        [
+            "breaking: GET /items parameters.query.q: parameter moved from query to header",
+            "breaking: GET /items responses.default.body.id: property became optional",
+            "breaking: GET /items responses.default.body.name: property removed",
+            "breaking: GET /items responses.default.body.kind: enumeration values added: c",
+            "non-breaking: GET /items responses.default.body.size: maximum changed from 10 to 5",
+            "non-breaking: GET /items responses.default.body.color: property added",
+            "breaking: GET /items responses.default.headers.etag: property removed",
+            "breaking: GET /items responses.404: response removed",
+            "non-breaking: GET /items responses.500: response added",
+        ]
+    );
+    assert!(changes.iter().any(|c| c.impact == Impact::Breaking));
+
+    Ok(())
+}
+
+#[test]
+fn diff_alternatives() -> anyhow::Result<()> {
+    let old = r#"
+        let @a = { 'a str };
+        let @b = { 'b int `maximum: 10` };
+        let @req = @a ~ @b;
+        let @res = @a | @b;
+        let @user = @a & { 'x str };
+        res /items on post : @req -> @res;
+        res /users on put : @user -> <>;
+    "#;
+    let new = r#"
+        let @a = { 'a str };
+        let @b = { 'b int `maximum: 10` };
+        let @req = int ~ @b ~ @a;
+        let @res = @b | { 'n num };
+        let @user = { 'x str } & @a & @b;
+        res /items on post : @req -> @res;
+        res /users on put : @user -> <>;
+    "#;
+
+    assert_eq!(
+        summary(&changes(old, new)?),
+        [
+            "non-breaking: POST /items request.body: alternatives added: integer",
+            "breaking: POST /items responses.default.body.a: property removed",
+            "non-breaking: POST /items responses.default.body.n: property added",
+            "breaking: PUT /users request.body: operands added: object",
+        ]
+    );
+
+    Ok(())
+}
@@ -0,0 +1,456 @@
+use crate::errors::{Error, Kind, Result, Severity};
+use crate::render::{is_property_name, sanitize, Annotations};
+use indexmap::IndexMap;
+use oal_model::locator::Locator;
+use oal_syntax::lexer::is_keyword;
+use serde_json::{Map, Value};
+
+/// The file extensions of schema documents.
+const EXTENSIONS: &[&str] = &[".json", ".yaml", ".yml"];
+
+/// The locations of named schema definitions within a document.
+const DEFINITIONS: &[&[&str]] = &[&["components", "schemas"], &["definitions"], &["$defs"]];
+
+/// The keywords that make a document root a schema in its own right.
+const SCHEMA_KEYWORDS: &[&str] = &[
+    "type",
+    "properties",
+    "items",
+    "allOf",
+    "oneOf",
+    "anyOf",
+    "$ref",
+    "enum",
+    "const",
+];
+
+/// The keywords dropped from schemas as they carry no meaning for the generated module.
+const IGNORED_KEYWORDS: &[&str] = &["$schema", "$id", "$comment"];
+
+/// Escapes a name as a JSON pointer segment.
+fn pointer_escape(name: &str) -> String {
+    name.replace('~', "~0").replace('/', "~1")
+}
+
+/// Returns the name of a document without directory and extension.
+fn stem(loc: &Locator) -> &str {
+    let name = loc.url().path().rsplit('/').next().unwrap_or_default();
+    name.rsplit_once('.').map_or(name, |(stem, _)| stem)
+}
+
+/// Returns the reference identifier of the root schema of a document.
+fn root_ident(loc: &Locator) -> String {
+    format!("@{}", sanitize(stem(loc)))
+}
+
+/// Returns the name of the definition a JSON pointer designates, if any.
+fn definition_name(pointer: &str) -> Option<String> {
+    let segments = pointer
+        .strip_prefix('/')?
+        .split('/')
+        .map(|s| s.replace("~1", "/").replace("~0", "~"))
+        .collect::<Vec<_>>();
+    DEFINITIONS
+        .iter()
+        .find_map(|path| match segments.split_last() {
+            Some((name, prefix)) if prefix == *path => Some(name.clone()),
+            _ => None,
+        })
+}
+
+/// A condition on the value of a keyword.
+type Predicate = fn(&Value) -> bool;
+
+/// Removes a keyword from a schema if its value satisfies the predicate.
+fn take(kws: &mut Map<String, Value>, key: &str, pred: Predicate) -> Option<Value> {
+    if kws.get(key).is_some_and(pred) {
+        kws.shift_remove(key)
+    } else {
+        None
+    }
+}
+
+/// Determines the type of a schema, from its keywords if not explicit.
+///
+/// A nullable type is kept as an extension annotation.
+fn schema_type(kws: &mut Map<String, Value>) -> Option<&'static str> {
+    const TYPES: &[&str] = &["string", "number", "integer", "boolean", "object", "array"];
+    let find = |t: &Value| TYPES.iter().copied().find(|n| t.as_str() == Some(*n));
+    let (name, nullable) = match kws.get("type") {
+        Some(Value::Array(ts)) => {
+            let names: Vec<_> = ts.iter().filter(|t| t.as_str() != Some("null")).collect();
+            match names[..] {
+                [t] => (find(t), names.len() < ts.len()),
+                _ => (None, false),
+            }
+        }
+        Some(t) => (find(t), false),
+        None => (None, false),
+    };
+    let name = match name {
+        Some(n) => {
+            kws.shift_remove("type");
+            if nullable {
+                kws.insert("nullable".to_owned(), Value::Bool(true));
+            }
+            Some(n)
+        }
+        None if kws.contains_key("type") => None,
+        None if kws.contains_key("properties") => Some("object"),
+        None if kws.contains_key("items") => Some("array"),
+        None => {
+            let values = kws.get("enum").and_then(Value::as_array);
+            match values.and_then(|v| v.first()).or(kws.get("const")) {
+                Some(v) if v.is_string() => Some("string"),
+                Some(v) if v.is_i64() || v.is_u64() => Some("integer"),
+                Some(v) if v.is_number() => Some("number"),
+                Some(v) if v.is_boolean() => Some("boolean"),
+                _ => None,
+            }
+        }
+    };
+    // A constant is an enumeration of a single value.
+    if name.is_some() && !kws.contains_key("enum") {
+        if let Some(value) = kws.shift_remove("const") {
+            kws.insert("enum".to_owned(), Value::Array(vec![value]));
+        }
+    }
+    name
+}
+
+fn string_type(kws: &mut Map<String, Value>, anns: &mut Annotations) -> String {
+    const CONSTRAINTS: &[&str] = &["enum", "pattern", "minLength", "maxLength"];
+    // Strings formatted as URI references without further constraints are URIs.
+    if kws.get("format").and_then(Value::as_str) == Some("uri-reference")
+        && CONSTRAINTS.iter().all(|k| !kws.contains_key(*k))
+    {
+        kws.shift_remove("format");
+        return "uri".to_owned();
+    }
+    let is_text = |v: &Value| v.as_array().is_some_and(|a| a.iter().all(Value::is_string));
+    let keys: [(&str, Predicate); 6] = [
+        ("format", Value::is_string),
+        ("pattern", Value::is_string),
+        ("enum", is_text),
+        ("minLength", Value::is_u64),
+        ("maxLength", Value::is_u64),
+        ("example", Value::is_string),
+    ];
+    for (key, pred) in keys {
+        if let Some(value) = take(kws, key, pred) {
+            anns.push(key, value);
+        }
+    }
+    "str".to_owned()
+}
+
+fn number_type(kws: &mut Map<String, Value>, anns: &mut Annotations, name: &str) -> String {
+    for key in ["minimum", "maximum", "multipleOf", "example"] {
+        if let Some(value) = take(kws, key, Value::is_number) {
+            anns.push(key, value);
+        }
+    }
+    name.to_owned()
+}
+
+/// Checks whether the document at the given location is a schema document
+/// rather than a program, based on its file extension.
+pub fn is_document(loc: &Locator) -> bool {
+    let path = loc.url().path();
+    EXTENSIONS.iter().any(|ext| path.ends_with(ext))
+}
+
+/// The result of translating a schema document.
+#[derive(Debug)]
+pub struct Translation {
+    /// The source code of the generated module.
+    pub program: String,
+    /// The warnings about the constructs translated as `x-` annotations,
+    /// which are not compiled back.
+    pub warnings: Vec<Error>,
+}
+
+struct Translator<'a> {
+    loc: &'a Locator,
+    /// The qualifiers of the documents referenced from this document, by relative path.
+    imports: IndexMap<String, String>,
+    /// The location in the document of the schema being translated, as JSON pointer segments.
+    scope: Vec<String>,
+    /// The warnings about the constructs translated as `x-` annotations.
+    warnings: Vec<Error>,
+}
+
+impl<'a> Translator<'a> {
+    fn new(loc: &'a Locator) -> Self {
+        Translator {
+            loc,
+            imports: IndexMap::new(),
+            scope: Vec::new(),
+            warnings: Vec::new(),
+        }
+    }
+
+    /// Translates a schema nested in the current one, at the given relative location.
+    fn scoped<T>(&mut self, segments: &[&str], f: impl FnOnce(&mut Self) -> T) -> T {
+        let depth = self.scope.len();
+        self.scope
+            .extend(segments.iter().map(|s| pointer_escape(s)));
+        let result = f(self);
+        self.scope.truncate(depth);
+        result
+    }
+
+    /// Warns about a construct translated as the given extension annotation,
+    /// at the given relative location.
+    fn unsupported(&mut self, segments: &[&str], key: &str) {
+        let pointer = self
+            .scope
+            .iter()
+            .cloned()
+            .chain(segments.iter().map(|s| pointer_escape(s)))
+            .fold("#".to_owned(), |p, s| format!("{p}/{s}"));
+        let warning = Error::new(
+            Kind::UnsupportedConstruct(format!("{}{pointer}", self.loc)),
+            format!("translated as annotation 'x-{key}'"),
+        )
+        .severity(Severity::Warning);
+        self.warnings.push(warning);
+    }
+
+    /// Adds an extension annotation for a keyword and warns about it.
+    fn extension<T: serde::Serialize>(&mut self, anns: &mut Annotations, key: &str, value: &T) {
+        anns.extension(key, value);
+        self.unsupported(&[key], key);
+    }
+
+    /// Returns the qualifier of a referenced document, importing it on first use.
+    fn qualifier(&mut self, path: &str) -> Result<String> {
+        if let Some(q) = self.imports.get(path) {
+            return Ok(q.clone());
+        }
+        let target = self.loc.join(path)?;
+        let mut base = sanitize(stem(&target));
+        if !base.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_') {
+            base.insert(0, '_');
+        }
+        let mut qualifier = base.clone();
+        let mut n = 1;
+        while is_keyword(&qualifier) || self.imports.values().any(|q| *q == qualifier) {
+            qualifier = format!("{base}{n}");
+            n += 1;
+        }
+        self.imports.insert(path.to_owned(), qualifier.clone());
+        Ok(qualifier)
+    }
+
+    /// Returns the expression of a schema reference, local or across documents.
+    fn reference(&mut self, reference: &str) -> Result<String> {
+        let (path, pointer) = reference.split_once('#').unwrap_or((reference, ""));
+        let ident = if pointer.is_empty() {
+            let target = if path.is_empty() {
+                self.loc.clone()
+            } else {
+                self.loc.join(path)?
+            };
+            root_ident(&target)
+        } else {
+            let name = definition_name(pointer)
+                .ok_or_else(|| Kind::UnsupportedReference(reference.to_owned()))?;
+            format!("@{}", sanitize(&name))
+        };
+        if path.is_empty() {
+            Ok(ident)
+        } else {
+            Ok(format!("{}.{ident}", self.qualifier(path)?))
+        }
+    }
+
+    fn inline_schema(&mut self, schema: &Value) -> Result<String> {
+        let (expr, anns) = self.schema(schema, false)?;
+        Ok(format!("{expr}{}", anns.inline()))
+    }
+
+    /// Returns the expression of a schema and its annotations.
+    ///
+    /// Keywords without an equivalent in the language are kept as extension annotations,
+    /// and top-level objects are laid out over several lines.
+    fn schema(&mut self, schema: &Value, top: bool) -> Result<(String, Annotations)> {
+        let mut anns = Annotations::default();
+        let mut kws = match schema {
+            Value::Object(o) => o.clone(),
+            Value::Bool(true) => Map::new(),
+            other => {
+                anns.extension("schema", other);
+                self.unsupported(&[], "schema");
+                return Ok(("{}".to_owned(), anns));
+            }
+        };
+        for kw in IGNORED_KEYWORDS {
+            kws.shift_remove(*kw);
+        }
+        for key in ["title", "description"] {
+            if let Some(text) = take(&mut kws, key, Value::is_string) {
+                anns.push(key, text);
+            }
+        }
+
+        let expr = if let Some(Value::String(r)) = take(&mut kws, "$ref", Value::is_string) {
+            self.reference(&r)?
+        } else if let Some(Value::Array(s)) = take(&mut kws, "allOf", Value::is_array) {
+            self.operation(&s, "allOf", " & ", top)?
+        } else if let Some(Value::Array(s)) = take(&mut kws, "oneOf", Value::is_array) {
+            self.operation(&s, "oneOf", " | ", top)?
+        } else if let Some(Value::Array(s)) = take(&mut kws, "anyOf", Value::is_array) {
+            self.operation(&s, "anyOf", " ~ ", top)?
+        } else {
+            match schema_type(&mut kws) {
+                Some("string") => string_type(&mut kws, &mut anns),
+                Some("number") => number_type(&mut kws, &mut anns, "num"),
+                Some("integer") => number_type(&mut kws, &mut anns, "int"),
+                Some("boolean") => "bool".to_owned(),
+                Some("object") => self.object_type(&mut kws, &mut anns, top)?,
+                Some("array") => self.array_type(&mut kws)?,
+                _ => "{}".to_owned(),
+            }
+        };
+
+        for (key, value) in kws {
+            self.extension(&mut anns, &key, &value);
+        }
+        Ok((expr, anns))
+    }
+
+    fn object_type(
+        &mut self,
+        kws: &mut Map<String, Value>,
+        anns: &mut Annotations,
+        top: bool,
+    ) -> Result<String> {
+        let required = take(kws, "required", Value::is_array).unwrap_or_default();
+        let is_required = |name: &str| {
+            required
+                .as_array()
+                .is_some_and(|r| r.iter().any(|n| n.as_str() == Some(name)))
+        };
+        let mut props = Vec::new();
+        let mut unsupported = Map::new();
+        if let Some(Value::Object(properties)) = take(kws, "properties", Value::is_object) {
+            for (name, schema) in properties {
+                if is_property_name(&name) {
+                    let mark = if is_required(&name) { "!" } else { "" };
+                    let schema =
+                        self.scoped(&["properties", &name], |t| t.inline_schema(&schema))?;
+                    props.push(format!("'{name}{mark} {schema}"));
+                } else {
+                    unsupported.insert(name, schema);
+                }
+            }
+        }
+        if !unsupported.is_empty() {
+            anns.extension("properties", &unsupported);
+            for name in unsupported.keys() {
+                self.unsupported(&["properties", name], "properties");
+            }
+        }
+        let expr = if props.is_empty() {
+            "{}".to_owned()
+        } else if top && props.len() > 1 {
+            format!("{{\n  {}\n}}", props.join("\n, "))
+        } else {
+            format!("{{ {} }}", props.join(", "))
+        };
+        Ok(expr)
+    }
+
+    fn array_type(&mut self, kws: &mut Map<String, Value>) -> Result<String> {
+        let is_schema = |v: &Value| v.is_object() || v.as_bool() == Some(true);
+        let item = match take(kws, "items", is_schema) {
+            Some(item) => self.scoped(&["items"], |t| t.inline_schema(&item))?,
+            None => "{}".to_owned(),
+        };
+        Ok(format!("[{item}]"))
+    }
+
+    fn operation(
+        &mut self,
+        schemas: &[Value],
+        keyword: &str,
+        op: &str,
+        top: bool,
+    ) -> Result<String> {
+        let terms = schemas
+            .iter()
+            .enumerate()
+            .map(|(i, s)| self.scoped(&[keyword, &i.to_string()], |t| t.inline_schema(s)))
+            .collect::<Result<Vec<_>>>()?;
+        let expr = if terms.is_empty() {
+            "{}".to_owned()
+        } else if top {
+            terms.join(op)
+        } else {
+            format!("({})", terms.join(op))
+        };
+        Ok(expr)
+    }
+
+    fn declaration(&mut self, ident: &str, schema: &Value) -> Result<String> {
+        let (expr, anns) = self.schema(schema, true)?;
+        Ok(format!("\n{}let {ident} = {expr};\n", anns.lines("")))
+    }
+
+    fn translate(mut self, doc: &Value) -> Result<Translation> {
+        let mut decls = String::new();
+        let is_root_schema =
+            doc.get("openapi").is_none() && SCHEMA_KEYWORDS.iter().any(|k| doc.get(k).is_some());
+        if is_root_schema {
+            let mut root = doc.clone();
+            if let Some(o) = root.as_object_mut() {
+                o.shift_remove("definitions");
+                o.shift_remove("$defs");
+            }
+            decls.push_str(&self.declaration(&root_ident(self.loc), &root)?);
+        }
+        for path in DEFINITIONS {
+            let defs = path.iter().try_fold(doc, |v, key| v.get(key));
+            if let Some(Value::Object(defs)) = defs {
+                for (name, schema) in defs {
+                    let ident = format!("@{}", sanitize(name));
+                    let segments: Vec<&str> = path.iter().copied().chain([name.as_str()]).collect();
+                    let decl = self.scoped(&segments, |t| t.declaration(&ident, schema))?;
+                    decls.push_str(&decl);
+                }
+            }
+        }
+
+        let mut program = format!("// Generated from the schema document {}.\n", self.loc);
+        for (path, qualifier) in self.imports.iter() {
+            program.push_str(&format!("use \"{path}\" as {qualifier};\n"));
+        }
+        program.push_str(&decls);
+        Ok(Translation {
+            program,
+            warnings: self.warnings,
+        })
+    }
+}
+
+/// Translates a JSON Schema or OpenAPI document into the source code of a module.
+///
+/// Each entry of `components.schemas`, `definitions` or `$defs` becomes a reference declaration,
+/// as does the document root if it is a schema itself, named after the document.
+/// References to other documents become imports of the corresponding modules,
+/// and keywords without equivalent in the language become `x-` annotations, with a warning.
+pub fn translate(loc: &Locator, input: &str) -> Result<Translation> {
+    let doc: Value = if loc.url().path().ends_with(".json") {
+        serde_json::from_str(input)?
+    } else {
+        serde_yaml::from_str(input)?
+    };
+    if !doc.is_object() {
+        return Err(Error::new(
+            Kind::InvalidModule(loc.clone()),
+            "expected a schema document",
+        ));
+    }
+    Translator::new(loc).translate(&doc)
+}
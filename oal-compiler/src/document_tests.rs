@@ -0,0 +1,237 @@
+use crate::document::{is_document, translate};
+use crate::errors::{Error, Kind};
+use crate::eval::eval;
+use crate::module::{load, Loader, ModuleSet};
+use crate::spec::{Reference, SchemaExpr};
+use crate::tree::Tree;
+use oal_model::locator::Locator;
+use std::collections::HashMap;
+
+struct ContextFiles(HashMap<&'static str, &'static str>);
+
+impl Loader<anyhow::Error> for ContextFiles {
+    fn is_valid(&mut self, loc: &Locator) -> bool {
+        self.0.contains_key(loc.url().as_str())
+    }
+
+    fn load(&mut self, loc: &Locator) -> anyhow::Result<String> {
+        Ok(self.0[loc.url().as_str()].to_owned())
+    }
+
+    fn parse(&mut self, loc: Locator, input: String) -> anyhow::Result<Tree> {
+        let (tree, errs) = oal_syntax::parse(loc, input);
+        assert!(errs.is_empty(), "{errs:?}");
+        Ok(tree.expect("parsing failed"))
+    }
+
+    fn compile(&mut self, mods: &ModuleSet, loc: &Locator) -> anyhow::Result<()> {
+        crate::compile::compile(mods, loc)?;
+        Ok(())
+    }
+}
+
+#[test]
+fn document_extension() -> anyhow::Result<()> {
+    assert!(is_document(&Locator::try_from(
+        "file:///schemas/event.json"
+    )?));
+    assert!(is_document(&Locator::try_from("file:///components.yaml")?));
+    assert!(is_document(&Locator::try_from("file:///components.yml")?));
+    assert!(!is_document(&Locator::try_from("file:///module.oal")?));
+
+    Ok(())
+}
+
+#[test]
+fn document_translate() -> anyhow::Result<()> {
+    let loc = Locator::try_from("file:///schemas/event.json")?;
+    let input = r##"{
+        "$schema": "http://json-schema.org/draft-07/schema#",
+        "title": "Event",
+        "type": "object",
+        "required": ["id", "kind"],
+        "properties": {
+            "id": { "type": "string", "format": "uuid" },
+            "kind": { "$ref": "#/definitions/Kind" },
+            "address": { "$ref": "../components.yaml#/components/schemas/Address" },
+            "tags": { "type": "array", "items": { "type": "string" }, "uniqueItems": true },
+            "note": { "type": ["string", "null"] }
+        },
+        "definitions": {
+            "Kind": { "enum": ["created", "deleted"] },
+            "Size": { "type": "integer", "minimum": 0, "exclusiveMaximum": 10 },
+            "Any": { "oneOf": [{ "$ref": "#/definitions/Kind" }, { "type": "boolean" }] }
+        }
+    }"##;
+
+    let translation = translate(&loc, input)?;
+
+    assert_eq!(
+        translation.program,
+        r#"// Generated from the schema document file:///schemas/event.json.
+use "../components.yaml" as components;
+
+# title: "Event"
+let @event = {
+  'id! str `format: "uuid"`
+, 'kind! @Kind
+, 'address components.@Address
+, 'tags [str] `x-uniqueItems: true`
+, 'note str `x-nullable: true`
+};
+
+# enum: ["created","deleted"]
+let @Kind = str;
+
+# minimum: 0
+# x-exclusiveMaximum: 10
+let @Size = int;
+
+let @Any = @Kind | bool;
+"#
+    );
+
+    Ok(())
+}
+
+#[test]
+fn document_unsupported_constructs() -> anyhow::Result<()> {
+    let loc = Locator::try_from("file:///schemas/item.json")?;
+    let input = r##"{
+        "type": "object",
+        "properties": {
+            "x y": { "type": "string" },
+            "size": { "type": "number", "minimum": "abc" },
+            "tags": { "type": "array", "items": { "type": "string", "uniqueItems": true } }
+        },
+        "definitions": {
+            "Never": false
+        }
+    }"##;
+
+    let translation = translate(&loc, input)?;
+
+    let warnings: Vec<_> = translation
+        .warnings
+        .iter()
+        .map(|w| {
+            assert!(w.is_warning());
+            match &w.kind {
+                Kind::UnsupportedConstruct(pointer) => pointer.as_str(),
+                _ => panic!("expected an unsupported construct"),
+            }
+        })
+        .collect();
+    assert_eq!(
+        warnings,
+        vec![
+            "file:///schemas/item.json#/properties/size/minimum",
+            "file:///schemas/item.json#/properties/tags/items/uniqueItems",
+            "file:///schemas/item.json#/properties/x y",
+            "file:///schemas/item.json#/definitions/Never",
+        ]
+    );
+
+    Ok(())
+}
+
+#[test]
+fn document_unsupported_reference() -> anyhow::Result<()> {
+    let loc = Locator::try_from("file:///components.yaml")?;
+    let input = r##"
+        components:
+          schemas:
+            Street:
+              $ref: '#/components/schemas/Address/properties/street'
+    "##;
+
+    let err = translate(&loc, input).expect_err("expected an error");
+
+    assert!(matches!(err.kind, Kind::UnsupportedReference(_)));
+
+    Ok(())
+}
+
+#[test]
+fn document_load() -> anyhow::Result<()> {
+    let mut ctx = ContextFiles(HashMap::from([
+        (
+            "file:///main.oal",
+            r#"
+            use "schemas/event.json" as e;
+            use "components.yaml" as m;
+            res /events on post : e.@event -> m.@Address;
+            "#,
+        ),
+        (
+            "file:///schemas/event.json",
+            r##"{
+                "type": "object",
+                "properties": {
+                    "address": { "$ref": "../components.yaml#/components/schemas/Address" }
+                }
+            }"##,
+        ),
+        (
+            "file:///components.yaml",
+            r#"
+            openapi: 3.0.3
+            components:
+              schemas:
+                Address:
+                  type: object
+                  required: [street]
+                  properties:
+                    street: { type: string, description: "The street name." }
+                    city: { $ref: '#/components/schemas/City' }
+                City:
+                  type: string
+                  maxLength: 64
+            "#,
+        ),
+    ]));
+    let main = Locator::try_from("file:///main.oal")?;
+
+    let mods = load(&mut ctx, &main)?;
+    assert_eq!(mods.len(), 3);
+    let spec = eval(&mods)?;
+
+    let address = spec
+        .refs
+        .iter()
+        .find_map(|(k, r)| match r {
+            Reference::Schema(s) if k.untagged().ends_with("Address") => Some(s),
+            _ => None,
+        })
+        .expect("expected the address reference");
+    let SchemaExpr::Object(obj) = &address.expr else {
+        panic!("expected an object");
+    };
+    assert_eq!(obj.props.len(), 2);
+    assert_eq!(obj.props[0].required, Some(true));
+    assert_eq!(
+        obj.props[0].schema.desc.as_deref(),
+        Some("The street name.")
+    );
+
+    Ok(())
+}
+
+#[test]
+fn document_invalid() -> anyhow::Result<()> {
+    let mut ctx = ContextFiles(HashMap::from([
+        ("file:///main.oal", r#"use "schema.json";"#),
+        ("file:///schema.json", "[]"),
+    ]));
+    let main = Locator::try_from("file:///main.oal")?;
+
+    let err = load(&mut ctx, &main).expect_err("expected an error");
+
+    let err = err
+        .downcast_ref::<Error>()
+        .expect("expected compiler error");
+    assert!(matches!(err.kind, Kind::InvalidModule(_)));
+    assert!(err.span().is_some());
+
+    Ok(())
+}
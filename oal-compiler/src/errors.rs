@@ -8,6 +8,8 @@ pub enum Kind {
     Locator(#[from] oal_model::locator::Error),
     #[error("invalid YAML: {0}")]
     Yaml(#[from] serde_yaml::Error),
+    #[error("invalid JSON: {0}")]
+    Json(#[from] serde_json::Error),
     #[error("invalid syntax: {0}")]
     Syntax(#[from] oal_syntax::errors::Error),
     #[error("not in scope")]
@@ -20,24 +22,59 @@ pub enum Kind {
     InvalidLiteral,
     #[error("invalid identifier")]
     InvalidIdentifier,
+    #[error("unknown annotation")]
+    UnknownAnnotation,
+    #[error("invalid annotation")]
+    InvalidAnnotation,
+    #[error("unexpected expression")]
+    UnexpectedExpression,
+    #[error("conflicting path")]
+    ConflictingPath,
+    #[error("excluded dependency")]
+    ExcludedDependency,
     #[error("invalid module: {0}")]
     InvalidModule(Locator),
+    #[error("unsupported reference: {0}")]
+    UnsupportedReference(String),
+    #[error("unsupported construct: {0}")]
+    UnsupportedConstruct(String),
+    #[error("{0}")]
+    Lint(crate::lint::Rule),
+}
+
+/// The severity of a diagnostic.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Severity {
+    #[default]
+    Error,
+    Warning,
 }
 
 #[derive(Debug)]
 pub struct Error {
     msg: String,
-    details: Vec<String>,
     span: Option<Span>,
+    severity: Severity,
+    extra: Box<Extra>,
     pub kind: Kind,
 }
 
+/// The less common error attributes, boxed to keep the error type small.
+#[derive(Debug, Default)]
+struct Extra {
+    details: Vec<String>,
+    expected: Option<String>,
+    found: Option<String>,
+    labels: Vec<(Span, String)>,
+}
+
 impl<E: Into<Kind>> From<E> for Error {
     fn from(e: E) -> Self {
         Error {
             msg: Default::default(),
-            details: Default::default(),
             span: Default::default(),
+            severity: Default::default(),
+            extra: Default::default(),
             kind: e.into(),
         }
     }
@@ -47,14 +84,15 @@ impl Error {
     pub fn new<S: Into<String>>(kind: Kind, msg: S) -> Self {
         Error {
             msg: msg.into(),
-            details: Vec::new(),
             span: None,
+            severity: Severity::Error,
+            extra: Default::default(),
             kind,
         }
     }
 
     pub fn with<T: Debug>(mut self, e: &T) -> Self {
-        self.details.push(format!("{e:?}"));
+        self.extra.details.push(format!("{e:?}"));
         self
     }
 
@@ -63,9 +101,58 @@ impl Error {
         self
     }
 
+    /// Sets the severity, e.g. to downgrade the error to a warning.
+    pub fn severity(mut self, severity: Severity) -> Self {
+        self.severity = severity;
+        self
+    }
+
+    /// Sets the expected type, e.g. a type tag.
+    pub fn expected<T: Display>(mut self, t: T) -> Self {
+        self.extra.expected = Some(t.to_string());
+        self
+    }
+
+    /// Sets the type actually found, e.g. a type tag.
+    pub fn found<T: Display>(mut self, t: T) -> Self {
+        self.extra.found = Some(t.to_string());
+        self
+    }
+
+    /// Adds a secondary labelled span, if any.
+    pub fn label<S: Into<String>>(mut self, span: Option<Span>, msg: S) -> Self {
+        if let Some(span) = span {
+            self.extra.labels.push((span, msg.into()));
+        }
+        self
+    }
+
+    /// Adds a collection of secondary labelled spans.
+    pub fn with_labels<I: IntoIterator<Item = (Span, String)>>(mut self, labels: I) -> Self {
+        self.extra.labels.extend(labels);
+        self
+    }
+
     pub fn span(&self) -> Option<&Span> {
         self.span.as_ref()
     }
+
+    pub fn is_warning(&self) -> bool {
+        self.severity == Severity::Warning
+    }
+
+    pub fn expected_type(&self) -> Option<&str> {
+        self.extra.expected.as_deref()
+    }
+
+    pub fn found_type(&self) -> Option<&str> {
+        self.extra.found.as_deref()
+    }
+
+    /// Returns the secondary labelled spans.
+    pub fn labels(&self) -> &[(Span, String)] {
+        &self.extra.labels
+    }
 }
 
 impl Display for Error {
@@ -74,6 +161,12 @@ impl Display for Error {
         if !self.msg.is_empty() {
             write!(f, ": {}", self.msg)?;
         }
+        match (&self.extra.expected, &self.extra.found) {
+            (Some(e), Some(t)) => write!(f, " (expected {e}, found {t})")?,
+            (Some(e), None) => write!(f, " (expected {e})")?,
+            (None, Some(t)) => write!(f, " (found {t})")?,
+            (None, None) => {}
+        }
         std::fmt::Result::Ok(())
     }
 }
@@ -81,3 +174,96 @@ impl Display for Error {
 impl std::error::Error for Error {}
 
 pub type Result<T> = std::result::Result<T, Error>;
+
+/// The maximum number of errors accumulated before giving up.
+pub const MAX_ERRORS: usize = 100;
+
+/// A collection of independent errors.
+#[derive(Debug, Default)]
+pub struct Errors(Vec<Error>);
+
+impl Errors {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Records an error, unless the maximum number of errors is reached.
+    pub fn push(&mut self, err: Error) {
+        if !self.is_full() {
+            self.0.push(err);
+        }
+    }
+
+    /// Records an error, unless the same error was already recorded at the same location,
+    /// e.g. when evaluating a declaration several times.
+    pub fn push_once(&mut self, err: Error) {
+        let message = err.to_string();
+        if !self
+            .0
+            .iter()
+            .any(|e| e.span == err.span && e.to_string() == message)
+        {
+            self.push(err);
+        }
+    }
+
+    /// Records the error of the given result, if any.
+    pub fn check<T>(&mut self, res: Result<T>) -> Option<T> {
+        match res {
+            Ok(value) => Some(value),
+            Err(err) => {
+                self.push(err);
+                None
+            }
+        }
+    }
+
+    /// Returns true if no more errors should be accumulated.
+    pub fn is_full(&self) -> bool {
+        self.0.len() >= MAX_ERRORS
+    }
+
+    /// Returns the given value if no error was accumulated.
+    pub fn into_result<T>(self, value: T) -> std::result::Result<T, Errors> {
+        if self.0.is_empty() {
+            Ok(value)
+        } else {
+            Err(self)
+        }
+    }
+}
+
+impl std::ops::Deref for Errors {
+    type Target = [Error];
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl IntoIterator for Errors {
+    type Item = Error;
+    type IntoIter = std::vec::IntoIter<Error>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+impl From<Error> for Errors {
+    fn from(err: Error) -> Self {
+        Errors(vec![err])
+    }
+}
+
+impl Display for Errors {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        match self.0.split_first() {
+            None => write!(f, "no error"),
+            Some((first, [])) => write!(f, "{first}"),
+            Some((first, others)) => write!(f, "{first} (and {} more)", others.len()),
+        }
+    }
+}
+
+impl std::error::Error for Errors {}
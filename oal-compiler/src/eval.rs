@@ -1,16 +1,19 @@
-use crate::annotation::Annotation;
+use crate::annotation::{self, Annotation, Invalid};
 use crate::definition::{Definition, InternalRef};
-use crate::errors::{Error, Kind, Result};
+use crate::errors::{Error, Errors, Kind, Result};
+use crate::inference::tag::Tag;
 use crate::module::ModuleSet;
 use crate::spec::{
-    Array, Content, Object, PrimBoolean, PrimInteger, PrimNumber, PrimString, Property, Ranges,
-    Reference, Relation, Schema, SchemaExpr, Spec, Transfer, Transfers, Uri, UriSegment,
-    VariadicOp,
+    status_label, Array, Content, Object, PrimBoolean, PrimInteger, PrimNumber, PrimString,
+    Property, Ranges, Reference, Relation, Schema, SchemaExpr, Spec, Transfer, Transfers, Uri,
+    UriSegment, VariadicOp,
 };
 use crate::tree::{Core, NRef};
+use crate::versioning::{Version, Versions};
 use enum_map::EnumMap;
 use indexmap::IndexMap;
 use oal_model::grammar::AbstractSyntaxNode;
+use oal_model::span::Span;
 use oal_syntax::atom;
 use oal_syntax::lexer as lex;
 use oal_syntax::parser as syn;
@@ -67,19 +70,48 @@ impl<'a> Expr<'a> {
                 | Expr::Array(_)
                 | Expr::Uri(_)
                 | Expr::VariadicOp(_)
-                | Expr::Reference(_, _)
                 | Expr::Relation(_)
                 | Expr::Recursion(_)
-        )
+        ) || matches!(self, Expr::Reference(_, v) if v.0.is_schema_like())
     }
 
     fn is_content_like(&self) -> bool {
-        matches!(self, Expr::Content(_)) || self.is_schema_like()
+        match self {
+            Expr::Content(_) => true,
+            Expr::Reference(_, v) => v.0.is_content_like(),
+            e => e.is_schema_like(),
+        }
     }
 
     fn is_uri_like(&self) -> bool {
         matches!(self, Expr::Uri(_) | Expr::Relation(_))
     }
+
+    /// Returns a short description of the expression for diagnostics.
+    fn describe(&self) -> &'static str {
+        match self {
+            Expr::Spec(_) => "a specification",
+            Expr::Uri(_) => "a URI",
+            Expr::Relation(_) => "a relation",
+            Expr::Transfer(_) => "a transfer",
+            Expr::Content(_) => "a content",
+            Expr::Object(_) => "an object",
+            Expr::Ranges(_) => "ranges",
+            Expr::Property(_) => "a property",
+            Expr::PrimInteger(_) => "an integer",
+            Expr::PrimNumber(_) => "a number",
+            Expr::PrimString(_) => "a string primitive",
+            Expr::PrimBoolean(_) => "a boolean",
+            Expr::VariadicOp(_) => "a schema operation",
+            Expr::Reference(_, v) => v.0.describe(),
+            Expr::Array(_) => "an array",
+            Expr::String(_) => "a string",
+            Expr::Number(_) => "a number literal",
+            Expr::HttpStatus(_) => "an HTTP status",
+            Expr::Lambda(_) => "a function",
+            Expr::Recursion(_) => "a recursive reference",
+        }
+    }
 }
 
 type Scope<'a> = HashMap<atom::Ident, Value<'a>>;
@@ -93,6 +125,8 @@ pub struct Context<'a> {
     scopes: Vec<(ScopeId, Scope<'a>)>,
     /// The sequence of unique scope identifiers in the evaluation tree.
     scope_id_seq: ScopeId,
+    /// The errors accumulated from independent evaluations.
+    errors: Errors,
 }
 
 impl<'a> Context<'a> {
@@ -102,6 +136,7 @@ impl<'a> Context<'a> {
             refs: IndexMap::new(),
             scopes: Vec::new(),
             scope_id_seq: 0,
+            errors: Errors::new(),
         }
     }
 
@@ -142,25 +177,158 @@ impl<'a> Context<'a> {
     }
 }
 
-fn compose_annotations<'a, I>(anns: I) -> Result<Annotation>
+/// Returns the annotation context matching the type of the given node, if any.
+fn annotation_context(node: NRef) -> Option<annotation::Context> {
+    fn from_tag(tag: &Tag) -> Option<annotation::Context> {
+        match tag {
+            Tag::Primitive => Some(annotation::Context::Primitive),
+            Tag::Object | Tag::Array | Tag::Any => Some(annotation::Context::Schema),
+            Tag::Uri | Tag::Relation => Some(annotation::Context::Relation),
+            Tag::Content => Some(annotation::Context::Content),
+            Tag::Transfer => Some(annotation::Context::Transfer),
+            Tag::Property(_) => Some(annotation::Context::Property),
+            Tag::Func(f) => from_tag(&f.range),
+            _ => None,
+        }
+    }
+    node.syntax().core_ref().tag().and_then(from_tag)
+}
+
+/// Returns the offset of the given key inside the annotation text.
+fn annotation_key_offset(text: &str, key: &str) -> Option<usize> {
+    let is_word = |c: char| c.is_alphanumeric() || c == '_' || c == '-';
+    let (idx, _) = text.match_indices(key).find(|(i, _)| {
+        let before = text[..*i].chars().next_back();
+        let after = text[*i + key.len()..].chars().next();
+        !before.is_some_and(is_word) && !after.is_some_and(is_word)
+    })?;
+    Some(idx)
+}
+
+/// Returns the span of the given range of the annotation text.
+fn annotation_span(a: &syn::Annotation<Core>, range: std::ops::Range<usize>) -> Option<Span> {
+    let span = a.node().span()?;
+    // Both line and inline annotations are prefixed by a single delimiter.
+    let start = span.start() + 1;
+    Some(Span::new(
+        span.locator().clone(),
+        start + range.start..start + range.end,
+    ))
+}
+
+/// Returns the span of the given key inside the annotation text.
+fn annotation_key_span(a: &syn::Annotation<Core>, key: &str) -> Option<Span> {
+    let idx = annotation_key_offset(a.as_str(), key)?;
+    annotation_span(a, idx..idx + key.len())
+}
+
+/// Returns the span of the value of the given key inside the annotation text,
+/// up to the next entry of the flow mapping or the end of the line.
+fn annotation_value_span(a: &syn::Annotation<Core>, key: &str) -> Option<Span> {
+    let text = a.as_str();
+    let idx = annotation_key_offset(text, key)? + key.len();
+    let rest = text[idx..].trim_start().strip_prefix(':')?;
+    let start = text.len() - rest.trim_start().len();
+    let (mut depth, mut quote) = (0usize, None);
+    let mut end = text.len();
+    for (i, c) in text[start..].char_indices() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), _) => {}
+            (None, '"' | '\'') => quote = Some(c),
+            (None, '[' | '{') => depth += 1,
+            (None, ']' | '}') => depth = depth.saturating_sub(1),
+            (None, ',') if depth == 0 => {
+                end = start + i;
+                break;
+            }
+            (None, '\n') => {
+                end = start + i;
+                break;
+            }
+            _ => {}
+        }
+    }
+    let value = text[start..end].trim_end();
+    (!value.is_empty()).then(|| annotation_span(a, start..start + value.len()))?
+}
+
+fn compose_annotations<'a, I, D>(
+    errors: &mut Errors,
+    anns: I,
+    docs: D,
+    ctx: Option<annotation::Context>,
+) -> Result<Annotation>
 where
     I: Iterator<Item = syn::Annotation<'a, Core>>,
+    D: Iterator<Item = syn::Doc<'a, Core>>,
 {
-    let mut ann = Annotation::default();
+    // Doc comments provide the default description, superseded by explicit annotations.
+    let docs = docs.map(|d| d.as_str()).collect::<Vec<_>>();
+    let mut ann = if docs.is_empty() {
+        Annotation::default()
+    } else {
+        Annotation::from_description(docs.join("\n"))
+    };
+    let within = ctx.map(|c| format!(" for {c}")).unwrap_or_default();
     for a in anns {
         let other =
             Annotation::try_from(a.as_str()).map_err(|err| Error::from(err).at(a.node().span()))?;
-        ann.extend(other);
+        // All the invalid keys are reported, and the annotation is then left out.
+        let invalid = other.validate(ctx);
+        for invalid in invalid.iter() {
+            let key_span = || annotation_key_span(&a, invalid.key()).or_else(|| a.node().span());
+            let err = match invalid {
+                Invalid::UnknownKey { key, suggestion } => {
+                    let hint = suggestion
+                        .map(|s| format!(", did you mean '{s}'?"))
+                        .unwrap_or_default();
+                    Error::new(
+                        Kind::UnknownAnnotation,
+                        format!("'{key}' is not a known key{within}{hint}"),
+                    )
+                    .at(key_span())
+                }
+                Invalid::UnexpectedValue { key, expected } => Error::new(
+                    Kind::InvalidAnnotation,
+                    format!("'{key}' expects {expected}{within}"),
+                )
+                .at(annotation_value_span(&a, key).or_else(key_span)),
+            };
+            errors.push_once(err);
+        }
+        if invalid.is_empty() {
+            ann.extend(other);
+        }
     }
     Ok(ann)
 }
 
-pub fn cast_schema(from: (Expr, AnnRef)) -> Schema {
+/// Returns the error for a value that does not have the expected shape.
+fn unexpected(expected: &str, found: &Expr) -> Error {
+    Error::new(
+        Kind::UnexpectedExpression,
+        format!("expected {expected}, found {}", found.describe()),
+    )
+}
+
+/// Returns the range of versions of an annotated element.
+fn versions(ann: &Annotation) -> Versions {
+    let version = |key| ann.get_scalar(key).map(|v| Version::from(v.as_str()));
+    Versions {
+        since: version("since"),
+        until: version("until"),
+        deprecated_since: version("deprecatedSince"),
+    }
+}
+
+pub fn cast_schema(from: (Expr, AnnRef)) -> Result<Schema> {
     let ann = from.1;
     let desc = ann.get_string("description");
     let title = ann.get_string("title");
     let required = ann.get_bool("required");
     let examples = ann.get_props("examples");
+    let audience = ann.get_enum("audience").unwrap_or_default();
 
     let expr = match from.0 {
         Expr::Object(o) => SchemaExpr::Object(*o),
@@ -174,56 +342,82 @@ pub fn cast_schema(from: (Expr, AnnRef)) -> Schema {
         Expr::Reference(r, _) => SchemaExpr::Ref(r),
         Expr::Relation(r) => SchemaExpr::Rel(r),
         Expr::Recursion(r) => SchemaExpr::Ref(r),
-        e => panic!("not a schema: {e:?}"),
+        e => return Err(unexpected("a schema", &e)),
     };
 
-    Schema {
+    Ok(Schema {
         expr,
         desc,
         title,
         required,
         examples,
-    }
+        audience,
+        versions: versions(&ann),
+        deprecated: false,
+    })
 }
 
-pub fn cast_content(from: (Expr, AnnRef)) -> Content {
+pub fn cast_content(from: (Expr, AnnRef)) -> Result<Content> {
     if let Expr::Content(c) = from.0 {
-        *c
+        Ok(*c)
     } else if from.0.is_schema_like() {
-        Content::from(cast_schema(from))
-    } else if let Expr::Reference(_, v) = from.0 {
-        cast_content(*v)
+        cast_schema(from).map(Content::from)
+    } else if let Expr::Reference(ident, v) = from.0 {
+        let mut content = cast_content(*v)?;
+        content.reference = Some(ident);
+        Ok(content)
     } else {
-        panic!("not a content: {:?}", from.0)
+        Err(unexpected("a content", &from.0))
+    }
+}
+
+/// Derives reference identifiers for the contents of a ranges reference.
+///
+/// Contents that are not themselves references are named after the ranges reference and their status,
+/// unless the status is shared with another content.
+fn name_ranges(ident: &atom::Ident, ranges: &mut Ranges) {
+    let statuses: Vec<_> = ranges.keys().map(|(s, _)| *s).collect();
+    for ((status, _), content) in ranges.iter_mut() {
+        let is_unique = statuses.iter().filter(|s| *s == status).count() == 1;
+        if content.reference.is_none() && is_unique {
+            let name = format!("{ident}-{}", status_label(*status));
+            content.reference = Some(name.as_str().into());
+        }
     }
 }
 
-pub fn cast_ranges(from: (Expr, AnnRef)) -> Ranges {
+pub fn cast_ranges(from: (Expr, AnnRef)) -> Result<Ranges> {
     if let Expr::Ranges(r) = from.0 {
-        *r
+        Ok(*r)
     } else if from.0.is_content_like() {
-        let c = cast_content(from);
-        Ranges::from([((c.status, c.media.clone()), c)])
-    } else if let Expr::Reference(_, v) = from.0 {
-        cast_ranges(*v)
+        let c = cast_content(from)?;
+        Ok(Ranges::from([((c.status, c.media.clone()), c)]))
+    } else if let Expr::Reference(ident, v) = from.0 {
+        let mut ranges = cast_ranges(*v)?;
+        name_ranges(&ident, &mut ranges);
+        Ok(ranges)
     } else {
-        panic!("not ranges: {:?}", from.0)
+        Err(unexpected("ranges", &from.0))
     }
 }
 
-pub fn cast_string(from: (Expr, AnnRef)) -> String {
+pub fn cast_string(from: (Expr, AnnRef)) -> Result<String> {
     match from.0 {
-        Expr::String(s) => s,
+        Expr::String(s) => Ok(s),
         Expr::Reference(_, v) => cast_string(*v),
-        e => panic!("not a string: {e:?}"),
+        e => Err(unexpected("a string", &e)),
     }
 }
 
-pub fn cast_property(from: (Expr, AnnRef)) -> Property {
+pub fn cast_property(from: (Expr, AnnRef)) -> Result<Property> {
     match from.0 {
-        Expr::Property(p) => *p,
-        Expr::Reference(_, v) => cast_property(*v),
-        e => panic!("not a property: {e:?}"),
+        Expr::Property(p) => Ok(*p),
+        Expr::Reference(ident, v) => {
+            let mut prop = cast_property(*v)?;
+            prop.reference = Some(ident);
+            Ok(prop)
+        }
+        e => Err(unexpected("a property", &e)),
     }
 }
 
@@ -235,62 +429,76 @@ pub fn cast_http_status(from: (Expr, AnnRef)) -> Result<atom::HttpStatus> {
             Ok(s)
         }
         Expr::Reference(_, v) => cast_http_status(*v),
-        e => panic!("not an HTTP status: {e:?}"),
+        e => Err(unexpected("an HTTP status", &e)),
     }
 }
 
-pub fn cast_object(from: (Expr, AnnRef)) -> Object {
+pub fn cast_object(from: (Expr, AnnRef)) -> Result<Object> {
     match from.0 {
-        Expr::Object(o) => *o,
+        Expr::Object(o) => Ok(*o),
         Expr::Reference(_, v) => cast_object(*v),
-        e => panic!("not an object: {e:?}"),
+        e => Err(unexpected("an object", &e)),
     }
 }
 
-pub fn cast_transfer(from: (Expr, AnnRef)) -> Transfer {
+pub fn cast_transfer(from: (Expr, AnnRef)) -> Result<Transfer> {
     match from.0 {
-        Expr::Transfer(x) => *x,
+        Expr::Transfer(x) => Ok(*x),
         Expr::Reference(_, v) => cast_transfer(*v),
-        e => panic!("not a transfer: {e:?}"),
+        e => Err(unexpected("a transfer", &e)),
     }
 }
 
-pub fn cast_relation(from: (Expr, AnnRef)) -> Relation {
+pub fn cast_relation(from: (Expr, AnnRef)) -> Result<Relation> {
     if let Expr::Relation(r) = from.0 {
-        *r
+        Ok(*r)
     } else if from.0.is_uri_like() {
-        Relation::from(cast_uri(from))
+        cast_uri(from).map(Relation::from)
     } else if let Expr::Reference(_, v) = from.0 {
         cast_relation(*v)
     } else {
-        panic!("not a relation: {:?}", from.0)
+        Err(unexpected("a relation", &from.0))
     }
 }
 
-pub fn cast_uri(from: (Expr, AnnRef)) -> Uri {
+pub fn cast_uri(from: (Expr, AnnRef)) -> Result<Uri> {
     match from.0 {
-        Expr::Uri(u) => *u,
-        Expr::Relation(r) => r.uri,
+        Expr::Uri(u) => Ok(*u),
+        Expr::Relation(r) => Ok(r.uri),
         Expr::Reference(_, v) => cast_uri(*v),
-        e => panic!("not a uri: {e:?}"),
+        e => Err(unexpected("a URI", &e)),
     }
 }
 
-pub fn cast_lambda(from: (Expr, AnnRef)) -> Lambda {
+pub fn cast_lambda(from: (Expr, AnnRef)) -> Result<Lambda> {
     match from.0 {
-        Expr::Lambda(l) => l,
+        Expr::Lambda(l) => Ok(l),
         Expr::Reference(_, v) => cast_lambda(*v),
-        e => panic!("not a lambda: {e:?}"),
+        e => Err(unexpected("a function", &e)),
     }
 }
 
+/// Evaluates a node and casts the resulting value, reporting failures at the node location.
+fn eval_as<'a, T, F>(ctx: &mut Context<'a>, node: NRef<'a>, cast: F) -> Result<T>
+where
+    F: FnOnce(Value<'a>) -> Result<T>,
+{
+    let value = eval_any(ctx, node, AnnRef::default())?;
+    cast(value).map_err(|err| err.at(node.span()))
+}
+
 pub fn eval_terminal<'a>(
     ctx: &mut Context<'a>,
     terminal: syn::Terminal<'a, Core>,
     ann: AnnRef,
 ) -> Result<(Expr<'a>, AnnRef)> {
     let mut next_ann = ann.as_ref().clone();
-    next_ann.extend(compose_annotations(terminal.annotations())?);
+    next_ann.extend(compose_annotations(
+        &mut ctx.errors,
+        terminal.annotations(),
+        terminal.docs(),
+        annotation_context(terminal.node()),
+    )?);
     let next_ann = AnnRef::new(next_ann);
     eval_any(ctx, terminal.inner(), next_ann)
 }
@@ -300,10 +508,20 @@ pub fn eval_transfer<'a>(
     transfer: syn::Transfer<'a, Core>,
     ann: AnnRef,
 ) -> Result<(Expr<'a>, AnnRef)> {
+    let mut next_ann = ann.as_ref().clone();
+    next_ann.extend(compose_annotations(
+        &mut ctx.errors,
+        transfer.annotations(),
+        transfer.docs(),
+        Some(annotation::Context::Transfer),
+    )?);
+    let ann = AnnRef::new(next_ann);
+
     let desc = ann.get_string("description");
     let summary = ann.get_string("summary");
     let tags = ann.get_enum("tags").unwrap_or_default();
     let id = ann.get_string("operationId");
+    let audience = ann.get_enum("audience").unwrap_or_default();
 
     let mut methods = EnumMap::default();
     for m in transfer.methods() {
@@ -311,14 +529,14 @@ pub fn eval_transfer<'a>(
     }
 
     let domain = match transfer.domain() {
-        Some(term) => cast_content(eval_terminal(ctx, term, AnnRef::default())?),
+        Some(term) => eval_as(ctx, term.node(), cast_content)?,
         None => Content::default(),
     };
 
-    let ranges = cast_ranges(eval_any(ctx, transfer.range(), AnnRef::default())?);
+    let ranges = eval_as(ctx, transfer.range(), cast_ranges)?;
 
     let params = match transfer.params() {
-        Some(object) => Some(cast_object(eval_object(ctx, object, AnnRef::default())?)),
+        Some(object) => Some(eval_as(ctx, object.node(), cast_object)?),
         None => None,
     };
 
@@ -331,6 +549,10 @@ pub fn eval_transfer<'a>(
         summary,
         tags,
         id,
+        audience,
+        versions: versions(&ann),
+        deprecated: false,
+        span: transfer.node().span(),
     };
 
     let expr = Expr::Transfer(Box::new(xfer));
@@ -342,11 +564,11 @@ pub fn eval_relation<'a>(
     relation: syn::Relation<'a, Core>,
     ann: AnnRef,
 ) -> Result<(Expr<'a>, AnnRef)> {
-    let uri = cast_uri(eval_terminal(ctx, relation.uri(), AnnRef::default())?);
+    let uri = eval_as(ctx, relation.uri().node(), cast_uri)?;
 
     let mut xfers = Transfers::default();
     for x in relation.transfers() {
-        let xfer = cast_transfer(eval_any(ctx, x, AnnRef::default())?);
+        let xfer = eval_as(ctx, x, cast_transfer)?;
         for (m, b) in xfer.methods {
             if b {
                 xfers[m] = Some(xfer.clone());
@@ -354,30 +576,123 @@ pub fn eval_relation<'a>(
         }
     }
 
-    let rel = Relation { uri, xfers };
+    let rel = Relation {
+        uri,
+        xfers,
+        span: None,
+        audience: ann.get_enum("audience").unwrap_or_default(),
+        versions: versions(&ann),
+    };
     let expr = Expr::Relation(Box::new(rel));
     Ok((expr, ann))
 }
 
+/// Moves the URI parameters of a relation onto each of its transfers.
+fn move_params(rel: &mut Relation) {
+    let Some(params) = rel.uri.params.take() else {
+        return;
+    };
+    for (_, x) in rel.xfers.iter_mut() {
+        if let Some(x) = x {
+            let mut props = params.props.clone();
+            if let Some(p) = x.params.take() {
+                props.extend(p.props);
+            }
+            x.params = Some(Object { props });
+        }
+    }
+}
+
+/// Merges the transfers of a relation into another relation with the same path.
+///
+/// Fails if the paths differ in their variables or if both relations define a transfer
+/// for the same method. If the URI parameters differ, they are moved onto the transfers.
+fn merge_relations(target: &mut Relation, mut other: Relation) -> Result<()> {
+    let conflict = |msg: String| {
+        Error::new(Kind::ConflictingPath, msg)
+            .at(other.span.clone())
+            .label(target.span.clone(), "previously declared here")
+    };
+    let (path, other_path) = (target.uri.pattern(), other.uri.pattern());
+    if path != other_path {
+        return Err(conflict(format!(
+            "'{other_path}' is ambiguous with '{path}'"
+        )));
+    }
+    if target.uri.path != other.uri.path {
+        return Err(conflict(format!(
+            "'{path}' is declared with different variables"
+        )));
+    }
+    let overlap: Vec<_> = other
+        .xfers
+        .iter()
+        .filter(|(m, x)| x.is_some() && target.xfers[*m].is_some())
+        .map(|(m, _)| format!("{m:?}").to_uppercase())
+        .collect();
+    if !overlap.is_empty() {
+        let methods = overlap.join(", ");
+        return Err(conflict(format!(
+            "'{path}' is already declared for {methods}"
+        )));
+    }
+    if target.uri.params != other.uri.params {
+        move_params(target);
+        move_params(&mut other);
+    }
+    for (m, x) in other.xfers {
+        if x.is_some() {
+            target.xfers[m] = x;
+        }
+    }
+    Ok(())
+}
+
 pub fn eval_program<'a>(
     ctx: &mut Context<'a>,
     program: syn::Program<'a, Core>,
     ann: AnnRef,
 ) -> Result<(Expr<'a>, AnnRef)> {
-    let mut rels = Vec::new();
+    let mut rels: Vec<Relation> = Vec::new();
+    // The index of relations by path pattern, regardless of variable names.
+    let mut paths = HashMap::new();
     for res in program.resources() {
-        let rel = cast_relation(eval_any(ctx, res.relation(), AnnRef::default())?);
-        rels.push(rel);
+        // Resources are evaluated independently to report as many errors as possible.
+        let rel = eval_as(ctx, res.relation(), cast_relation);
+        if let Some(mut rel) = ctx.errors.check(rel) {
+            rel.span = res.node().span();
+            let path = rel.uri.pattern_with(|_| "{}".to_owned());
+            if let Some(&idx) = paths.get(&path) {
+                let merged = merge_relations(&mut rels[idx], rel);
+                ctx.errors.check(merged);
+            } else {
+                paths.insert(path, rels.len());
+                rels.push(rel);
+            }
+        }
+        if ctx.errors.is_full() {
+            break;
+        }
     }
 
     let mut refs = IndexMap::new();
     for (ident, value) in ctx.refs.iter() {
         if let Some((expr, ann)) = value {
-            // The type checker already asserts that all references are valid schemas.
-            refs.insert(
-                ident.clone(),
-                Reference::Schema(cast_schema((expr.clone(), ann.clone()))),
-            );
+            // The type checker already asserts that all references are valid schemas,
+            // contents or properties.
+            let value = (expr.clone(), ann.clone());
+            let reference = match expr {
+                Expr::Property(_) => Reference::Property(cast_property(value)?),
+                Expr::Content(_) => Reference::Content(cast_content(value)?),
+                Expr::Ranges(_) => {
+                    // Name the contents of the ranges as they are named at use sites.
+                    let mut ranges = cast_ranges(value)?;
+                    name_ranges(ident, &mut ranges);
+                    Reference::Ranges(ranges)
+                }
+                _ => Reference::Schema(cast_schema(value)?),
+            };
+            refs.insert(ident.clone(), reference);
         }
     }
 
@@ -402,7 +717,7 @@ pub fn eval_uri_template<'a>(
                 path.push(s);
             }
             syn::UriSegment::Variable(var) => {
-                let p = cast_property(eval_any(ctx, var.inner(), AnnRef::default())?);
+                let p = eval_as(ctx, var.inner(), cast_property)?;
                 let s = UriSegment::Variable(Box::new(p));
                 path.push(s);
             }
@@ -410,7 +725,7 @@ pub fn eval_uri_template<'a>(
     }
 
     let params = match template.params() {
-        Some(p) => Some(cast_object(eval_object(ctx, p, AnnRef::default())?)),
+        Some(p) => Some(eval_as(ctx, p.node(), cast_object)?),
         None => None,
     };
 
@@ -433,7 +748,12 @@ pub fn eval_declaration<'a>(
         let expr = Expr::Lambda(Lambda::External(decl));
         Ok((expr, ann))
     } else {
-        let mut rhs_ann = compose_annotations(decl.annotations())?;
+        let mut rhs_ann = compose_annotations(
+            &mut ctx.errors,
+            decl.annotations(),
+            decl.docs(),
+            annotation_context(decl.node()),
+        )?;
         rhs_ann.extend(ann.as_ref().clone());
         let rhs_ann = AnnRef::new(rhs_ann);
 
@@ -477,7 +797,9 @@ pub fn eval_binding<'a>(
 ) -> Result<(Expr<'a>, AnnRef)> {
     let ident = binding.ident();
     let Some((expr, prev_ann)) = ctx.lookup_binding(&ident) else {
-        panic!("binding '{}' should exist", ident)
+        return Err(Error::new(Kind::UnexpectedExpression, "unbound variable")
+            .with(&ident)
+            .at(binding.node().span()));
     };
     let mut next_ann = prev_ann.as_ref().clone();
     next_ann.extend(ann.as_ref().clone());
@@ -490,7 +812,11 @@ pub fn eval_variable<'a>(
     ann: AnnRef,
 ) -> Result<(Expr<'a>, AnnRef)> {
     let core = variable.node().syntax().core_ref();
-    let defn = core.definition().expect("variable is not defined");
+    let Some(defn) = core.definition() else {
+        return Err(Error::new(Kind::UnexpectedExpression, "undefined variable")
+            .with(&variable.ident())
+            .at(variable.node().span()));
+    };
     match defn {
         Definition::External(ext) => eval_any(ctx, ext.node(ctx.mods), ann),
         Definition::Internal(int) => {
@@ -514,7 +840,7 @@ pub fn eval_content<'a>(
 
     let schema = match content.body() {
         Some(body) => {
-            let s = cast_schema(eval_any(ctx, body, AnnRef::default())?);
+            let s = eval_as(ctx, body, cast_schema)?;
             Some(Box::new(s))
         }
         None => None,
@@ -528,14 +854,16 @@ pub fn eval_content<'a>(
     let mut media = None;
     let mut headers = None;
     for meta in content.meta().into_iter().flatten() {
-        let rhs = eval_any(ctx, meta.rhs(), AnnRef::default())?;
         match meta.kind() {
-            syn::ContentTagKind::Media => media = Some(cast_string(rhs)),
-            syn::ContentTagKind::Headers => headers = Some(cast_object(rhs)),
+            syn::ContentTagKind::Media => media = Some(eval_as(ctx, meta.rhs(), cast_string)?),
+            syn::ContentTagKind::Headers => headers = Some(eval_as(ctx, meta.rhs(), cast_object)?),
             syn::ContentTagKind::Status => {
-                let s = cast_http_status(rhs).map_err(|_| {
-                    Error::new(Kind::InvalidLiteral, "not a valid HTTP status")
-                        .at(meta.rhs().span())
+                let s = eval_as(ctx, meta.rhs(), cast_http_status).map_err(|e| {
+                    Error::new(
+                        Kind::InvalidLiteral,
+                        format!("not a valid HTTP status ({e})"),
+                    )
+                    .at(meta.rhs().span())
                 })?;
                 status = Some(s)
             }
@@ -549,6 +877,7 @@ pub fn eval_content<'a>(
         headers,
         desc,
         examples,
+        reference: None,
     };
 
     let expr = Expr::Content(Box::new(cnt));
@@ -562,7 +891,7 @@ pub fn eval_object<'a>(
 ) -> Result<(Expr<'a>, AnnRef)> {
     let mut props = Vec::new();
     for prop in object.properties() {
-        props.push(cast_property(eval_any(ctx, prop, AnnRef::default())?));
+        props.push(eval_as(ctx, prop, cast_property)?);
     }
     let obj = Object { props };
     let expr = Expr::Object(Box::new(obj));
@@ -578,14 +907,14 @@ pub fn eval_variadic_operation<'a>(
     let expr = if op == atom::VariadicOperator::Range {
         let mut ranges = Ranges::new();
         for operand in operation.operands() {
-            let r = cast_ranges(eval_any(ctx, operand, AnnRef::default())?);
+            let r = eval_as(ctx, operand, cast_ranges)?;
             ranges.extend(r.into_iter());
         }
         Expr::Ranges(Box::new(ranges))
     } else {
         let mut schemas = Vec::new();
         for operand in operation.operands() {
-            let s = cast_schema(eval_any(ctx, operand, AnnRef::default())?);
+            let s = eval_as(ctx, operand, cast_schema)?;
             schemas.push(s);
         }
         let var_op = VariadicOp { op, schemas };
@@ -599,7 +928,9 @@ pub fn eval_unary_operation<'a>(
     operation: syn::UnaryOp<'a, Core>,
     ann: AnnRef,
 ) -> Result<(Expr<'a>, AnnRef)> {
-    let mut prop = cast_property(eval_any(ctx, operation.operand(), AnnRef::default())?);
+    let mut prop = eval_as(ctx, operation.operand(), cast_property)?;
+    // The property differs from the referenced one, if any.
+    prop.reference = None;
     match operation.operator() {
         atom::UnaryOperator::Optional => prop.required = Some(false),
         atom::UnaryOperator::Required => prop.required = Some(true),
@@ -616,13 +947,16 @@ pub fn eval_literal<'a>(
     let expr = match literal.kind() {
         syn::LiteralKind::HttpStatus => {
             let lex::TokenValue::HttpStatus(status) = literal.value() else {
-                panic!("expected an HTTP status")
+                return Err(Error::new(Kind::InvalidLiteral, "expected an HTTP status")
+                    .at(literal.node().span()));
             };
             Expr::HttpStatus(*status)
         }
         syn::LiteralKind::Number => {
             let lex::TokenValue::Number(number) = literal.value() else {
-                panic!("expected a number")
+                return Err(
+                    Error::new(Kind::InvalidLiteral, "expected a number").at(literal.node().span())
+                );
             };
             Expr::Number(*number)
         }
@@ -641,15 +975,20 @@ pub fn eval_property<'a>(
 ) -> Result<(Expr<'a>, AnnRef)> {
     let desc = ann.get_string("description");
     let required = ann.get_bool("required").or_else(|| property.required());
+    let audience = ann.get_enum("audience").unwrap_or_default();
 
     let name = property.name();
-    let schema = cast_schema(eval_any(ctx, property.rhs(), AnnRef::default())?);
+    let schema = eval_as(ctx, property.rhs(), cast_schema)?;
 
     let prop = Property {
         name,
         schema,
         desc,
         required,
+        reference: None,
+        audience,
+        versions: versions(&ann),
+        deprecated: false,
     };
 
     let expr = Expr::Property(Box::new(prop));
@@ -709,7 +1048,7 @@ pub fn eval_array<'a>(
     array: syn::Array<'a, Core>,
     ann: AnnRef,
 ) -> Result<(Expr<'a>, AnnRef)> {
-    let schema = cast_schema(eval_any(ctx, array.inner(), AnnRef::default())?);
+    let schema = eval_as(ctx, array.inner(), cast_schema)?;
     let array = Array { item: schema };
     let expr = Expr::Array(Box::new(array));
     Ok((expr, ann))
@@ -720,7 +1059,7 @@ pub fn eval_application<'a>(
     app: syn::Application<'a, Core>,
     ann: AnnRef,
 ) -> Result<(Expr<'a>, AnnRef)> {
-    match cast_lambda(eval_variable(ctx, app.lambda(), AnnRef::default())?) {
+    match eval_as(ctx, app.lambda().node(), cast_lambda)? {
         Lambda::Internal(internal) => {
             let args = app
                 .arguments()
@@ -735,7 +1074,12 @@ pub fn eval_application<'a>(
                 scope.insert(binding.ident(), value);
             }
 
-            let mut app_ann = compose_annotations(decl.annotations())?;
+            let mut app_ann = compose_annotations(
+                &mut ctx.errors,
+                decl.annotations(),
+                decl.docs(),
+                annotation_context(decl.node()),
+            )?;
             app_ann.extend(ann.as_ref().clone());
             let app_ann = AnnRef::new(app_ann);
 
@@ -817,16 +1161,19 @@ pub fn eval_any<'a>(
     } else if let Some(rec) = syn::Recursion::cast(node) {
         eval_recursion(ctx, rec, ann)
     } else {
-        panic!("unexpected node: {node:#?}")
+        Err(Error::new(Kind::UnexpectedExpression, "not an expression")
+            .with(&node)
+            .at(node.span()))
     }
 }
 
-pub fn eval(mods: &ModuleSet) -> Result<Spec> {
+pub fn eval(mods: &ModuleSet) -> std::result::Result<Spec, Errors> {
     let ctx = &mut Context::new(mods);
     let ann = AnnRef::default();
-    let (expr, _) = eval_any(ctx, mods.main().root(), ann)?;
+    let root = mods.main().root();
+    let (expr, _) = eval_any(ctx, root, ann)?;
     let Expr::Spec(spec) = expr else {
-        panic!("expected a specification")
+        return Err(unexpected("a specification", &expr).at(root.span()).into());
     };
-    Ok(*spec)
+    std::mem::take(&mut ctx.errors).into_result(*spec)
 }
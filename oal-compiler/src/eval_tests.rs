@@ -1,7 +1,7 @@
 use crate::errors;
 use crate::inference::{check_complete, constrain, substitute, tag};
 use crate::resolve::resolve;
-use crate::spec::{Object, Reference, SchemaExpr, Spec, UriSegment};
+use crate::spec::{Object, Reference, SchemaExpr, Spec, Transfer, UriSegment};
 use crate::tests::mods_from;
 use crate::typecheck::{cycles_check, type_check};
 use oal_syntax::atom::{HttpStatus, Method, VariadicOperator};
@@ -114,6 +114,54 @@ fn eval_composed_annotation() -> anyhow::Result<()> {
     Ok(())
 }
 
+#[test]
+fn eval_doc_comments() -> anyhow::Result<()> {
+    let s = eval_check(
+        r#"
+        /// A *number*.
+        ///
+        /// With details.
+        let a = num;
+        /**
+         * A record.
+         */
+        let r = {
+            /// A property.
+            'prop a,
+            /// Overridden.
+            # description: "explicit"
+            'other a
+        };
+        res / on /// A transfer.
+            get -> <r>;
+    "#,
+    )?;
+
+    let p = s.rels.first().unwrap();
+    let x = p.xfers[Method::Get]
+        .as_ref()
+        .expect("expected transfer on HTTP GET");
+    assert_eq!(x.desc.as_ref().unwrap(), "A transfer.");
+
+    let c = x.ranges.values().next().unwrap();
+    let s = c.schema.as_ref().unwrap();
+    assert_eq!(s.desc.as_ref().unwrap(), "A record.");
+    let SchemaExpr::Object(ref o) = s.expr else {
+        panic!("expected an object")
+    };
+    let mut props = o.props.iter();
+    let p = props.next().unwrap();
+    assert_eq!(p.desc.as_ref().unwrap(), "A property.");
+    assert_eq!(
+        p.schema.desc.as_ref().unwrap(),
+        "A *number*.\n\nWith details."
+    );
+    let p = props.next().unwrap();
+    assert_eq!(p.desc.as_ref().unwrap(), "explicit");
+
+    Ok(())
+}
+
 #[test]
 fn eval_invalid_annotation() -> anyhow::Result<()> {
     let code = r#"
@@ -125,7 +173,8 @@ fn eval_invalid_annotation() -> anyhow::Result<()> {
     assert!(matches!(
         eval_check(code)
             .expect_err(format!("expected error evaluating: {}", code).as_str())
-            .downcast_ref::<errors::Error>()
+            .downcast_ref::<errors::Errors>()
+            .and_then(|errs| errs.first())
             .expect("expected compiler error")
             .kind,
         errors::Kind::Yaml(_)
@@ -134,6 +183,148 @@ fn eval_invalid_annotation() -> anyhow::Result<()> {
     Ok(())
 }
 
+#[test]
+fn eval_unknown_annotation() -> anyhow::Result<()> {
+    let code = r#"
+        let a = str `minLenght: 1`;
+        res / on get -> a;
+    "#;
+
+    let err = eval_check(code).expect_err("expected an unknown annotation");
+    let err = err
+        .downcast_ref::<errors::Errors>()
+        .and_then(|errs| errs.first())
+        .expect("expected compiler error");
+    assert!(matches!(err.kind, errors::Kind::UnknownAnnotation));
+    assert!(err.to_string().contains("did you mean 'minLength'?"));
+    let span = err.span().expect("expected a span");
+    assert_eq!(&code[span.range()], "minLenght");
+
+    let code = r#"
+        # operationID: xyz
+        let x = get -> {};
+        res / on x;
+    "#;
+
+    let err = eval_check(code).expect_err("expected an unknown annotation");
+    let err = err
+        .downcast_ref::<errors::Errors>()
+        .and_then(|errs| errs.first())
+        .expect("expected compiler error");
+    assert!(err.to_string().contains("did you mean 'operationId'?"));
+
+    let code = r#"
+        # summary: "not a schema annotation"
+        let r = {};
+        res / on get -> r;
+    "#;
+
+    assert!(matches!(
+        eval_check(code)
+            .expect_err("expected an unknown annotation")
+            .downcast_ref::<errors::Errors>()
+            .and_then(|errs| errs.first())
+            .expect("expected compiler error")
+            .kind,
+        errors::Kind::UnknownAnnotation
+    ));
+
+    let code = r#"
+        # x-internal: true
+        let r = { 'a str `x-nullable: true` };
+        res / on get -> r;
+    "#;
+
+    assert!(eval_check(code).is_ok());
+
+    Ok(())
+}
+
+#[test]
+fn eval_annotation_value_type() -> anyhow::Result<()> {
+    let code = r#"
+        let a = num `minimum: zero`;
+        res / on get -> a;
+    "#;
+
+    let err = eval_check(code).expect_err("expected an invalid annotation");
+    let err = err
+        .downcast_ref::<errors::Errors>()
+        .and_then(|errs| errs.first())
+        .expect("expected compiler error");
+    assert!(matches!(err.kind, errors::Kind::InvalidAnnotation));
+    assert!(err.to_string().contains("'minimum' expects a number"));
+    let span = err.span().expect("expected a span");
+    assert_eq!(&code[span.range()], "zero");
+
+    Ok(())
+}
+
+#[test]
+fn eval_all_invalid_annotations() -> anyhow::Result<()> {
+    let code = r#"
+        let a = str `minLenght: 1, enum: [a, b], maxLength: "many", patern: x`;
+        # minimum: [1, 2]
+        let b = int;
+        res / on get -> { 'a a, 'b b, 'c a };
+    "#;
+
+    let err = eval_check(code).expect_err("expected invalid annotations");
+    let errs = err
+        .downcast_ref::<errors::Errors>()
+        .expect("expected compiler errors");
+    let spans: Vec<_> = errs
+        .iter()
+        .map(|e| &code[e.span().expect("expected a span").range()])
+        .collect();
+    assert_eq!(spans, ["minLenght", "\"many\"", "patern", "[1, 2]"]);
+
+    Ok(())
+}
+
+#[test]
+fn eval_accumulate_errors() -> anyhow::Result<()> {
+    let code = r#"
+        res /a on get -> <status=999, {}>;
+        res /b on get -> {};
+        res /c on get -> <status=42, {}>;
+    "#;
+
+    let err = eval_check(code).expect_err("expected evaluation errors");
+    let errs = err
+        .downcast_ref::<errors::Errors>()
+        .expect("expected compiler errors");
+    assert_eq!(errs.len(), 2);
+    assert!(errs
+        .iter()
+        .all(|e| matches!(e.kind, errors::Kind::InvalidLiteral)));
+
+    Ok(())
+}
+
+#[test]
+fn eval_unexpected_expression() -> anyhow::Result<()> {
+    // Programs that pass the type checker but cannot be evaluated.
+    let cases = [
+        ("res / on put : (<{}> :: <{}>) -> {};", "(<{}> :: <{}>)"),
+        ("res / on get -> <headers=(rec x x), {}>;", "(rec x x)"),
+    ];
+
+    for (code, fragment) in cases {
+        let err =
+            eval_nocheck(code).expect_err(format!("expected error evaluating: {code}").as_str());
+        let err = err
+            .downcast_ref::<errors::Errors>()
+            .and_then(|errs| errs.first())
+            .expect("expected compiler error");
+        assert!(matches!(err.kind, errors::Kind::UnexpectedExpression));
+        let span = err.span().expect("expected a span");
+        assert_eq!(&code[span.range()], fragment);
+    }
+
+    Ok(())
+}
+
 #[test]
 fn eval_content() -> anyhow::Result<()> {
     let s = eval_check(
@@ -232,14 +423,16 @@ fn eval_invalid_status() -> anyhow::Result<()> {
         res / on get -> <status=999,{}>;
     "#;
 
-    assert!(matches!(
-        eval_check(code)
-            .expect_err(format!("expected error evaluating: {}", code).as_str())
-            .downcast_ref::<errors::Error>()
-            .expect("expected compiler error")
-            .kind,
-        errors::Kind::InvalidLiteral
-    ));
+    let err = eval_check(code).expect_err(format!("expected error evaluating: {}", code).as_str());
+    let err = err
+        .downcast_ref::<errors::Errors>()
+        .and_then(|errs| errs.first())
+        .expect("expected compiler error");
+    assert!(matches!(err.kind, errors::Kind::InvalidLiteral));
+    assert_eq!(
+        err.to_string(),
+        "invalid literal: not a valid HTTP status (invalid syntax: value not valid for the domain)"
+    );
 
     Ok(())
 }
@@ -438,7 +631,9 @@ fn eval_reference() -> anyhow::Result<()> {
 
     assert_eq!(s.refs.len(), 1);
 
-    let Reference::Schema(r) = s.refs.values().next().unwrap();
+    let Reference::Schema(r) = s.refs.values().next().unwrap() else {
+        panic!("expected a schema reference")
+    };
     let SchemaExpr::Object(o) = &r.expr else {
         panic!("expected an object")
     };
@@ -462,7 +657,9 @@ fn eval_reference_fallback() -> anyhow::Result<()> {
     assert_eq!(*p.uri.path.first().unwrap(), UriSegment::Literal("".into()));
 
     assert_eq!(s.refs.len(), 1);
-    let Reference::Schema(r) = s.refs.values().next().unwrap();
+    let Reference::Schema(r) = s.refs.values().next().unwrap() else {
+        panic!("expected a schema reference")
+    };
     let SchemaExpr::Uri(u) = &r.expr else {
         panic!("expected an URI")
     };
@@ -484,7 +681,8 @@ fn eval_identifier_duplicate() -> anyhow::Result<()> {
     assert!(matches!(
         eval_check(code)
             .expect_err(format!("expected error evaluating: {}", code).as_str())
-            .downcast_ref::<errors::Error>()
+            .downcast_ref::<errors::Errors>()
+            .and_then(|errs| errs.first())
             .expect("expected compiler error")
             .kind,
         errors::Kind::InvalidIdentifier
@@ -670,7 +868,9 @@ fn eval_single_recursion() -> anyhow::Result<()> {
     assert!(id1.as_ref().starts_with("hash-"));
     assert_eq!(id1, id2);
     let recursion = s.refs.get(id1).expect("reference should exist");
-    let Reference::Schema(schema) = recursion;
+    let Reference::Schema(schema) = recursion else {
+        panic!("expected a schema reference")
+    };
     let SchemaExpr::Array(_) = &schema.expr else {
         panic!("schema should be an array")
     };
@@ -707,7 +907,9 @@ fn eval_mutual_recursion() -> anyhow::Result<()> {
         panic!("range should be a reference")
     };
     let ref_a = s.refs.get(id_a).expect("reference should exist");
-    let Reference::Schema(schema) = ref_a;
+    let Reference::Schema(schema) = ref_a else {
+        panic!("expected a schema reference")
+    };
     let SchemaExpr::Object(obj) = &schema.expr else {
         panic!("schema should be an object")
     };
@@ -718,7 +920,9 @@ fn eval_mutual_recursion() -> anyhow::Result<()> {
         panic!("schema should be a reference")
     };
     let ref_b = s.refs.get(id_b).expect("reference should exist");
-    let Reference::Schema(schema) = ref_b;
+    let Reference::Schema(schema) = ref_b else {
+        panic!("expected a schema reference")
+    };
     let SchemaExpr::Object(obj) = &schema.expr else {
         panic!("schema should be an object")
     };
@@ -744,7 +948,8 @@ fn eval_recursive_lambda() -> anyhow::Result<()> {
     assert!(matches!(
         eval_check(code)
             .expect_err(format!("expected error evaluating: {}", code).as_str())
-            .downcast_ref::<errors::Error>()
+            .downcast_ref::<errors::Errors>()
+            .and_then(|errs| errs.first())
             .expect("expected compiler error")
             .kind,
         errors::Kind::InvalidType
@@ -781,7 +986,8 @@ fn eval_recursive_uri() -> anyhow::Result<()> {
     assert!(matches!(
         eval_check(code)
             .expect_err(format!("expected error evaluating: {}", code).as_str())
-            .downcast_ref::<errors::Error>()
+            .downcast_ref::<errors::Errors>()
+            .and_then(|errs| errs.first())
             .expect("expected compiler error")
             .kind,
         errors::Kind::InvalidType
@@ -869,3 +1075,84 @@ fn eval_internal() -> anyhow::Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn eval_duplicate_paths() -> anyhow::Result<()> {
+    let s = eval_check(
+        r#"
+        res /a/{ 'x str } on get -> {};
+        res /b on get -> {};
+        res /a/{ 'x str } on put : {} -> {};
+    "#,
+    )?;
+
+    assert_eq!(s.rels.len(), 2);
+    let rel = s.rels.first().unwrap();
+    assert_eq!(rel.uri.pattern(), "/a/{x}");
+    assert!(rel.xfers[Method::Get].is_some());
+    assert!(rel.xfers[Method::Put].is_some());
+
+    let cases = [
+        (
+            "res /a/{ 'x str } on get -> {}; res /a/{ 'y str } on put : {} -> {};",
+            "conflicting path: '/a/{y}' is ambiguous with '/a/{x}'",
+        ),
+        (
+            "res /a on get, put : {} -> {}; res /a on put : {} -> {};",
+            "conflicting path: '/a' is already declared for PUT",
+        ),
+        (
+            "res /a/{ 'x str } on get -> {}; res /a/{ 'x int } on put : {} -> {};",
+            "conflicting path: '/a/{x}' is declared with different variables",
+        ),
+    ];
+
+    for (code, msg) in cases {
+        let err =
+            eval_check(code).expect_err(format!("expected error evaluating: {code}").as_str());
+        let err = err
+            .downcast_ref::<errors::Errors>()
+            .and_then(|errs| errs.first())
+            .expect("expected compiler error");
+        assert!(matches!(err.kind, errors::Kind::ConflictingPath));
+        assert_eq!(err.to_string(), msg);
+        assert_eq!(err.labels().len(), 1);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn eval_duplicate_paths_params() -> anyhow::Result<()> {
+    let s = eval_check(
+        r#"
+        let a = {};
+        res /a?{ 'q str } on get -> a;
+        res /a?{ 'r int } on put : a -> a;
+        res /a?{ 'r int } on post : a -> a;
+        res /b?{ 'q str } on get -> a;
+        res /b?{ 'q str } on put : a -> a;
+    "#,
+    )?;
+
+    assert_eq!(s.rels.len(), 2);
+    let names = |x: &Option<Transfer>| {
+        let params = x.as_ref().unwrap().params.as_ref();
+        params.map_or(Vec::new(), |o| {
+            o.props.iter().map(|p| p.name.to_string()).collect()
+        })
+    };
+
+    let rel = &s.rels[0];
+    assert!(rel.uri.params.is_none());
+    assert_eq!(names(&rel.xfers[Method::Get]), ["q"]);
+    assert_eq!(names(&rel.xfers[Method::Put]), ["r"]);
+    assert_eq!(names(&rel.xfers[Method::Post]), ["r"]);
+
+    let rel = &s.rels[1];
+    let params = rel.uri.params.as_ref().expect("expected parameters");
+    assert_eq!(params.props.len(), 1);
+    assert!(names(&rel.xfers[Method::Get]).is_empty());
+
+    Ok(())
+}
@@ -0,0 +1,283 @@
+use crate::errors::{Error, Errors, Kind};
+use crate::spec::{
+    Content, Object, Property, Reference, Relation, Schema, SchemaExpr, Spec, Transfer, UriSegment,
+};
+use oal_model::span::Span;
+use oal_syntax::atom;
+use std::collections::HashSet;
+
+/// A predicate on the elements of a specification, deciding which ones are kept.
+pub(crate) trait Filter {
+    fn schema(&self, schema: &Schema) -> bool;
+    fn property(&self, prop: &Property) -> bool;
+    fn transfer(&self, xfer: &Transfer) -> bool;
+    fn relation(&self, rel: &Relation) -> bool;
+    /// Describes why an element is pruned, e.g. "not visible to the selected audiences".
+    fn exclusion(&self) -> String;
+}
+
+fn includes_property<F: Filter>(filter: &F, prop: &Property) -> bool {
+    filter.property(prop) && filter.schema(&prop.schema)
+}
+
+fn includes_reference<F: Filter>(filter: &F, reference: &Reference) -> bool {
+    match reference {
+        Reference::Schema(s) => filter.schema(s),
+        Reference::Property(p) => includes_property(filter, p),
+        Reference::Content(c) => c.schema.as_ref().is_none_or(|s| filter.schema(s)),
+        Reference::Ranges(_) => true,
+    }
+}
+
+/// Checks whether a property depends on a pruned reference,
+/// in which case it is kept for the dependency to be reported.
+fn depends_on_pruned(prop: &Property, pruned: &HashSet<atom::Ident>) -> bool {
+    let schema_ref = match &prop.schema.expr {
+        SchemaExpr::Ref(ident) => Some(ident),
+        _ => None,
+    };
+    prop.reference
+        .iter()
+        .chain(schema_ref)
+        .any(|i| pruned.contains(i))
+}
+
+fn prune_object<F: Filter>(filter: &F, pruned: &HashSet<atom::Ident>, obj: &mut Object) {
+    obj.props
+        .retain(|p| depends_on_pruned(p, pruned) || includes_property(filter, p));
+    for prop in obj.props.iter_mut() {
+        prune_schema(filter, pruned, &mut prop.schema);
+    }
+}
+
+fn prune_schema<F: Filter>(filter: &F, pruned: &HashSet<atom::Ident>, schema: &mut Schema) {
+    match &mut schema.expr {
+        SchemaExpr::Object(o) => prune_object(filter, pruned, o),
+        SchemaExpr::Array(a) => prune_schema(filter, pruned, &mut a.item),
+        SchemaExpr::Op(op) => op
+            .schemas
+            .iter_mut()
+            .for_each(|s| prune_schema(filter, pruned, s)),
+        SchemaExpr::Uri(u) => {
+            if let Some(params) = &mut u.params {
+                prune_object(filter, pruned, params);
+            }
+        }
+        SchemaExpr::Rel(r) => {
+            if let Some(params) = &mut r.uri.params {
+                prune_object(filter, pruned, params);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn prune_content<F: Filter>(filter: &F, pruned: &HashSet<atom::Ident>, content: &mut Content) {
+    if let Some(schema) = &mut content.schema {
+        prune_schema(filter, pruned, schema);
+    }
+    if let Some(headers) = &mut content.headers {
+        prune_object(filter, pruned, headers);
+    }
+}
+
+fn prune_transfer<F: Filter>(filter: &F, pruned: &HashSet<atom::Ident>, xfer: &mut Transfer) {
+    prune_content(filter, pruned, &mut xfer.domain);
+    xfer.ranges
+        .values_mut()
+        .for_each(|c| prune_content(filter, pruned, c));
+    if let Some(params) = &mut xfer.params {
+        prune_object(filter, pruned, params);
+    }
+}
+
+/// Prunes the transfers of a relation. Returns false if the relation itself is pruned.
+fn prune_relation<F: Filter>(
+    filter: &F,
+    pruned: &HashSet<atom::Ident>,
+    rel: &mut Relation,
+) -> bool {
+    if !filter.relation(rel) {
+        return false;
+    }
+    if let Some(params) = &mut rel.uri.params {
+        prune_object(filter, pruned, params);
+    }
+    let had_xfers = rel.xfers.values().any(Option::is_some);
+    for (_, slot) in rel.xfers.iter_mut() {
+        if slot.as_ref().is_some_and(|x| !filter.transfer(x)) {
+            *slot = None;
+        }
+        if let Some(xfer) = slot {
+            prune_transfer(filter, pruned, xfer);
+        }
+    }
+    !had_xfers || rel.xfers.values().any(Option::is_some)
+}
+
+/// Walks the elements kept by the selection, collecting the reachable references
+/// and reporting dependencies on pruned elements.
+struct Checker<'a, F> {
+    filter: &'a F,
+    spec: &'a Spec,
+    pruned: &'a HashSet<atom::Ident>,
+    reachable: HashSet<atom::Ident>,
+    errors: Errors,
+    /// The label and span of the kept element being walked.
+    user: (String, Option<Span>),
+}
+
+impl<'a, F: Filter> Checker<'a, F> {
+    fn report(&mut self, dep: String) {
+        let (user, span) = &self.user;
+        let msg = format!(
+            "{dep} is {} but '{user}' depends on it",
+            self.filter.exclusion()
+        );
+        self.errors
+            .push(Error::new(Kind::ExcludedDependency, msg).at(span.clone()));
+    }
+
+    fn reference(&mut self, ident: &atom::Ident) {
+        if self.pruned.contains(ident) {
+            self.report(format!("'{ident}'"));
+        } else if self.reachable.insert(ident.clone()) {
+            match self.spec.refs.get(ident) {
+                Some(Reference::Schema(s)) => self.schema(s),
+                Some(Reference::Content(c)) => self.content(c),
+                Some(Reference::Property(p)) => self.property(p),
+                Some(Reference::Ranges(r)) => r.values().for_each(|c| self.content(c)),
+                None => {}
+            }
+        }
+    }
+
+    fn schema(&mut self, schema: &Schema) {
+        if !self.filter.schema(schema) {
+            // Schemas obtained from a reference share the annotations of its declaration.
+            let dep = match &schema.expr {
+                SchemaExpr::Ref(ident) => format!("'{ident}'"),
+                _ => "a schema".to_owned(),
+            };
+            self.report(dep);
+            return;
+        }
+        match &schema.expr {
+            SchemaExpr::Ref(ident) => self.reference(ident),
+            SchemaExpr::Object(o) => self.object(o),
+            SchemaExpr::Array(a) => self.schema(&a.item),
+            SchemaExpr::Op(op) => op.schemas.iter().for_each(|s| self.schema(s)),
+            SchemaExpr::Uri(u) => self.uri(u),
+            SchemaExpr::Rel(r) => self.uri(&r.uri),
+            _ => {}
+        }
+    }
+
+    fn property(&mut self, prop: &Property) {
+        if let Some(ident) = prop.reference.as_ref().filter(|i| self.pruned.contains(*i)) {
+            self.report(format!("'{ident}'"));
+        } else if !depends_on_pruned(prop, self.pruned) && !includes_property(self.filter, prop) {
+            self.report(format!("property '{}'", prop.name));
+        } else {
+            if let Some(ident) = &prop.reference {
+                self.reference(ident);
+            }
+            self.schema(&prop.schema);
+        }
+    }
+
+    fn object(&mut self, obj: &Object) {
+        obj.props.iter().for_each(|p| self.property(p));
+    }
+
+    fn uri(&mut self, uri: &crate::spec::Uri) {
+        for segment in uri.path.iter() {
+            if let UriSegment::Variable(prop) = segment {
+                self.property(prop);
+            }
+        }
+        if let Some(params) = &uri.params {
+            self.object(params);
+        }
+    }
+
+    fn content(&mut self, content: &Content) {
+        if let Some(ident) = &content.reference {
+            self.reference(ident);
+        }
+        if let Some(schema) = &content.schema {
+            self.schema(schema);
+        }
+        if let Some(headers) = &content.headers {
+            self.object(headers);
+        }
+    }
+
+    fn relation(&mut self, rel: &Relation) {
+        let path = rel.uri.pattern();
+        self.user = (path.clone(), rel.span.clone());
+        self.uri(&rel.uri);
+        for (method, xfer) in rel.xfers.iter() {
+            let Some(xfer) = xfer else { continue };
+            let op = format!("{} {path}", format!("{method:?}").to_uppercase());
+            self.user = (op, rel.span.clone());
+            self.content(&xfer.domain);
+            xfer.ranges.values().for_each(|c| self.content(c));
+            if let Some(params) = &xfer.params {
+                self.object(params);
+            }
+        }
+    }
+}
+
+/// Prunes the elements of a specification that are not included by the filter.
+///
+/// References that are no longer reachable from any relation are removed.
+/// Fails if a kept element depends on a pruned element.
+pub(crate) fn apply<F: Filter>(spec: &mut Spec, filter: &F) -> std::result::Result<(), Errors> {
+    let pruned: HashSet<atom::Ident> = spec
+        .refs
+        .iter()
+        .filter(|(_, r)| !includes_reference(filter, r))
+        .map(|(i, _)| i.clone())
+        .collect();
+    spec.refs.retain(|i, _| !pruned.contains(i));
+    spec.rels
+        .retain_mut(|rel| prune_relation(filter, &pruned, rel));
+    for reference in spec.refs.values_mut() {
+        match reference {
+            Reference::Schema(s) => prune_schema(filter, &pruned, s),
+            Reference::Content(c) => prune_content(filter, &pruned, c),
+            Reference::Property(p) => prune_schema(filter, &pruned, &mut p.schema),
+            Reference::Ranges(r) => r
+                .values_mut()
+                .for_each(|c| prune_content(filter, &pruned, c)),
+        }
+    }
+
+    let mut checker = Checker {
+        filter,
+        spec,
+        pruned: &pruned,
+        reachable: HashSet::new(),
+        errors: Errors::new(),
+        user: Default::default(),
+    };
+    for rel in spec.rels.iter() {
+        checker.relation(rel);
+    }
+    let Checker {
+        reachable, errors, ..
+    } = checker;
+
+    // A ranges reference is reachable through the contents named after it.
+    spec.refs.retain(|ident, reference| match reference {
+        Reference::Ranges(r) => r
+            .values()
+            .filter_map(|c| c.reference.as_ref())
+            .any(|i| reachable.contains(i)),
+        _ => reachable.contains(ident),
+    });
+
+    errors.into_result(())
+}
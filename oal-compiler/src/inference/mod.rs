@@ -10,7 +10,7 @@ mod union_tests;
 #[cfg(test)]
 use crate::errors::{Error, Kind};
 
-use crate::definition::Definition;
+use crate::definition::{definition_label, Definition};
 use crate::errors::Result;
 use crate::module::ModuleSet;
 use crate::tree::{get_tag, set_tag};
@@ -104,18 +104,25 @@ pub fn tag(mods: &ModuleSet, loc: &Locator) -> Result<usize> {
 pub fn constrain(mods: &ModuleSet, loc: &Locator) -> Result<InferenceSet> {
     let module = mods.get(loc).expect("module not found");
     let mut set = InferenceSet::new();
+    let defn = |node| definition_label(mods, node);
 
     for node in module.root().descendants() {
         if let Some(rel) = syn::Relation::cast(node) {
-            set.push(get_tag(rel.uri().node()), Tag::Uri, rel.uri().node().span());
+            let uri = rel.uri().node();
+            set.push_with(get_tag(uri), Tag::Uri, uri.span(), defn(uri));
             for xfer in rel.transfers() {
-                set.push(get_tag(xfer), Tag::Transfer, xfer.span());
+                set.push_with(get_tag(xfer), Tag::Transfer, xfer.span(), defn(xfer));
             }
         } else if let Some(uri) = syn::UriTemplate::cast(node) {
             for seg in uri.segments() {
                 if let syn::UriSegment::Variable(var) = seg {
                     let tag = Tag::Property(Box::new(Tag::Primitive));
-                    set.push(get_tag(var.inner()), tag, var.inner().span())
+                    set.push_with(
+                        get_tag(var.inner()),
+                        tag,
+                        var.inner().span(),
+                        defn(var.inner()),
+                    )
                 }
             }
             if let Some(params) = uri.params() {
@@ -131,7 +138,7 @@ pub fn constrain(mods: &ModuleSet, loc: &Locator) -> Result<InferenceSet> {
                     syn::ContentTagKind::Media => Some(Tag::Text),
                     syn::ContentTagKind::Status => None,
                 } {
-                    set.push(get_tag(meta.rhs()), t, meta.rhs().span());
+                    set.push_with(get_tag(meta.rhs()), t, meta.rhs().span(), defn(meta.rhs()));
                 }
             }
         } else if let Some(xfer) = syn::Transfer::cast(node) {
@@ -145,7 +152,7 @@ pub fn constrain(mods: &ModuleSet, loc: &Locator) -> Result<InferenceSet> {
                     atom::VariadicOperator::Join => Some(Tag::Object),
                     atom::VariadicOperator::Sum => Some(get_tag(node)),
                 } {
-                    set.push(get_tag(operand), t, operand.span());
+                    set.push_with(get_tag(operand), t, operand.span(), defn(operand));
                 }
             }
         } else if let Some(op) = syn::UnaryOp::cast(node) {
@@ -168,10 +175,17 @@ pub fn constrain(mods: &ModuleSet, loc: &Locator) -> Result<InferenceSet> {
         } else if let Some(app) = syn::Application::cast(node) {
             let bindings = app.arguments().map(|a| get_tag(a.node())).collect();
             let range = get_tag(node).into();
-            let lambda = get_tag(app.lambda().node());
-            set.push(lambda, Tag::Func(FuncTag { bindings, range }), node.span());
+            let lambda = app.lambda().node();
+            let tag = Tag::Func(FuncTag { bindings, range });
+            set.push_with(get_tag(lambda), tag, node.span(), defn(lambda));
         } else if let Some(term) = syn::Terminal::cast(node) {
-            set.push(get_tag(node), get_tag(term.inner()), node.span());
+            // The inner tag is what is found, the terminal tag what is expected from the context.
+            set.push_with(
+                get_tag(term.inner()),
+                get_tag(node),
+                node.span(),
+                defn(term.inner()),
+            );
         } else if let Some(expr) = syn::SubExpression::cast(node) {
             set.push(get_tag(node), get_tag(expr.inner()), node.span());
         } else if let Some(expr) = syn::Recursion::cast(node) {
@@ -118,3 +118,23 @@ fn infer_unify() -> anyhow::Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn infer_mismatch() -> anyhow::Result<()> {
+    let code = "let a = {};\nlet b = a on get -> {};";
+    let (mods, _) = compile(code)?;
+
+    let eqs = constrain(&mods, mods.base())?;
+    let errs = eqs.unify().expect_err("expected a type mismatch");
+    assert_eq!(errs.len(), 1);
+
+    let err = errs.first().unwrap();
+    assert_eq!(err.expected_type(), Some("uri"));
+    assert_eq!(err.found_type(), Some("object"));
+    let (span, msg) = err.labels().first().expect("expected a label");
+    assert_eq!(&code[span.range()], "a");
+    assert_eq!(span.start(), 4);
+    assert_eq!(msg, "'a' is defined here");
+
+    Ok(())
+}
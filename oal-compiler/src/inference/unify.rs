@@ -1,6 +1,6 @@
 use super::tag::{FuncTag, Tag};
 use super::union;
-use crate::errors::{Error, Kind, Result};
+use crate::errors::{Error, Errors, Kind, Result};
 use oal_model::span::Span;
 
 fn occurs(a: &Tag, b: &Tag) -> bool {
@@ -47,7 +47,8 @@ fn unify(sets: &mut union::UnionFind, left: &Tag, right: &Tag) -> Result<()> {
     {
         if left_bindings.len() != right_bindings.len() {
             Err(Error::new(Kind::InvalidType, "function arity mismatch")
-                .with(&(left_bindings, right_bindings)))
+                .expected(format!("{} argument(s)", right_bindings.len()))
+                .found(format!("{} argument(s)", left_bindings.len())))
         } else {
             unify(sets, left_range, right_range).and_then(|_| {
                 left_bindings
@@ -59,10 +60,9 @@ fn unify(sets: &mut union::UnionFind, left: &Tag, right: &Tag) -> Result<()> {
     } else if let (Tag::Property(left_prop), Tag::Property(right_prop)) = (&left, &right) {
         unify(sets, left_prop, right_prop)
     } else {
-        Err(Error::new(
-            Kind::InvalidType,
-            format!("'{left}' does not match '{right}'"),
-        ))
+        Err(Error::new(Kind::InvalidType, "type mismatch")
+            .expected(&right)
+            .found(&left))
     }
 }
 
@@ -71,6 +71,8 @@ struct TypeEquation {
     pub left: Tag,
     pub right: Tag,
     pub span: Option<Span>,
+    /// The secondary labelled spans, e.g. where a variable was defined.
+    pub labels: Vec<(Span, String)>,
 }
 
 impl TypeEquation {
@@ -88,15 +90,33 @@ impl InferenceSet {
     }
 
     pub fn push(&mut self, left: Tag, right: Tag, span: Option<Span>) {
-        self.0.push(TypeEquation { left, right, span });
+        self.push_with(left, right, span, None);
     }
 
-    pub fn unify(&self) -> Result<union::UnionFind> {
+    /// Pushes an equation with secondary labelled spans.
+    pub fn push_with<I>(&mut self, left: Tag, right: Tag, span: Option<Span>, labels: I)
+    where
+        I: IntoIterator<Item = (Span, String)>,
+    {
+        let labels = labels.into_iter().collect();
+        self.0.push(TypeEquation {
+            left,
+            right,
+            span,
+            labels,
+        });
+    }
+
+    pub fn unify(&self) -> std::result::Result<union::UnionFind, Errors> {
         let mut sets = union::UnionFind::new();
+        let mut errs = Errors::new();
         for eq in self.0.iter() {
-            eq.unify(&mut sets).map_err(|err| err.at(eq.span.clone()))?;
+            errs.check(
+                eq.unify(&mut sets)
+                    .map_err(|err| err.at(eq.span.clone()).with_labels(eq.labels.clone())),
+            );
         }
-        Ok(sets)
+        errs.into_result(sets)
     }
 
     #[cfg(test)]
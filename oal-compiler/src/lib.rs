@@ -1,22 +1,39 @@
 mod annotation;
+pub mod audience;
 pub mod compile;
 pub mod definition;
+pub mod diff;
+pub mod document;
 mod env;
 pub mod errors;
 pub mod eval;
+mod filter;
 mod inference;
+pub mod lint;
 pub mod module;
+pub mod render;
 mod resolve;
 pub mod spec;
 mod stdlib;
+#[cfg(any(test, feature = "testing"))]
+pub mod tests;
 pub mod tree;
 mod typecheck;
+pub mod versioning;
 
+#[cfg(test)]
+mod audience_tests;
 #[cfg(test)]
 mod compile_tests;
 #[cfg(test)]
+mod diff_tests;
+#[cfg(test)]
+mod document_tests;
+#[cfg(test)]
 mod eval_tests;
 #[cfg(test)]
+mod lint_tests;
+#[cfg(test)]
 mod module_tests;
 #[cfg(test)]
 mod resolve_tests;
@@ -25,6 +42,6 @@ mod spec_tests;
 #[cfg(test)]
 mod stdlib_tests;
 #[cfg(test)]
-mod tests;
-#[cfg(test)]
 mod typecheck_tests;
+#[cfg(test)]
+mod versioning_tests;
@@ -0,0 +1,275 @@
+use crate::definition::{Definition, External};
+use crate::errors::{Error, Kind, Severity};
+use crate::module::ModuleSet;
+use crate::spec::{Relation, Spec};
+use crate::tree::{NRef, Tree};
+use enum_map::{Enum, EnumMap};
+use oal_model::grammar::AbstractSyntaxNode;
+use oal_model::locator::Locator;
+use oal_model::span::Span;
+use oal_syntax::atom;
+use oal_syntax::parser as syn;
+use serde::Deserialize;
+use std::collections::HashSet;
+use std::fmt::{Display, Formatter};
+
+/// A lint rule.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Enum, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Rule {
+    /// A declaration that is never referenced.
+    UnusedDeclaration,
+    /// An import from which no declaration is referenced.
+    UnusedImport,
+    /// A binding that hides a declaration or an enclosing binding.
+    ShadowedBinding,
+    /// A reference that is used but not reachable from any resource.
+    UnreachableReference,
+    /// A relation without any transfer.
+    EmptyRelation,
+    /// A response without description.
+    MissingDescription,
+    /// An operation without tags.
+    MissingTags,
+}
+
+impl Display for Rule {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Rule::UnusedDeclaration => "unused-declaration",
+            Rule::UnusedImport => "unused-import",
+            Rule::ShadowedBinding => "shadowed-binding",
+            Rule::UnreachableReference => "unreachable-reference",
+            Rule::EmptyRelation => "empty-relation",
+            Rule::MissingDescription => "missing-description",
+            Rule::MissingTags => "missing-tags",
+        };
+        f.write_str(name)
+    }
+}
+
+/// The level of a lint rule.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Level {
+    /// The rule is disabled.
+    Allow,
+    /// A violation of the rule is reported as a warning.
+    Warn,
+    /// A violation of the rule is reported as an error.
+    Deny,
+}
+
+/// The lint configuration, i.e. the level of each rule.
+#[derive(Clone, Debug)]
+pub struct Config(EnumMap<Rule, Level>);
+
+impl Default for Config {
+    fn default() -> Self {
+        Config(EnumMap::from_fn(|rule| match rule {
+            Rule::MissingDescription | Rule::MissingTags => Level::Allow,
+            _ => Level::Warn,
+        }))
+    }
+}
+
+impl Config {
+    /// Sets the level of a rule.
+    pub fn set(&mut self, rule: Rule, level: Level) {
+        self.0[rule] = level;
+    }
+
+    /// Returns the level of a rule.
+    pub fn level(&self, rule: Rule) -> Level {
+        self.0[rule]
+    }
+}
+
+impl<I: IntoIterator<Item = (Rule, Level)>> From<I> for Config {
+    fn from(levels: I) -> Self {
+        let mut config = Config::default();
+        for (rule, level) in levels {
+            config.set(rule, level);
+        }
+        config
+    }
+}
+
+struct Linter<'a> {
+    config: &'a Config,
+    diags: Vec<Error>,
+}
+
+impl<'a> Linter<'a> {
+    fn report<S: Into<String>>(&mut self, rule: Rule, msg: S, span: Option<Span>) {
+        let severity = match self.config.level(rule) {
+            Level::Allow => return,
+            Level::Warn => Severity::Warning,
+            Level::Deny => Severity::Error,
+        };
+        let err = Error::new(Kind::Lint(rule), msg)
+            .severity(severity)
+            .at(span);
+        self.diags.push(err);
+    }
+}
+
+/// Returns the modules in a stable order.
+fn modules(mods: &ModuleSet) -> Vec<&Tree> {
+    let mut trees: Vec<_> = mods.modules().collect();
+    trees.sort_by(|a, b| a.locator().url().as_str().cmp(b.locator().url().as_str()));
+    trees
+}
+
+fn program(tree: &Tree) -> syn::Program<'_, crate::tree::Core> {
+    syn::Program::cast(tree.root()).expect("module root must be a program")
+}
+
+/// Returns the external definition of a variable, if any.
+fn external(var: &syn::Variable<'_, crate::tree::Core>) -> Option<External> {
+    match var.node().syntax().core_ref().definition() {
+        Some(Definition::External(ext)) => Some(ext.clone()),
+        _ => None,
+    }
+}
+
+/// Returns the variables of a module, along with their external definition.
+fn variables(tree: &Tree) -> impl Iterator<Item = (NRef<'_>, External)> {
+    tree.root()
+        .descendants()
+        .filter_map(syn::Variable::cast)
+        .filter_map(|var| external(&var).map(|ext| (var.node(), ext)))
+}
+
+/// Returns the definitions referenced from outside of their own declaration.
+fn used_definitions(mods: &ModuleSet) -> HashSet<External> {
+    let mut used = HashSet::new();
+    for tree in modules(mods) {
+        for (node, ext) in variables(tree) {
+            let is_recursive = node.ancestors().any(|a| External::new(a) == ext);
+            if !is_recursive {
+                used.insert(ext);
+            }
+        }
+    }
+    used
+}
+
+fn lint_declarations(linter: &mut Linter, mods: &ModuleSet, used: &HashSet<External>) {
+    for tree in modules(mods) {
+        for decl in program(tree).declarations() {
+            if !used.contains(&External::new(decl.node())) {
+                let msg = format!("'{}' is never used", decl.ident());
+                linter.report(
+                    Rule::UnusedDeclaration,
+                    msg,
+                    decl.identifier().node().span(),
+                );
+            }
+        }
+    }
+}
+
+fn lint_imports(linter: &mut Linter, mods: &ModuleSet) {
+    for tree in modules(mods) {
+        let loc = tree.locator();
+        let used: HashSet<Locator> = variables(tree)
+            .map(|(_, ext)| ext.locator().clone())
+            .collect();
+        for import in program(tree).imports() {
+            // Invalid imports are already reported by the compiler.
+            let Ok(other) = loc.join(import.module()) else {
+                continue;
+            };
+            if !used.contains(&other) {
+                let msg = format!("nothing is used from '{}'", import.module());
+                linter.report(Rule::UnusedImport, msg, import.node().span());
+            }
+        }
+    }
+}
+
+fn lint_bindings(linter: &mut Linter, mods: &ModuleSet) {
+    for tree in modules(mods) {
+        let decls: HashSet<atom::Ident> = program(tree).declarations().map(|d| d.ident()).collect();
+        for binding in tree.root().descendants().filter_map(syn::Binding::cast) {
+            let ident = binding.ident();
+            let outer = binding.node().ancestors().skip(1).flat_map(|a| {
+                let decl = syn::Declaration::cast(a)
+                    .into_iter()
+                    .flat_map(|d| d.bindings());
+                let rec = syn::Recursion::cast(a).map(|r| r.binding());
+                decl.chain(rec)
+            });
+            let shadows = outer
+                .filter(|b| b.node().index() != binding.node().index())
+                .any(|b| b.ident() == ident);
+            if shadows || decls.contains(&ident) {
+                let msg = format!("'{ident}' shadows an existing definition");
+                linter.report(Rule::ShadowedBinding, msg, binding.node().span());
+            }
+        }
+    }
+}
+
+fn lint_references(linter: &mut Linter, mods: &ModuleSet, spec: &Spec, used: &HashSet<External>) {
+    for tree in modules(mods) {
+        for decl in program(tree).declarations() {
+            let ident = decl.ident();
+            if ident.is_reference()
+                && used.contains(&External::new(decl.node()))
+                && !spec.refs.contains_key(&ident)
+            {
+                let msg = format!("'{ident}' is not reachable from any resource");
+                linter.report(
+                    Rule::UnreachableReference,
+                    msg,
+                    decl.identifier().node().span(),
+                );
+            }
+        }
+    }
+}
+
+fn lint_relation(linter: &mut Linter, rel: &Relation) {
+    let span = &rel.span;
+    let path = rel.uri.pattern();
+    if rel.xfers.values().all(Option::is_none) {
+        let msg = format!("'{path}' has no transfer");
+        linter.report(Rule::EmptyRelation, msg, span.clone());
+    }
+    for (method, xfer) in rel.xfers.iter() {
+        let Some(xfer) = xfer else { continue };
+        let op = format!("{} {path}", format!("{method:?}").to_uppercase());
+        if xfer.ranges.values().any(|c| c.desc.is_none()) {
+            let msg = format!("'{op}' has a response without description");
+            linter.report(Rule::MissingDescription, msg, span.clone());
+        }
+        if xfer.tags.is_empty() {
+            let msg = format!("'{op}' has no tags");
+            linter.report(Rule::MissingTags, msg, span.clone());
+        }
+    }
+}
+
+/// Checks the module-set and its evaluated specification against the lint rules.
+///
+/// Returns the diagnostics for the enabled rules, as warnings or errors depending on their level.
+pub fn lint(mods: &ModuleSet, spec: &Spec, config: &Config) -> Vec<Error> {
+    let mut linter = Linter {
+        config,
+        diags: Vec::new(),
+    };
+
+    let used = used_definitions(mods);
+    lint_declarations(&mut linter, mods, &used);
+    lint_imports(&mut linter, mods);
+    lint_bindings(&mut linter, mods);
+    lint_references(&mut linter, mods, spec, &used);
+
+    for rel in spec.rels.iter() {
+        lint_relation(&mut linter, rel);
+    }
+
+    linter.diags
+}
@@ -0,0 +1,164 @@
+use crate::compile::compile;
+use crate::errors::{Error, Kind};
+use crate::lint::{lint, Config, Level, Rule};
+use crate::module::ModuleSet;
+use crate::tests::mods_from;
+use oal_model::locator::Locator;
+
+fn lint_mods(mods: &ModuleSet, config: &Config) -> anyhow::Result<Vec<Error>> {
+    compile(mods, mods.base())?;
+    let spec = crate::eval::eval(mods)?;
+    Ok(lint(mods, &spec, config))
+}
+
+fn rules(diags: &[Error]) -> Vec<Rule> {
+    diags
+        .iter()
+        .map(|d| match d.kind {
+            Kind::Lint(rule) => rule,
+            _ => panic!("expected a lint error"),
+        })
+        .collect()
+}
+
+#[test]
+fn lint_unused_declaration() -> anyhow::Result<()> {
+    let mods = mods_from(
+        r#"
+        let a = num;
+        let b = rec x { 'p x };
+        let c = /;
+        res c on get -> {};
+    "#,
+    )?;
+
+    let diags = lint_mods(&mods, &Config::default())?;
+
+    assert_eq!(
+        rules(&diags),
+        [Rule::UnusedDeclaration, Rule::UnusedDeclaration]
+    );
+    assert!(diags.iter().all(|d| d.is_warning()));
+    assert_eq!(
+        diags[0].to_string(),
+        "unused-declaration: 'a' is never used"
+    );
+    assert_eq!(
+        diags[1].to_string(),
+        "unused-declaration: 'b' is never used"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn lint_unused_import() -> anyhow::Result<()> {
+    let base = Locator::try_from("file:main.oal")?;
+    let (main, errs) = oal_syntax::parse(base, "use \"module.oal\";\nres / on get -> {};");
+    assert!(errs.is_empty());
+    let mut mods = ModuleSet::new(main.expect("parsing failed"));
+
+    let loc = Locator::try_from("file:module.oal")?;
+    let (module, errs) = oal_syntax::parse(loc.clone(), "let a = num;");
+    assert!(errs.is_empty());
+    mods.insert(module.expect("parsing failed"));
+    compile(&mods, &loc)?;
+
+    let config = Config::from([(Rule::UnusedDeclaration, Level::Allow)]);
+    let diags = lint_mods(&mods, &config)?;
+
+    assert_eq!(rules(&diags), [Rule::UnusedImport]);
+    assert_eq!(
+        diags[0].to_string(),
+        "unused-import: nothing is used from 'module.oal'"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn lint_shadowed_binding() -> anyhow::Result<()> {
+    let mods = mods_from(
+        r#"
+        let a = num;
+        let f a = a;
+        let g x = rec x { 'p x };
+        let h = g (f a);
+        res / on get -> h;
+    "#,
+    )?;
+
+    let diags = lint_mods(&mods, &Config::default())?;
+
+    assert_eq!(
+        rules(&diags),
+        [Rule::ShadowedBinding, Rule::ShadowedBinding]
+    );
+    assert_eq!(
+        diags[0].to_string(),
+        "shadowed-binding: 'a' shadows an existing definition"
+    );
+    assert_eq!(
+        diags[1].to_string(),
+        "shadowed-binding: 'x' shadows an existing definition"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn lint_unreachable_reference() -> anyhow::Result<()> {
+    let mods = mods_from(
+        r#"
+        let @a = {};
+        let b = @a;
+        let c = /;
+        res c on get -> {};
+    "#,
+    )?;
+
+    let config = Config::from([(Rule::UnusedDeclaration, Level::Allow)]);
+    let diags = lint_mods(&mods, &config)?;
+
+    assert_eq!(rules(&diags), [Rule::UnreachableReference]);
+    assert_eq!(
+        diags[0].to_string(),
+        "unreachable-reference: '@a' is not reachable from any resource"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn lint_relations() -> anyhow::Result<()> {
+    let code = r#"
+        res /a;
+        res /b on get -> <{}> `description: "some content"`;
+        # tags: [c]
+        let op = get -> {};
+        res /c on op;
+    "#;
+
+    let diags = lint_mods(&mods_from(code)?, &Config::default())?;
+
+    assert_eq!(rules(&diags), [Rule::EmptyRelation]);
+    assert_eq!(diags[0].to_string(), "empty-relation: '/a' has no transfer");
+
+    let config = Config::from([
+        (Rule::EmptyRelation, Level::Allow),
+        (Rule::MissingDescription, Level::Deny),
+        (Rule::MissingTags, Level::Warn),
+    ]);
+    let diags = lint_mods(&mods_from(code)?, &config)?;
+
+    assert_eq!(rules(&diags), [Rule::MissingTags, Rule::MissingDescription]);
+    assert!(diags[0].is_warning());
+    assert!(!diags[1].is_warning());
+    assert_eq!(diags[0].to_string(), "missing-tags: 'GET /b' has no tags");
+    assert_eq!(
+        diags[1].to_string(),
+        "missing-description: 'GET /c' has a response without description"
+    );
+
+    Ok(())
+}
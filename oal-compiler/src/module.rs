@@ -1,3 +1,4 @@
+use crate::document;
 use crate::errors::{Error, Kind};
 use crate::tree::Tree;
 use oal_model::grammar::AbstractSyntaxNode;
@@ -63,6 +64,10 @@ pub trait Loader<E: From<Error>> {
     fn parse(&mut self, loc: Locator, input: String) -> std::result::Result<Tree, E>;
     /// Compiles a module.
     fn compile(&mut self, mods: &ModuleSet, loc: &Locator) -> std::result::Result<(), E>;
+    /// Reports a warning raised while loading a module, e.g. when translating a schema document.
+    fn warn(&mut self, _loc: &Locator, _warning: Error) -> std::result::Result<(), E> {
+        Ok(())
+    }
 }
 
 /// Loads and compiles the set of modules for a main program.
@@ -84,8 +89,8 @@ where
     queue.push(root);
 
     while let Some(n) = queue.pop() {
-        let loc = graph.node_weight(n).unwrap();
-        let module = mods.get(loc).unwrap();
+        let loc = graph.node_weight(n).unwrap().clone();
+        let module = mods.get(&loc).unwrap();
 
         let mut imports = Vec::new();
         let prog = Program::cast(module.root()).expect("expected a program");
@@ -101,14 +106,23 @@ where
                         .into(),
                 );
             }
-            imports.push(target);
+            imports.push((target, span));
         }
 
-        for import in imports {
+        for (import, span) in imports {
             if let Some(m) = deps.get(&import) {
                 graph.add_edge(*m, n, ());
             } else {
-                let input = loader.load(&import)?;
+                let mut input = loader.load(&import)?;
+                // Schema documents are translated into modules of reference declarations.
+                if document::is_document(&import) {
+                    let translation =
+                        document::translate(&import, &input).map_err(|err| err.at(span.clone()))?;
+                    for warning in translation.warnings {
+                        loader.warn(&loc, warning.at(span.clone()))?;
+                    }
+                    input = translation.program;
+                }
                 let module = loader.parse(import.clone(), input)?;
                 mods.insert(module);
 
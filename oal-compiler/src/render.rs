@@ -0,0 +1,86 @@
+use indexmap::IndexMap;
+use serde_json::Value;
+
+/// A set of annotations, rendered as YAML flow mappings.
+#[derive(Clone, Default)]
+pub struct Annotations(Vec<(String, Value)>);
+
+impl Annotations {
+    pub fn push<V: Into<Value>>(&mut self, key: &str, value: V) {
+        self.0.push((key.to_owned(), value.into()));
+    }
+
+    /// Adds an extension annotation for a construct that has no equivalent in the language.
+    pub fn extension<T: serde::Serialize>(&mut self, key: &str, value: &T) {
+        let value = serde_json::to_value(value).expect("value should serialize");
+        self.0.push((format!("x-{key}"), value));
+    }
+
+    /// Adds the specification extensions of an object.
+    pub fn extend(&mut self, extensions: &IndexMap<String, Value>) {
+        self.0
+            .extend(extensions.iter().map(|(k, v)| (k.clone(), v.clone())));
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    fn entries(&self) -> impl Iterator<Item = String> + '_ {
+        self.0
+            .iter()
+            .map(|(k, v)| format!("{}: {}", render_key(k), render_value(v)))
+    }
+
+    /// Renders the annotations inline, with a leading space.
+    pub fn inline(&self) -> String {
+        if self.0.is_empty() {
+            String::new()
+        } else {
+            format!(" `{}`", self.entries().collect::<Vec<_>>().join(", "))
+        }
+    }
+
+    /// Renders the annotations as lines preceding a statement.
+    pub fn lines(&self, indent: &str) -> String {
+        self.entries().map(|e| format!("{indent}# {e}\n")).collect()
+    }
+}
+
+fn render_key(key: &str) -> String {
+    if !key.is_empty()
+        && key
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+    {
+        key.to_owned()
+    } else {
+        render_value(&Value::from(key))
+    }
+}
+
+/// Renders a value as JSON, which is valid YAML, escaping the delimiter of inline annotations.
+fn render_value(value: &Value) -> String {
+    value.to_string().replace('`', "\\u0060")
+}
+
+/// Checks whether a name can be used as is in a property.
+pub fn is_property_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "$@_-".contains(c))
+}
+
+/// Replaces the characters that are not allowed in identifiers.
+pub fn sanitize(name: &str) -> String {
+    name.chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || "$_-".contains(c) {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
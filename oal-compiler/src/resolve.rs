@@ -1,6 +1,6 @@
 use crate::definition::{Definition, External};
 use crate::env::{Entry, Env};
-use crate::errors::{Error, Kind, Result};
+use crate::errors::{Error, Errors, Kind, Result};
 use crate::module::ModuleSet;
 use crate::stdlib;
 use crate::tree::Core;
@@ -137,8 +137,10 @@ fn close_recursion(env: &mut Env) -> Result<()> {
     Ok(())
 }
 
-pub fn resolve(mods: &ModuleSet, loc: &Locator) -> Result<Graph> {
+/// Resolves variable and function references. Returns the graph of definitions.
+pub fn resolve(mods: &ModuleSet, loc: &Locator) -> std::result::Result<Graph, Errors> {
     let mut defg = Builder::default();
+    let mut errs = Errors::new();
 
     let env = &mut Env::new();
     stdlib::import(env)?;
@@ -146,10 +148,10 @@ pub fn resolve(mods: &ModuleSet, loc: &Locator) -> Result<Graph> {
     let tree = mods.get(loc).unwrap();
     let prog = Program::cast(tree.root()).expect("root should be a program");
     for import in prog.imports() {
-        declare_import(env, mods, loc, import)?;
+        errs.check(declare_import(env, mods, loc, import));
     }
     for decl in prog.declarations() {
-        declare_variable(env, decl)?;
+        errs.check(declare_variable(env, decl));
     }
 
     for cursor in tree.root().traverse() {
@@ -158,7 +160,7 @@ pub fn resolve(mods: &ModuleSet, loc: &Locator) -> Result<Graph> {
                 if let Some(decl) = Declaration::cast(node) {
                     open_declaration(env, &mut defg, decl)?;
                 } else if let Some(var) = Variable::cast(node) {
-                    define_variable(env, &mut defg, var)?;
+                    errs.check(define_variable(env, &mut defg, var));
                 } else if let Some(rec) = Recursion::cast(node) {
                     open_recursion(env, rec)?;
                 }
@@ -173,5 +175,5 @@ pub fn resolve(mods: &ModuleSet, loc: &Locator) -> Result<Graph> {
         }
     }
 
-    Ok(defg.graph())
+    errs.into_result(defg.graph())
 }
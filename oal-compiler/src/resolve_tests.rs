@@ -108,7 +108,8 @@ fn resolve_not_in_scope() -> anyhow::Result<()> {
     let mods = mods_from("let a = f {};")?;
 
     if let Err(e) = resolve(&mods, mods.base()) {
-        assert!(matches!(e.kind, Kind::NotInScope));
+        assert_eq!(e.len(), 1);
+        assert!(matches!(e[0].kind, Kind::NotInScope));
     } else {
         panic!("expected an error");
     }
@@ -116,6 +117,19 @@ fn resolve_not_in_scope() -> anyhow::Result<()> {
     Ok(())
 }
 
+#[test]
+fn resolve_accumulate_errors() -> anyhow::Result<()> {
+    let mods = mods_from("let a = f {}; let b = g {}; let a = {};")?;
+
+    let errs = resolve(&mods, mods.base()).expect_err("expected errors");
+    assert_eq!(errs.len(), 3);
+    assert!(matches!(errs[0].kind, Kind::InvalidIdentifier));
+    assert!(matches!(errs[1].kind, Kind::NotInScope));
+    assert!(matches!(errs[2].kind, Kind::NotInScope));
+
+    Ok(())
+}
+
 #[test]
 fn resolve_graph() -> anyhow::Result<()> {
     let mods = mods_from(
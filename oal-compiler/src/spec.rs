@@ -1,5 +1,7 @@
+use crate::versioning::Versions;
 use enum_map::EnumMap;
 use indexmap::IndexMap;
+use oal_model::span::Span;
 use oal_syntax::atom;
 use std::collections::HashMap;
 use std::fmt::Debug;
@@ -83,6 +85,11 @@ pub struct Schema {
     pub title: Option<String>,
     pub required: Option<bool>,
     pub examples: Option<HashMap<String, String>>,
+    /// The audiences the schema is restricted to, visible to all if empty.
+    pub audience: Vec<String>,
+    /// The versions the schema is available in.
+    pub versions: Versions,
+    pub deprecated: bool,
 }
 
 #[derive(Clone, Debug, PartialEq, Default)]
@@ -134,6 +141,25 @@ pub struct Property {
     pub schema: Schema,
     pub desc: Option<String>,
     pub required: Option<bool>,
+    /// The reference identifier the property was obtained from, if any.
+    pub reference: Option<atom::Ident>,
+    /// The audiences the property is restricted to, visible to all if empty.
+    pub audience: Vec<String>,
+    /// The versions the property is available in.
+    pub versions: Versions,
+    pub deprecated: bool,
+}
+
+impl Property {
+    /// Checks whether the property is required in an object, as stated by itself or by its schema.
+    pub fn is_required(&self) -> bool {
+        self.required.or(self.schema.required).unwrap_or(false)
+    }
+
+    /// Checks whether the property is required as a parameter or a header.
+    pub fn is_required_param(&self) -> bool {
+        self.required.unwrap_or(false)
+    }
 }
 
 #[derive(Clone, Debug, PartialEq, Default)]
@@ -151,6 +177,8 @@ pub struct Content {
     pub headers: Option<Object>,
     pub desc: Option<String>,
     pub examples: Option<HashMap<String, String>>,
+    /// The reference identifier the content was obtained from, if any.
+    pub reference: Option<atom::Ident>,
 }
 
 impl From<Schema> for Content {
@@ -161,6 +189,7 @@ impl From<Schema> for Content {
         let media = None;
         let headers = None;
         let examples = Default::default();
+        let reference = None;
         Content {
             schema,
             status,
@@ -168,12 +197,29 @@ impl From<Schema> for Content {
             headers,
             desc,
             examples,
+            reference,
         }
     }
 }
 
 pub type Ranges = IndexMap<(Option<atom::HttpStatus>, Option<MediaType>), Content>;
 
+/// Returns the label of an HTTP status, e.g. `404`, `4XX` or `default`.
+pub fn status_label(status: Option<atom::HttpStatus>) -> String {
+    match status {
+        Some(atom::HttpStatus::Code(code)) => code.to_string(),
+        Some(atom::HttpStatus::Range(range)) => match range {
+            atom::HttpStatusRange::Info => "1XX",
+            atom::HttpStatusRange::Success => "2XX",
+            atom::HttpStatusRange::Redirect => "3XX",
+            atom::HttpStatusRange::ClientError => "4XX",
+            atom::HttpStatusRange::ServerError => "5XX",
+        }
+        .to_owned(),
+        None => "default".to_owned(),
+    }
+}
+
 #[derive(Clone, Debug, PartialEq)]
 pub struct Transfer {
     pub methods: EnumMap<atom::Method, bool>,
@@ -184,14 +230,42 @@ pub struct Transfer {
     pub summary: Option<String>,
     pub tags: Vec<String>,
     pub id: Option<String>,
+    /// The audiences the transfer is restricted to, visible to all if empty.
+    pub audience: Vec<String>,
+    /// The versions the transfer is available in.
+    pub versions: Versions,
+    pub deprecated: bool,
+    /// The span of the transfer expression, if any.
+    pub span: Option<Span>,
 }
 
 pub type Transfers = EnumMap<atom::Method, Option<Transfer>>;
 
+/// Returns the label of an HTTP method, e.g. `GET`.
+pub fn method_label(m: atom::Method) -> &'static str {
+    match m {
+        atom::Method::Get => "GET",
+        atom::Method::Put => "PUT",
+        atom::Method::Post => "POST",
+        atom::Method::Patch => "PATCH",
+        atom::Method::Delete => "DELETE",
+        atom::Method::Options => "OPTIONS",
+        atom::Method::Head => "HEAD",
+        atom::Method::Trace => "TRACE",
+        atom::Method::Query => "QUERY",
+    }
+}
+
 #[derive(Clone, Debug, PartialEq)]
 pub struct Relation {
     pub uri: Uri,
     pub xfers: Transfers,
+    /// The span of the resource declaring the relation, if any.
+    pub span: Option<Span>,
+    /// The audiences the relation is restricted to, visible to all if empty.
+    pub audience: Vec<String>,
+    /// The versions the relation is available in.
+    pub versions: Versions,
 }
 
 impl From<Uri> for Relation {
@@ -199,6 +273,9 @@ impl From<Uri> for Relation {
         Relation {
             uri,
             xfers: Transfers::default(),
+            span: None,
+            audience: Vec::new(),
+            versions: Versions::default(),
         }
     }
 }
@@ -206,6 +283,9 @@ impl From<Uri> for Relation {
 #[derive(Clone, Debug, PartialEq)]
 pub enum Reference {
     Schema(Schema),
+    Content(Content),
+    Ranges(Ranges),
+    Property(Property),
 }
 
 pub type Relations = Vec<Relation>;
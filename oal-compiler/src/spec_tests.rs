@@ -32,9 +32,16 @@ fn uri_pattern() {
                                 title: None,
                                 required: None,
                                 examples: None,
+                                audience: Vec::new(),
+                                versions: Default::default(),
+                                deprecated: false,
                             },
                             desc: None,
                             required: None,
+                            reference: None,
+                            audience: Vec::new(),
+                            versions: Default::default(),
+                            deprecated: false,
                         }
                         .into(),
                     ),
@@ -62,9 +69,16 @@ fn make_param(name: &str) -> Object {
                 title: None,
                 required: None,
                 examples: None,
+                audience: Vec::new(),
+                versions: Default::default(),
+                deprecated: false,
             },
             desc: None,
             required: None,
+            reference: None,
+            audience: Vec::new(),
+            versions: Default::default(),
+            deprecated: false,
         }],
     }
 }
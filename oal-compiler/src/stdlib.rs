@@ -1,6 +1,6 @@
 use crate::definition::{Definition, Internal};
 use crate::env::Env;
-use crate::errors::Result;
+use crate::errors::{Error, Kind, Result};
 use crate::eval::{cast_uri, AnnRef, Expr, Value};
 use crate::inference::tag;
 use oal_syntax::atom::Ident;
@@ -24,9 +24,14 @@ impl Internal for Concat {
     }
 
     fn eval<'a>(&self, mut args: Vec<Value<'a>>, ann: AnnRef) -> Result<Value<'a>> {
-        assert_eq!(args.len(), 2);
-        let right = cast_uri(args.pop().unwrap());
-        let mut left = cast_uri(args.pop().unwrap());
+        let (Some(right), Some(left), None) = (args.pop(), args.pop(), args.pop()) else {
+            return Err(Error::new(
+                Kind::UnexpectedExpression,
+                "concat expects two arguments",
+            ));
+        };
+        let right = cast_uri(right)?;
+        let mut left = cast_uri(left)?;
         left.append(right);
         let expr = Expr::Uri(Box::new(left));
         Ok((expr, ann))
@@ -1,4 +1,5 @@
 use crate::module::ModuleSet;
+use crate::spec::Spec;
 use oal_model::locator::Locator;
 
 pub fn mods_from(code: &str) -> anyhow::Result<ModuleSet> {
@@ -13,3 +14,11 @@ pub fn mods_from(code: &str) -> anyhow::Result<ModuleSet> {
     let tree = tree.expect("expected a syntax tree");
     Ok(ModuleSet::new(tree))
 }
+
+/// Compiles and evaluates a program without imports.
+pub fn eval(code: &str) -> anyhow::Result<Spec> {
+    let mods = mods_from(code)?;
+    crate::compile::compile(&mods, mods.base())?;
+    let spec = crate::eval::eval(&mods)?;
+    Ok(spec)
+}
@@ -1,4 +1,5 @@
-use crate::errors::{Error, Kind, Result};
+use crate::definition::definition_label;
+use crate::errors::{Error, Errors, Kind, Result};
 use crate::inference::tag::Tag;
 use crate::module::ModuleSet;
 use crate::resolve::Graph;
@@ -71,185 +72,268 @@ fn get_tag(n: NRef) -> TagWrap {
     TagWrap(crate::tree::get_tag(n))
 }
 
-fn check_variadic_operation(op: syn::VariadicOp<Core>) -> Result<()> {
-    match op.operator() {
-        atom::VariadicOperator::Join => {
-            if !op.operands().all(|o| get_tag(o).is_object()) {
-                return Err(Error::new(Kind::InvalidType, "ill-formed join").with(&op));
-            }
-        }
+/// The tags of schemas.
+fn schema_tags() -> Vec<Tag> {
+    vec![
+        Tag::Primitive,
+        Tag::Relation,
+        Tag::Object,
+        Tag::Array,
+        Tag::Uri,
+        Tag::Any,
+    ]
+}
+
+/// The tags of schemas and contents.
+fn content_like_tags() -> Vec<Tag> {
+    let mut tags = schema_tags();
+    tags.push(Tag::Content);
+    tags
+}
+
+/// The tag of properties, whatever their schema.
+fn property_tag() -> Tag {
+    Tag::Property(Box::new(Tag::Any))
+}
+
+/// Describes a list of alternative tags, e.g. `text, number or uri`.
+fn describe(tags: &[Tag]) -> String {
+    let names: Vec<_> = tags.iter().map(Tag::to_string).collect();
+    match names.split_last() {
+        Some((last, init)) if !init.is_empty() => format!("{} or {last}", init.join(", ")),
+        _ => names.concat(),
+    }
+}
+
+/// Returns a type error for the given offending node.
+fn mismatch(mods: &ModuleSet, msg: &str, expected: &[Tag], node: NRef) -> Error {
+    let found = get_tag(node).0;
+    Error::new(Kind::InvalidType, msg)
+        .expected(describe(expected))
+        .found(&found)
+        .label(node.span(), format!("this is {found}"))
+        .with_labels(definition_label(mods, node))
+}
+
+fn check_variadic_operation(mods: &ModuleSet, op: syn::VariadicOp<Core>) -> Result<()> {
+    let (msg, expected, is_valid): (_, _, fn(&TagWrap) -> bool) = match op.operator() {
+        atom::VariadicOperator::Join => ("ill-formed join", vec![Tag::Object], TagWrap::is_object),
         atom::VariadicOperator::Any | atom::VariadicOperator::Sum => {
-            if !op.operands().all(|o| get_tag(o).is_schema()) {
-                return Err(Error::new(Kind::InvalidType, "ill-formed alternative").with(&op));
-            }
-        }
-        atom::VariadicOperator::Range => {
-            if !op.operands().all(|o| get_tag(o).is_content_like()) {
-                return Err(Error::new(Kind::InvalidType, "ill-formed ranges").with(&op));
-            }
+            ("ill-formed alternative", schema_tags(), TagWrap::is_schema)
         }
+        atom::VariadicOperator::Range => (
+            "ill-formed ranges",
+            content_like_tags(),
+            TagWrap::is_content_like,
+        ),
+    };
+    if let Some(o) = op.operands().find(|o| !is_valid(&get_tag(*o))) {
+        return Err(mismatch(mods, msg, &expected, o));
     }
     Ok(())
 }
 
-fn check_unary_operation(op: syn::UnaryOp<Core>) -> Result<()> {
+fn check_unary_operation(mods: &ModuleSet, op: syn::UnaryOp<Core>) -> Result<()> {
     match op.operator() {
         atom::UnaryOperator::Optional | atom::UnaryOperator::Required => {
             if !get_tag(op.operand()).is_property() {
-                return Err(Error::new(Kind::InvalidType, "ill-formed optionality").with(&op));
+                let msg = "ill-formed optionality";
+                return Err(mismatch(mods, msg, &[property_tag()], op.operand()));
             }
         }
     }
     Ok(())
 }
 
-fn check_content(content: syn::Content<Core>) -> Result<()> {
+fn check_content(mods: &ModuleSet, content: syn::Content<Core>) -> Result<()> {
     for meta in content.meta().into_iter().flatten() {
+        let rhs = meta.rhs();
         match meta.kind() {
             syn::ContentTagKind::Media => {
-                if !get_tag(meta.rhs()).is_text() {
-                    return Err(Error::new(Kind::InvalidType, "ill-formed media").with(&meta));
+                if !get_tag(rhs).is_text() {
+                    return Err(mismatch(mods, "ill-formed media", &[Tag::Text], rhs));
                 }
             }
             syn::ContentTagKind::Headers => {
-                if !get_tag(meta.rhs()).is_schema() {
-                    return Err(Error::new(Kind::InvalidType, "ill-formed headers").with(&meta));
+                if !get_tag(rhs).is_schema() {
+                    return Err(mismatch(mods, "ill-formed headers", &schema_tags(), rhs));
                 }
             }
             syn::ContentTagKind::Status => {
-                if !get_tag(meta.rhs()).is_status_like() {
-                    return Err(Error::new(Kind::InvalidType, "ill-formed status").with(&meta));
+                if !get_tag(rhs).is_status_like() {
+                    return Err(mismatch(
+                        mods,
+                        "ill-formed status",
+                        &[Tag::Status, Tag::Number],
+                        rhs,
+                    ));
                 }
             }
         }
     }
     if let Some(body) = content.body() {
         if !get_tag(body).is_schema() {
-            return Err(Error::new(Kind::InvalidType, "ill-formed body").with(&content));
+            return Err(mismatch(mods, "ill-formed body", &schema_tags(), body));
         }
     }
     Ok(())
 }
 
-fn check_transfer(xfer: syn::Transfer<Core>) -> Result<()> {
+fn check_transfer(mods: &ModuleSet, xfer: syn::Transfer<Core>) -> Result<()> {
     if let Some(domain) = xfer.domain() {
         if !get_tag(domain.inner()).is_content_like() {
-            return Err(Error::new(Kind::InvalidType, "ill-formed domain").with(&domain));
+            return Err(mismatch(
+                mods,
+                "ill-formed domain",
+                &content_like_tags(),
+                domain.node(),
+            ));
         }
     }
     if !get_tag(xfer.range()).is_content_like() {
-        return Err(Error::new(Kind::InvalidType, "ill-formed range").with(&xfer.range()));
+        return Err(mismatch(
+            mods,
+            "ill-formed range",
+            &content_like_tags(),
+            xfer.range(),
+        ));
     }
     Ok(())
 }
 
-fn check_relation(relation: syn::Relation<Core>) -> Result<()> {
-    if !get_tag(relation.uri().inner()).is_uri() {
-        return Err(Error::new(Kind::InvalidType, "ill-formed uri").with(&relation.uri()));
+fn check_relation(mods: &ModuleSet, relation: syn::Relation<Core>) -> Result<()> {
+    let uri = relation.uri();
+    if !get_tag(uri.inner()).is_uri() {
+        return Err(mismatch(mods, "ill-formed uri", &[Tag::Uri], uri.node()));
     }
-    if !relation.transfers().all(|t| get_tag(t).is_transfer()) {
-        return Err(Error::new(Kind::InvalidType, "ill-formed transfers").with(&relation));
+    if let Some(t) = relation.transfers().find(|t| !get_tag(*t).is_transfer()) {
+        return Err(mismatch(mods, "ill-formed transfers", &[Tag::Transfer], t));
     }
     Ok(())
 }
 
-fn check_uri(uri: syn::UriTemplate<Core>) -> Result<()> {
-    if !uri.segments().all(|s| match s {
-        syn::UriSegment::Element(_) => true,
-        syn::UriSegment::Variable(v) => get_tag(v.inner()).is_primitive_property(),
-    }) {
-        return Err(Error::new(Kind::InvalidType, "ill-formed uri").with(&uri));
+fn check_uri(mods: &ModuleSet, uri: syn::UriTemplate<Core>) -> Result<()> {
+    for s in uri.segments() {
+        if let syn::UriSegment::Variable(v) = s {
+            if !get_tag(v.inner()).is_primitive_property() {
+                let expected = Tag::Property(Box::new(Tag::Primitive));
+                return Err(mismatch(mods, "ill-formed uri", &[expected], v.inner()));
+            }
+        }
     }
     Ok(())
 }
 
-fn check_array(array: syn::Array<Core>) -> Result<()> {
+fn check_array(mods: &ModuleSet, array: syn::Array<Core>) -> Result<()> {
     if !get_tag(array.inner()).is_schema() {
-        return Err(Error::new(Kind::InvalidType, "ill-formed array").with(&array));
+        return Err(mismatch(
+            mods,
+            "ill-formed array",
+            &schema_tags(),
+            array.inner(),
+        ));
     }
     Ok(())
 }
 
-fn check_property(prop: syn::Property<Core>) -> Result<()> {
+fn check_property(mods: &ModuleSet, prop: syn::Property<Core>) -> Result<()> {
     if !get_tag(prop.rhs()).is_schema() {
-        return Err(Error::new(Kind::InvalidType, "ill-formed property").with(&prop));
+        return Err(mismatch(
+            mods,
+            "ill-formed property",
+            &schema_tags(),
+            prop.rhs(),
+        ));
     }
     Ok(())
 }
 
-fn check_object(object: syn::Object<Core>) -> Result<()> {
-    if !object.properties().all(|p| get_tag(p).is_property()) {
-        return Err(Error::new(Kind::InvalidType, "ill-formed object").with(&object));
+fn check_object(mods: &ModuleSet, object: syn::Object<Core>) -> Result<()> {
+    if let Some(p) = object.properties().find(|p| !get_tag(*p).is_property()) {
+        return Err(mismatch(mods, "ill-formed object", &[property_tag()], p));
     }
     Ok(())
 }
 
-fn check_declaration(decl: syn::Declaration<Core>) -> Result<()> {
-    let rhs = get_tag(decl.rhs());
-    if decl.ident().is_reference() && !rhs.is_schema() {
-        return Err(
-            Error::new(Kind::InvalidType, "ill-formed reference, not a schema").with(&decl),
-        );
+fn check_declaration(mods: &ModuleSet, decl: syn::Declaration<Core>) -> Result<()> {
+    let tag = get_tag(decl.rhs());
+    if decl.ident().is_reference() && !tag.is_content_like() && !tag.is_property() {
+        let msg = "ill-formed reference, not a schema, content or property";
+        let mut expected = content_like_tags();
+        expected.push(property_tag());
+        return Err(mismatch(mods, msg, &expected, decl.rhs()));
     }
     Ok(())
 }
 
-fn check_resource(res: syn::Resource<Core>) -> Result<()> {
+fn check_resource(mods: &ModuleSet, res: syn::Resource<Core>) -> Result<()> {
     if !get_tag(res.relation()).is_relation_like() {
-        return Err(Error::new(Kind::InvalidType, "ill-formed resource").with(&res));
+        return Err(mismatch(
+            mods,
+            "ill-formed resource",
+            &[Tag::Relation, Tag::Uri],
+            res.relation(),
+        ));
     }
     Ok(())
 }
 
-fn check_recursion(rec: syn::Recursion<Core>) -> Result<()> {
+fn check_recursion(mods: &ModuleSet, rec: syn::Recursion<Core>) -> Result<()> {
     let tag = get_tag(rec.node());
     // TODO: support for recursive URI definitions (i.e. self-reference via query string)
     if !tag.is_schema() || tag.is_uri() {
-        return Err(Error::new(Kind::InvalidType, "ill-formed recursion, not a schema").with(&rec));
+        let msg = "ill-formed recursion, not a schema";
+        let expected: Vec<_> = schema_tags()
+            .into_iter()
+            .filter(|t| *t != Tag::Uri)
+            .collect();
+        return Err(mismatch(mods, msg, &expected, rec.rhs()));
     }
     Ok(())
 }
 
-/// Returns an error if there is at least one type mismatch.
-pub fn type_check(mods: &ModuleSet, loc: &Locator) -> Result<()> {
+/// Returns the errors for all type mismatches, if any.
+pub fn type_check(mods: &ModuleSet, loc: &Locator) -> std::result::Result<(), Errors> {
     let module = mods.get(loc).expect("module not found");
+    let mut errs = Errors::new();
 
     for node in module.root().descendants() {
-        if let Some(operation) = syn::VariadicOp::cast(node) {
-            check_variadic_operation(operation)
+        let res = if let Some(operation) = syn::VariadicOp::cast(node) {
+            check_variadic_operation(mods, operation)
         } else if let Some(operation) = syn::UnaryOp::cast(node) {
-            check_unary_operation(operation)
+            check_unary_operation(mods, operation)
         } else if let Some(content) = syn::Content::cast(node) {
-            check_content(content)
+            check_content(mods, content)
         } else if let Some(xfer) = syn::Transfer::cast(node) {
-            check_transfer(xfer)
+            check_transfer(mods, xfer)
         } else if let Some(relation) = syn::Relation::cast(node) {
-            check_relation(relation)
+            check_relation(mods, relation)
         } else if let Some(uri) = syn::UriTemplate::cast(node) {
-            check_uri(uri)
+            check_uri(mods, uri)
         } else if let Some(array) = syn::Array::cast(node) {
-            check_array(array)
+            check_array(mods, array)
         } else if let Some(prop) = syn::Property::cast(node) {
-            check_property(prop)
+            check_property(mods, prop)
         } else if let Some(object) = syn::Object::cast(node) {
-            check_object(object)
+            check_object(mods, object)
         } else if let Some(decl) = syn::Declaration::cast(node) {
-            check_declaration(decl)
+            check_declaration(mods, decl)
         } else if let Some(res) = syn::Resource::cast(node) {
-            check_resource(res)
+            check_resource(mods, res)
         } else if let Some(rec) = syn::Recursion::cast(node) {
-            check_recursion(rec)
+            check_recursion(mods, rec)
         } else {
             Ok(())
-        }
-        .map_err(|err| err.at(node.span()))?;
+        };
+        errs.check(res.map_err(|err| err.at(node.span())));
     }
 
-    Ok(())
+    errs.into_result(())
 }
 
 /// Validates points of recursion in the graph of definitions.
-pub fn cycles_check(mut graph: Graph, mods: &ModuleSet) -> Result<()> {
+pub fn cycles_check(mut graph: Graph, mods: &ModuleSet) -> std::result::Result<(), Errors> {
+    let mut errs = Errors::new();
     let mut has_changed = true; // whether the graph has changed and another iteration is required.
     let mut inbounds = Vec::new(); // to avoid an allocation on each iteration.
 
@@ -288,7 +372,13 @@ pub fn cycles_check(mut graph: Graph, mods: &ModuleSet) -> Result<()> {
                 let index = component.first().expect("component should not be empty");
                 let ext = graph.node_weight(*index).expect("node should exist");
                 let node = ext.node(mods);
-                return Err(Error::new(Kind::InvalidType, "ill-formed recursion").at(node.span()));
+                errs.push(Error::new(Kind::InvalidType, "ill-formed recursion").at(node.span()));
+                // Detach the component to check the remaining definitions independently.
+                for index in component.iter() {
+                    for e in graph.edges_directed(*index, Incoming) {
+                        inbounds.push(e.id())
+                    }
+                }
             }
         }
         if !inbounds.is_empty() {
@@ -301,5 +391,5 @@ pub fn cycles_check(mut graph: Graph, mods: &ModuleSet) -> Result<()> {
             graph.remove_edge(e);
         }
     }
-    Ok(())
+    errs.into_result(())
 }
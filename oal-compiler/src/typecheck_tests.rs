@@ -31,6 +31,9 @@ fn typecheck_ok() {
         "let a = 'q str; let b = /path/{a};",
         r#"let a = <status=200, media="text/plain", headers={ 'h str }, str>;"#,
         "let @a = {};",
+        "let @a = <status=404, {}>;",
+        "let @a = <{}> :: <>;",
+        "let @a = 'p str;",
         "res /;",
         "res / on delete -> <>;",
         "let a = ('prop str) !;",
@@ -66,10 +69,67 @@ fn typecheck_error() {
         assert!(matches!(
             compile(c)
                 .expect_err(format!("expected error evaluating: {}", c).as_str())
-                .downcast_ref::<errors::Error>()
+                .downcast_ref::<errors::Errors>()
+                .and_then(|errs| errs.first())
                 .expect("expected compiler error")
                 .kind,
             errors::Kind::InvalidType
         ));
     }
 }
+
+#[test]
+fn typecheck_accumulate_errors() {
+    let code = "let a = <> ~ {}; let b = [<>]; res num;";
+
+    let err = compile(code).expect_err("expected type errors");
+    let errs = err
+        .downcast_ref::<errors::Errors>()
+        .expect("expected compiler errors");
+    assert_eq!(errs.len(), 3);
+    assert!(errs
+        .iter()
+        .all(|e| matches!(e.kind, errors::Kind::InvalidType)));
+}
+
+#[test]
+fn typecheck_error_details() {
+    let code = "let a = <>;\nlet b = [a];";
+    let err = compile(code).expect_err("expected a type error");
+    let err = err
+        .downcast_ref::<errors::Errors>()
+        .and_then(|errs| errs.first())
+        .expect("expected compiler error");
+    assert_eq!(
+        err.expected_type(),
+        Some("primitive, relation, object, array, uri or any")
+    );
+    assert_eq!(err.found_type(), Some("content"));
+    let labels = err
+        .labels()
+        .iter()
+        .map(|(s, m)| (&code[s.range()], m.as_str()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        labels,
+        vec![("a", "this is content"), ("a", "'a' is defined here")]
+    );
+
+    let code = "let a = {};\nres a;";
+    let err = compile(code).expect_err("expected a type error");
+    let err = err
+        .downcast_ref::<errors::Errors>()
+        .and_then(|errs| errs.first())
+        .expect("expected compiler error");
+    assert_eq!(err.expected_type(), Some("relation or uri"));
+    assert_eq!(err.found_type(), Some("object"));
+    let labels = err
+        .labels()
+        .iter()
+        .map(|(s, m)| (s.start(), m.as_str()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        labels,
+        vec![(16, "this is object"), (4, "'a' is defined here")]
+    );
+}
@@ -0,0 +1,198 @@
+use crate::errors::Errors;
+use crate::filter::{self, Filter};
+use crate::spec::{
+    Content, Object, Property, Reference, Relation, Schema, SchemaExpr, Spec, Transfer, Uri,
+    UriSegment,
+};
+use std::cmp::Ordering;
+use std::fmt::{Display, Formatter};
+
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum Segment {
+    Num(u64),
+    Text(String),
+}
+
+/// An API version, e.g. `2` or `1.3`, compared segment by segment.
+///
+/// Numeric segments compare as numbers and missing trailing segments as zeros.
+#[derive(Clone, Debug)]
+pub struct Version(Box<str>);
+
+impl From<&str> for Version {
+    fn from(s: &str) -> Self {
+        Version(s.trim().into())
+    }
+}
+
+impl Display for Version {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl Version {
+    fn segments(&self) -> impl Iterator<Item = Segment> + '_ {
+        let text = self.0.strip_prefix('v').unwrap_or(&self.0);
+        text.split('.').map(|seg| match seg.parse() {
+            Ok(n) => Segment::Num(n),
+            Err(_) => Segment::Text(seg.to_owned()),
+        })
+    }
+}
+
+impl Ord for Version {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let (mut a, mut b) = (self.segments(), other.segments());
+        loop {
+            let (sa, sb) = match (a.next(), b.next()) {
+                (None, None) => return Ordering::Equal,
+                (sa, sb) => (sa.unwrap_or(Segment::Num(0)), sb.unwrap_or(Segment::Num(0))),
+            };
+            match sa.cmp(&sb) {
+                Ordering::Equal => continue,
+                o => return o,
+            }
+        }
+    }
+}
+
+impl PartialOrd for Version {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for Version {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other).is_eq()
+    }
+}
+
+impl Eq for Version {}
+
+/// The range of versions an element is available in, with bounds included.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Versions {
+    pub since: Option<Version>,
+    pub until: Option<Version>,
+    pub deprecated_since: Option<Version>,
+}
+
+impl Versions {
+    /// Checks whether the element is available in the given version.
+    pub fn includes(&self, version: &Version) -> bool {
+        self.since.as_ref().is_none_or(|s| s <= version)
+            && self.until.as_ref().is_none_or(|u| version <= u)
+    }
+
+    /// Checks whether the element is deprecated in the given version.
+    pub fn is_deprecated(&self, version: &Version) -> bool {
+        self.deprecated_since.as_ref().is_some_and(|d| d <= version)
+    }
+}
+
+impl Filter for Version {
+    fn schema(&self, schema: &Schema) -> bool {
+        schema.versions.includes(self)
+    }
+
+    fn property(&self, prop: &Property) -> bool {
+        prop.versions.includes(self)
+    }
+
+    fn transfer(&self, xfer: &Transfer) -> bool {
+        xfer.versions.includes(self)
+    }
+
+    fn relation(&self, rel: &Relation) -> bool {
+        rel.versions.includes(self)
+    }
+
+    fn exclusion(&self) -> String {
+        format!("not available in version {self}")
+    }
+}
+
+fn deprecate_object(version: &Version, obj: &mut Object) {
+    obj.props
+        .iter_mut()
+        .for_each(|p| deprecate_property(version, p));
+}
+
+fn deprecate_property(version: &Version, prop: &mut Property) {
+    prop.deprecated |= prop.versions.is_deprecated(version);
+    deprecate_schema(version, &mut prop.schema);
+}
+
+fn deprecate_schema(version: &Version, schema: &mut Schema) {
+    schema.deprecated |= schema.versions.is_deprecated(version);
+    match &mut schema.expr {
+        SchemaExpr::Object(o) => deprecate_object(version, o),
+        SchemaExpr::Array(a) => deprecate_schema(version, &mut a.item),
+        SchemaExpr::Op(op) => op
+            .schemas
+            .iter_mut()
+            .for_each(|s| deprecate_schema(version, s)),
+        SchemaExpr::Uri(u) => deprecate_uri(version, u),
+        SchemaExpr::Rel(r) => deprecate_uri(version, &mut r.uri),
+        _ => {}
+    }
+}
+
+fn deprecate_uri(version: &Version, uri: &mut Uri) {
+    for segment in uri.path.iter_mut() {
+        if let UriSegment::Variable(prop) = segment {
+            deprecate_property(version, prop);
+        }
+    }
+    if let Some(params) = &mut uri.params {
+        deprecate_object(version, params);
+    }
+}
+
+fn deprecate_content(version: &Version, content: &mut Content) {
+    if let Some(schema) = &mut content.schema {
+        deprecate_schema(version, schema);
+    }
+    if let Some(headers) = &mut content.headers {
+        deprecate_object(version, headers);
+    }
+}
+
+fn deprecate_relation(version: &Version, rel: &mut Relation) {
+    let is_deprecated = rel.versions.is_deprecated(version);
+    deprecate_uri(version, &mut rel.uri);
+    for xfer in rel.xfers.values_mut().flatten() {
+        // Deprecating a relation deprecates all its operations.
+        xfer.deprecated |= is_deprecated || xfer.versions.is_deprecated(version);
+        deprecate_content(version, &mut xfer.domain);
+        xfer.ranges
+            .values_mut()
+            .for_each(|c| deprecate_content(version, c));
+        if let Some(params) = &mut xfer.params {
+            deprecate_object(version, params);
+        }
+    }
+}
+
+/// Restricts a specification to the elements available in the given version.
+///
+/// References that are no longer reachable from any relation are removed,
+/// and elements past their deprecation version are marked as deprecated.
+/// Fails if a kept element depends on a pruned element.
+pub fn select(spec: &mut Spec, version: &Version) -> std::result::Result<(), Errors> {
+    filter::apply(spec, version)?;
+    spec.rels
+        .iter_mut()
+        .for_each(|r| deprecate_relation(version, r));
+    for reference in spec.refs.values_mut() {
+        match reference {
+            Reference::Schema(s) => deprecate_schema(version, s),
+            Reference::Content(c) => deprecate_content(version, c),
+            Reference::Property(p) => deprecate_property(version, p),
+            Reference::Ranges(r) => r.values_mut().for_each(|c| deprecate_content(version, c)),
+        }
+    }
+    Ok(())
+}
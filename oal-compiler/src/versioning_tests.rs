@@ -0,0 +1,83 @@
+use crate::errors::Kind;
+use crate::spec::{Reference, SchemaExpr};
+use crate::tests::eval;
+use crate::versioning::{select, Version};
+use oal_syntax::atom::Method;
+
+#[test]
+fn version_ordering() {
+    let v = Version::from;
+    assert!(v("1.2") < v("1.10"));
+    assert!(v("2") > v("1.9"));
+    assert_eq!(v("2"), v("2.0"));
+    assert_eq!(v("v3"), v("3"));
+    assert!(v("1.0") < v("1.0.1"));
+}
+
+#[test]
+fn version_select() -> anyhow::Result<()> {
+    let code = r#"
+        let @item = {
+          'id int
+        , 'name str `until: 1`
+        , 'label str `since: 2, deprecatedSince: "2.1"`
+        };
+        # since: 2
+        let create = post -> @item;
+        # deprecatedSince: 2
+        let legacy = /legacy on get -> {};
+        res /items on get -> @item, create;
+        res legacy;
+    "#;
+
+    let mut spec = eval(code)?;
+    select(&mut spec, &Version::from("1")).map_err(|errs| anyhow::anyhow!("{errs:?}"))?;
+
+    assert!(spec.rels[0].xfers[Method::Post].is_none());
+    assert!(!spec.rels[1].xfers[Method::Get].as_ref().unwrap().deprecated);
+    let Some(Reference::Schema(item)) = spec.refs.values().next() else {
+        panic!("expected a schema reference")
+    };
+    let SchemaExpr::Object(obj) = &item.expr else {
+        panic!("expected an object")
+    };
+    let names: Vec<_> = obj.props.iter().map(|p| p.name.as_ref()).collect();
+    assert_eq!(names, ["id", "name"]);
+
+    let mut spec = eval(code)?;
+    select(&mut spec, &Version::from("2.1")).map_err(|errs| anyhow::anyhow!("{errs:?}"))?;
+
+    assert!(spec.rels[0].xfers[Method::Post].is_some());
+    assert!(spec.rels[1].xfers[Method::Get].as_ref().unwrap().deprecated);
+    let Some(Reference::Schema(item)) = spec.refs.values().next() else {
+        panic!("expected a schema reference")
+    };
+    let SchemaExpr::Object(obj) = &item.expr else {
+        panic!("expected an object")
+    };
+    let names: Vec<_> = obj.props.iter().map(|p| p.name.as_ref()).collect();
+    assert_eq!(names, ["id", "label"]);
+    assert!(obj.props[1].schema.deprecated);
+
+    Ok(())
+}
+
+#[test]
+fn version_excluded_dependency() -> anyhow::Result<()> {
+    let code = r#"
+        # since: 2
+        let @item = { 'id int };
+        res /items on get -> @item;
+    "#;
+
+    let mut spec = eval(code)?;
+    let errs = select(&mut spec, &Version::from("1")).expect_err("expected errors");
+
+    assert!(matches!(errs[0].kind, Kind::ExcludedDependency));
+    assert_eq!(
+        errs[0].to_string(),
+        "excluded dependency: '@item' is not available in version 1 but 'GET /items' depends on it"
+    );
+
+    Ok(())
+}
@@ -0,0 +1,641 @@
+#[cfg(test)]
+mod tests;
+
+use indexmap::IndexMap;
+use oal_compiler::spec;
+use oal_compiler::spec::{method_label, status_label, SchemaExpr};
+use oal_openapi::codegen::schema_label;
+use oal_openapi::Naming;
+use oal_syntax::atom;
+use std::collections::{HashMap, HashSet};
+
+/// The title of the index page.
+const TITLE: &str = "API reference";
+
+/// The format of the generated documentation.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum Format {
+    #[default]
+    Markdown,
+    Html,
+}
+
+impl Format {
+    fn extension(self) -> &'static str {
+        match self {
+            Format::Markdown => "md",
+            Format::Html => "html",
+        }
+    }
+}
+
+/// A page of documentation.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Page {
+    /// The path of the page relative to the index.
+    pub path: String,
+    pub content: String,
+}
+
+/// The generated documentation, i.e. an index and the pages it links to.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Documentation {
+    pub index: String,
+    pub pages: Vec<Page>,
+}
+
+/// Renders an inline code span.
+fn code(text: &str) -> String {
+    if text.contains('`') {
+        format!("`` {text} ``")
+    } else {
+        format!("`{text}`")
+    }
+}
+
+/// Renders Markdown text as the content of a table cell, i.e. on a single line.
+fn cell(text: &str) -> String {
+    text.lines()
+        .map(str::trim)
+        .collect::<Vec<_>>()
+        .join("<br>")
+        .replace('|', "\\|")
+}
+
+/// Converts a label into a file name.
+fn file_name(label: &str) -> String {
+    let name: String = label
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                c
+            } else {
+                '-'
+            }
+        })
+        .collect();
+    if name.is_empty() {
+        "page".to_owned()
+    } else {
+        name
+    }
+}
+
+/// Returns a name not used yet, numbered after the base name if needed.
+fn fresh(used: &mut HashSet<String>, base: &str) -> String {
+    let mut name = base.to_owned();
+    let mut n = 1;
+    while !used.insert(name.to_lowercase()) {
+        n += 1;
+        name = format!("{base}-{n}");
+    }
+    name
+}
+
+/// Returns the constraints of a schema, one sentence each.
+fn constraints(s: &spec::Schema) -> Vec<String> {
+    let mut lines = Vec::new();
+    match &s.expr {
+        SchemaExpr::Str(p) => {
+            if !p.enumeration.is_empty() {
+                let values: Vec<_> = p.enumeration.iter().map(|e| code(e)).collect();
+                lines.push(format!("Values: {}.", values.join(", ")));
+            }
+            if let Some(f) = &p.format {
+                lines.push(format!("Format: {}.", code(f)));
+            }
+            if let Some(r) = &p.pattern {
+                lines.push(format!("Pattern: {}.", code(r)));
+            }
+            if let Some(n) = p.min_length {
+                lines.push(format!("Minimum length: {n}."));
+            }
+            if let Some(n) = p.max_length {
+                lines.push(format!("Maximum length: {n}."));
+            }
+            if let Some(e) = &p.example {
+                lines.push(format!("Example: {}.", code(e)));
+            }
+        }
+        SchemaExpr::Num(p) => {
+            if let Some(n) = p.minimum {
+                lines.push(format!("Minimum: {n}."));
+            }
+            if let Some(n) = p.maximum {
+                lines.push(format!("Maximum: {n}."));
+            }
+            if let Some(n) = p.multiple_of {
+                lines.push(format!("Multiple of: {n}."));
+            }
+            if let Some(e) = p.example {
+                lines.push(format!("Example: {}.", code(&e.to_string())));
+            }
+        }
+        SchemaExpr::Int(p) => {
+            if let Some(n) = p.minimum {
+                lines.push(format!("Minimum: {n}."));
+            }
+            if let Some(n) = p.maximum {
+                lines.push(format!("Maximum: {n}."));
+            }
+            if let Some(n) = p.multiple_of {
+                lines.push(format!("Multiple of: {n}."));
+            }
+            if let Some(e) = p.example {
+                lines.push(format!("Example: {}.", code(&e.to_string())));
+            }
+        }
+        _ => {}
+    }
+    lines
+}
+
+/// Renders a list of examples, sorted by name.
+fn examples(examples: Option<&HashMap<String, String>>) -> String {
+    let mut examples: Vec<_> = examples.into_iter().flatten().collect();
+    if examples.is_empty() {
+        return String::new();
+    }
+    examples.sort();
+    let items: String = examples
+        .into_iter()
+        .map(|(name, url)| format!("- [{name}]({url})\n"))
+        .collect();
+    format!("**Examples**\n\n{items}\n")
+}
+
+/// Converts a Markdown page into a standalone HTML page.
+fn html(title: &str, markdown: &str) -> String {
+    let parser = pulldown_cmark::Parser::new_ext(markdown, pulldown_cmark::Options::ENABLE_TABLES);
+    let mut body = String::new();
+    pulldown_cmark::html::push_html(&mut body, parser);
+    let mut escaped = String::new();
+    pulldown_cmark::escape::escape_html(&mut escaped, title).expect("string should be writable");
+    format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{escaped}</title>\n</head>\n<body>\n{body}</body>\n</html>\n"
+    )
+}
+
+/// An operation, i.e. a transfer of a relation for a given method.
+struct Operation<'a> {
+    id: String,
+    /// The path of the operation page.
+    path: String,
+    method: atom::Method,
+    uri: &'a spec::Uri,
+    xfer: &'a spec::Transfer,
+}
+
+impl Operation<'_> {
+    fn title(&self) -> String {
+        match &self.xfer.summary {
+            Some(summary) => summary.clone(),
+            None => format!("{} {}", method_label(self.method), self.uri.pattern()),
+        }
+    }
+}
+
+/// Generates a Markdown or HTML API reference from a specification.
+pub struct Builder {
+    spec: spec::Spec,
+    naming: Naming,
+    format: Format,
+    /// The names and page paths of the schema references.
+    schemas: IndexMap<atom::Ident, (String, String)>,
+}
+
+impl Builder {
+    pub fn new(spec: spec::Spec) -> Builder {
+        Builder {
+            spec,
+            naming: Naming::default(),
+            format: Format::default(),
+            schemas: IndexMap::new(),
+        }
+    }
+
+    /// Sets the naming strategy for operation identifiers.
+    pub fn with_naming(mut self, naming: Naming) -> Self {
+        self.naming = naming;
+        self
+    }
+
+    /// Sets the format of the generated pages.
+    pub fn with_format(mut self, format: Format) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// Returns the path of a page relative to the index.
+    fn page_path(&self, dir: &str, name: &str) -> String {
+        format!("{dir}/{}.{}", file_name(name), self.format.extension())
+    }
+
+    /// Returns the link to a schema reference from a page at the given root.
+    fn schema_link(&self, ident: &atom::Ident, root: &str) -> String {
+        match self.schemas.get(ident) {
+            Some((name, path)) => format!("[{name}]({root}{path})"),
+            None => code(&ident.untagged()),
+        }
+    }
+
+    /// Returns the inline description of the type of a schema.
+    fn ty(&self, s: &spec::Schema, root: &str) -> String {
+        match &s.expr {
+            SchemaExpr::Num(_) => "number".to_owned(),
+            SchemaExpr::Int(_) => "integer".to_owned(),
+            SchemaExpr::Bool(_) => "boolean".to_owned(),
+            SchemaExpr::Str(_) => "string".to_owned(),
+            SchemaExpr::Uri(_) | SchemaExpr::Rel(_) => "uri".to_owned(),
+            SchemaExpr::Array(a) => format!("array of {}", self.ty(&a.item, root)),
+            SchemaExpr::Object(_) => "object".to_owned(),
+            SchemaExpr::Op(op) if op.schemas.len() == 1 => self.ty(&op.schemas[0], root),
+            SchemaExpr::Op(op) => {
+                let quantifier = match op.op {
+                    atom::VariadicOperator::Join => "all of",
+                    atom::VariadicOperator::Sum => "one of",
+                    atom::VariadicOperator::Any => "any of",
+                    atom::VariadicOperator::Range => unreachable!(),
+                };
+                let operands: Vec<_> = op.schemas.iter().map(|s| self.ty(s, root)).collect();
+                format!("{quantifier} {}", operands.join(", "))
+            }
+            SchemaExpr::Ref(ident) => self.schema_link(ident, root),
+        }
+    }
+
+    /// Returns the description of a property, i.e. its description and constraints.
+    fn details(&self, p: &spec::Property) -> String {
+        let mut lines = Vec::new();
+        match &p.schema.expr {
+            // The description of a reference documents the referenced schema instead.
+            SchemaExpr::Ref(_) => {
+                lines.extend(p.desc.clone());
+                if p.deprecated {
+                    lines.push("**Deprecated.**".to_owned());
+                }
+            }
+            _ => {
+                lines.extend(p.desc.clone().or_else(|| p.schema.desc.clone()));
+                lines.extend(constraints(&p.schema));
+                if p.deprecated || p.schema.deprecated {
+                    lines.push("**Deprecated.**".to_owned());
+                }
+            }
+        }
+        cell(&lines.join("\n"))
+    }
+
+    /// Appends the table rows of properties,
+    /// followed by the properties of inline objects with qualified names.
+    fn rows(
+        &self,
+        rows: &mut Vec<String>,
+        props: &[spec::Property],
+        prefix: &str,
+        root: &str,
+        required: fn(&spec::Property) -> bool,
+    ) {
+        for p in props {
+            let name = format!("{prefix}{}", p.name);
+            let mark = if required(p) { "yes" } else { "no" };
+            rows.push(format!(
+                "| {} | {} | {mark} | {} |\n",
+                code(&name),
+                self.ty(&p.schema, root),
+                self.details(p)
+            ));
+            let nested = match &p.schema.expr {
+                SchemaExpr::Object(o) => Some((o, format!("{name}."))),
+                SchemaExpr::Array(a) => match &a.item.expr {
+                    SchemaExpr::Object(o) => Some((o, format!("{name}[]."))),
+                    _ => None,
+                },
+                _ => None,
+            };
+            if let Some((o, prefix)) = nested {
+                self.rows(rows, &o.props, &prefix, root, spec::Property::is_required);
+            }
+        }
+    }
+
+    /// Returns the table of properties, if any.
+    fn properties(
+        &self,
+        props: &[spec::Property],
+        root: &str,
+        required: fn(&spec::Property) -> bool,
+    ) -> String {
+        let mut rows = Vec::new();
+        self.rows(&mut rows, props, "", root, required);
+        if rows.is_empty() {
+            String::new()
+        } else {
+            format!(
+                "| Name | Type | Required | Description |\n|---|---|---|---|\n{}\n",
+                rows.concat()
+            )
+        }
+    }
+
+    /// Returns the documentation of the body of a request or response.
+    fn body(&self, schema: &spec::Schema, root: &str) -> String {
+        let mut text = format!("**Body:** {}\n\n", self.ty(schema, root));
+        if let SchemaExpr::Object(o) = &schema.expr {
+            text.push_str(&self.properties(&o.props, root, spec::Property::is_required));
+        }
+        text
+    }
+
+    /// Returns the documentation of a request or response content.
+    fn content(&self, c: &spec::Content, root: &str) -> String {
+        let mut text = String::new();
+        if let Some(desc) = &c.desc {
+            text.push_str(&format!("{desc}\n\n"));
+        }
+        if let Some(media) = &c.media {
+            text.push_str(&format!("**Media type:** {}\n\n", code(media)));
+        }
+        if let Some(headers) = c.headers.as_ref().filter(|h| !h.props.is_empty()) {
+            text.push_str("**Headers**\n\n");
+            text.push_str(&self.properties(
+                &headers.props,
+                root,
+                spec::Property::is_required_param,
+            ));
+        }
+        if let Some(schema) = &c.schema {
+            text.push_str(&self.body(schema, root));
+        }
+        text.push_str(&examples(
+            c.examples
+                .as_ref()
+                .or_else(|| c.schema.as_ref().and_then(|s| s.examples.as_ref())),
+        ));
+        text
+    }
+
+    /// Returns the table of parameters of an operation, if any.
+    fn parameters(&self, op: &Operation) -> String {
+        let root = "../";
+        let mut rows = Vec::new();
+        for s in op.uri.path.iter() {
+            if let spec::UriSegment::Variable(p) = s {
+                rows.push((p.as_ref(), "path", true));
+            }
+        }
+        for params in [op.uri.params.as_ref(), op.xfer.params.as_ref()]
+            .into_iter()
+            .flatten()
+        {
+            rows.extend(
+                params
+                    .props
+                    .iter()
+                    .map(|p| (p, "query", p.is_required_param())),
+            );
+        }
+        if let Some(headers) = &op.xfer.domain.headers {
+            rows.extend(
+                headers
+                    .props
+                    .iter()
+                    .map(|p| (p, "header", p.is_required_param())),
+            );
+        }
+        if rows.is_empty() {
+            return String::new();
+        }
+        let rows: String = rows
+            .into_iter()
+            .map(|(p, location, required)| {
+                let mark = if required { "yes" } else { "no" };
+                format!(
+                    "| {} | {location} | {} | {mark} | {} |\n",
+                    code(p.name.as_ref()),
+                    self.ty(&p.schema, root),
+                    self.details(p)
+                )
+            })
+            .collect();
+        format!(
+            "## Parameters\n\n| Name | In | Type | Required | Description |\n|---|---|---|---|---|\n{rows}\n"
+        )
+    }
+
+    /// Returns the page of an operation.
+    fn operation(&self, op: &Operation) -> String {
+        let root = "../";
+        let xfer = op.xfer;
+        let mut text = format!("# {}\n\n", op.title());
+        if xfer.deprecated {
+            text.push_str("**Deprecated.**\n\n");
+        }
+        text.push_str(&format!(
+            "{}\n\n",
+            code(&format!("{} {}", method_label(op.method), op.uri.pattern()))
+        ));
+        text.push_str(&format!("**Operation:** {}\n\n", code(&op.id)));
+        if !xfer.tags.is_empty() {
+            let tags: Vec<_> = xfer.tags.iter().map(|t| code(t)).collect();
+            text.push_str(&format!("**Tags:** {}\n\n", tags.join(", ")));
+        }
+        if let Some(desc) = &xfer.desc {
+            text.push_str(&format!("{desc}\n\n"));
+        }
+        text.push_str(&self.parameters(op));
+        if xfer.domain.schema.is_some() || xfer.domain.desc.is_some() {
+            // Request headers are documented as parameters.
+            let domain = spec::Content {
+                headers: None,
+                ..xfer.domain.clone()
+            };
+            text.push_str(&format!("## Request\n\n{}", self.content(&domain, root)));
+        }
+        if !xfer.ranges.is_empty() {
+            text.push_str("## Responses\n\n");
+            for ((status, media), content) in xfer.ranges.iter() {
+                let mut heading = status_label(*status);
+                if media.is_some() && xfer.ranges.keys().filter(|(s, _)| s == status).count() > 1 {
+                    heading.push_str(&format!(
+                        " ({})",
+                        code(media.as_deref().unwrap_or_default())
+                    ));
+                }
+                text.push_str(&format!("### {heading}\n\n{}", self.content(content, root)));
+            }
+        }
+        text
+    }
+
+    /// Returns the page of a schema reference.
+    fn schema(&self, name: &str, s: &spec::Schema) -> String {
+        let root = "../";
+        let mut text = format!("# {name}\n\n");
+        if s.deprecated {
+            text.push_str("**Deprecated.**\n\n");
+        }
+        if let Some(title) = &s.title {
+            text.push_str(&format!("*{title}*\n\n"));
+        }
+        if let Some(desc) = &s.desc {
+            text.push_str(&format!("{desc}\n\n"));
+        }
+        text.push_str(&format!("**Type:** {}\n\n", self.ty(s, root)));
+        let lines = constraints(s);
+        if !lines.is_empty() {
+            let items: String = lines.iter().map(|l| format!("- {l}\n")).collect();
+            text.push_str(&format!("{items}\n"));
+        }
+        match &s.expr {
+            SchemaExpr::Object(o) if !o.props.is_empty() => {
+                text.push_str("## Properties\n\n");
+                text.push_str(&self.properties(&o.props, root, spec::Property::is_required));
+            }
+            SchemaExpr::Op(op) => {
+                // Inline objects are documented in place, references on their own page.
+                for (i, operand) in op.schemas.iter().enumerate() {
+                    if let SchemaExpr::Object(o) = &operand.expr {
+                        let table = self.properties(&o.props, root, spec::Property::is_required);
+                        if !table.is_empty() {
+                            text.push_str(&format!("## Operand {}\n\n{table}", i + 1));
+                        }
+                    }
+                }
+            }
+            _ => {}
+        }
+        text.push_str(&examples(s.examples.as_ref()));
+        text
+    }
+
+    /// Returns the index page, with operations grouped by tag.
+    fn index(&self, ops: &[Operation]) -> String {
+        let root = "";
+        let mut groups: IndexMap<Option<&str>, Vec<&Operation>> = IndexMap::new();
+        for op in ops {
+            if op.xfer.tags.is_empty() {
+                groups.entry(None).or_default().push(op);
+            }
+            for tag in op.xfer.tags.iter() {
+                groups.entry(Some(tag.as_str())).or_default().push(op);
+            }
+        }
+        // Untagged operations come last.
+        if let Some(untagged) = groups.shift_remove(&None) {
+            groups.insert(None, untagged);
+        }
+        let tagged = groups.keys().any(Option::is_some);
+        let mut text = format!("# {TITLE}\n\n");
+        for (tag, ops) in groups {
+            let heading = match tag {
+                Some(tag) => tag,
+                None if tagged => "Other operations",
+                None => "Operations",
+            };
+            let rows: String = ops
+                .iter()
+                .map(|op| {
+                    let summary = op.xfer.summary.as_deref().unwrap_or_default();
+                    format!(
+                        "| [{}]({root}{}) | {} | {} | {} |\n",
+                        op.id,
+                        op.path,
+                        code(method_label(op.method)),
+                        code(&op.uri.pattern()),
+                        cell(summary)
+                    )
+                })
+                .collect();
+            text.push_str(&format!(
+                "## {heading}\n\n| Operation | Method | Path | Summary |\n|---|---|---|---|\n{rows}\n"
+            ));
+        }
+        if !self.schemas.is_empty() {
+            let rows: String = self
+                .schemas
+                .keys()
+                .map(|ident| {
+                    let desc = match self.spec.refs.get(ident) {
+                        Some(spec::Reference::Schema(s)) => s.desc.as_deref(),
+                        _ => None,
+                    };
+                    // Only the first paragraph summarizes the schema.
+                    let summary = desc
+                        .and_then(|d| d.split("\n\n").next())
+                        .unwrap_or_default();
+                    format!(
+                        "| {} | {} |\n",
+                        self.schema_link(ident, root),
+                        cell(summary)
+                    )
+                })
+                .collect();
+            text.push_str(&format!(
+                "## Schemas\n\n| Schema | Description |\n|---|---|\n{rows}\n"
+            ));
+        }
+        text
+    }
+
+    /// Renders a page in the selected format.
+    fn render(&self, title: &str, markdown: String) -> String {
+        match self.format {
+            Format::Markdown => markdown,
+            Format::Html => html(title, &markdown),
+        }
+    }
+
+    /// Generates the documentation.
+    pub fn build(mut self) -> Documentation {
+        let mut used = HashSet::new();
+        for (ident, reference) in self.spec.refs.iter() {
+            if let spec::Reference::Schema(s) = reference {
+                let name = fresh(&mut used, &schema_label(ident, s));
+                let path = self.page_path("schemas", &name);
+                self.schemas.insert(ident.clone(), (name, path));
+            }
+        }
+
+        let mut used = HashSet::new();
+        let mut ops = Vec::new();
+        for rel in self.spec.rels.iter() {
+            let xfers = rel
+                .xfers
+                .iter()
+                .filter_map(|(m, x)| x.as_ref().map(|x| (m, x)));
+            for (method, xfer) in xfers {
+                let id = self.naming.transfer_id(xfer, method, &rel.uri);
+                let name = fresh(&mut used, &file_name(&id));
+                ops.push(Operation {
+                    path: self.page_path("operations", &name),
+                    id,
+                    method,
+                    uri: &rel.uri,
+                    xfer,
+                });
+            }
+        }
+
+        let mut pages = Vec::new();
+        for op in ops.iter() {
+            let content = self.render(&op.title(), self.operation(op));
+            pages.push(Page {
+                path: op.path.clone(),
+                content,
+            });
+        }
+        for (ident, (name, path)) in self.schemas.iter() {
+            if let Some(spec::Reference::Schema(s)) = self.spec.refs.get(ident) {
+                let content = self.render(name, self.schema(name, s));
+                pages.push(Page {
+                    path: path.clone(),
+                    content,
+                });
+            }
+        }
+        let index = self.render(TITLE, self.index(&ops));
+
+        Documentation { index, pages }
+    }
+}
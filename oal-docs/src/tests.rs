@@ -0,0 +1,127 @@
+use crate::{Builder, Format};
+use oal_compiler::tests::eval;
+use oal_openapi::Naming;
+
+const CODE: &str = r#"
+    # description: "An item of the *catalog*."
+    let @item = {
+      'id! int `minimum: 1`
+    , 'name str `description: "The name | label."`
+    , 'dims { 'width num }
+    , 'kind str `enum: [small, large]`
+    };
+    # tags: [items], summary: "Update an item"
+    # description: "Replaces the item.\n\nThe previous version is lost."
+    let update = put : <headers={ 'if-match str }, @item>
+      -> <status=200, headers={ 'etag! str }, @item>
+      :: <status=404, {}>;
+    # tags: [items]
+    let list = get -> [@item];
+    res /items/{ 'id int } ? { 'q str } on update;
+    res /items on list;
+    res /health on get -> <status=204>;
+"#;
+
+#[test]
+fn docs_markdown() -> anyhow::Result<()> {
+    let docs = Builder::new(eval(CODE)?).with_naming(Naming::Camel).build();
+
+    assert_eq!(
+        docs.index,
+        "# API reference\n\n\
+         ## items\n\n\
+         | Operation | Method | Path | Summary |\n|---|---|---|---|\n\
+         | [putItemsId](operations/putItemsId.md) | `PUT` | `/items/{id}` | Update an item |\n\
+         | [getItems](operations/getItems.md) | `GET` | `/items` |  |\n\n\
+         ## Other operations\n\n\
+         | Operation | Method | Path | Summary |\n|---|---|---|---|\n\
+         | [getHealth](operations/getHealth.md) | `GET` | `/health` |  |\n\n\
+         ## Schemas\n\n\
+         | Schema | Description |\n|---|---|\n\
+         | [item](schemas/item.md) | An item of the *catalog*. |\n\n"
+    );
+
+    let paths: Vec<_> = docs.pages.iter().map(|p| p.path.as_str()).collect();
+    assert_eq!(
+        paths,
+        [
+            "operations/putItemsId.md",
+            "operations/getItems.md",
+            "operations/getHealth.md",
+            "schemas/item.md"
+        ]
+    );
+
+    let update = &docs.pages[0].content;
+    assert!(update.starts_with(
+        "# Update an item\n\n`PUT /items/{id}`\n\n**Operation:** `putItemsId`\n\n**Tags:** `items`\n\n\
+         Replaces the item.\n\nThe previous version is lost.\n\n"
+    ));
+    assert!(update.contains(
+        "## Parameters\n\n\
+         | Name | In | Type | Required | Description |\n|---|---|---|---|---|\n\
+         | `id` | path | integer | yes |  |\n\
+         | `q` | query | string | no |  |\n\
+         | `if-match` | header | string | no |  |\n\n"
+    ));
+    assert!(
+        update.contains("## Request\n\n**Body:** [item](../schemas/item.md)\n\n## Responses\n\n")
+    );
+    assert!(update.contains(
+        "### 200\n\n**Headers**\n\n\
+         | Name | Type | Required | Description |\n|---|---|---|---|\n\
+         | `etag` | string | yes |  |\n\n\
+         **Body:** [item](../schemas/item.md)\n\n"
+    ));
+    assert!(update.contains("### 404\n\n**Body:** object\n\n"));
+
+    let list = &docs.pages[1].content;
+    assert!(list.contains("**Body:** array of [item](../schemas/item.md)\n\n"));
+
+    let item = &docs.pages[3].content;
+    assert_eq!(
+        item,
+        "# item\n\n\
+         An item of the *catalog*.\n\n\
+         **Type:** object\n\n\
+         ## Properties\n\n\
+         | Name | Type | Required | Description |\n|---|---|---|---|\n\
+         | `id` | integer | yes | Minimum: 1. |\n\
+         | `name` | string | no | The name \\| label. |\n\
+         | `dims` | object | no |  |\n\
+         | `dims.width` | number | no |  |\n\
+         | `kind` | string | no | Values: `small`, `large`. |\n\n"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn docs_html() -> anyhow::Result<()> {
+    let docs = Builder::new(eval(CODE)?)
+        .with_naming(Naming::Camel)
+        .with_format(Format::Html)
+        .build();
+
+    assert!(docs.index.starts_with(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>API reference</title>\n"
+    ));
+    assert!(docs
+        .index
+        .contains(r#"<td><a href="operations/putItemsId.html">putItemsId</a></td>"#));
+    assert!(docs
+        .index
+        .contains("<td>An item of the <em>catalog</em>.</td>"));
+
+    let update = &docs.pages[0];
+    assert_eq!(update.path, "operations/putItemsId.html");
+    assert!(update.content.contains("<title>Update an item</title>"));
+    assert!(update
+        .content
+        .contains("<p>Replaces the item.</p>\n<p>The previous version is lost.</p>\n"));
+    assert!(update
+        .content
+        .contains(r#"<a href="../schemas/item.html">item</a>"#));
+
+    Ok(())
+}
@@ -0,0 +1,10 @@
+/// The mock server error type.
+#[derive(thiserror::Error, Debug)]
+pub enum Error {
+    #[error("cannot listen on {address}: {reason}")]
+    Bind { address: String, reason: String },
+    #[error("input/output error")]
+    Io(#[from] std::io::Error),
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
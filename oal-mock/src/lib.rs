@@ -0,0 +1,329 @@
+pub mod errors;
+pub mod server;
+mod synthesis;
+
+#[cfg(test)]
+mod tests;
+
+pub use crate::server::Server;
+use crate::synthesis::Synthesizer;
+use oal_compiler::spec::{self, method_label};
+use oal_syntax::atom;
+use oal_validate::errors::Error;
+use oal_validate::{Contract, Message, Operation, Violation};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+
+/// The request header selecting the status of the response.
+pub const STATUS_HEADER: &str = "x-mock-status";
+
+/// The query parameter selecting the status of the response.
+pub const STATUS_PARAM: &str = "mock-status";
+
+/// The request header selecting the example of the response.
+pub const EXAMPLE_HEADER: &str = "x-mock-example";
+
+/// The query parameter selecting the example of the response.
+pub const EXAMPLE_PARAM: &str = "mock-example";
+
+/// The media type of responses without an explicit one.
+const DEFAULT_MEDIA: &str = "application/json";
+
+/// The media type of error responses.
+const PROBLEM_MEDIA: &str = "application/problem+json";
+
+/// An HTTP request received by the mock.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Request {
+    pub method: String,
+    /// The path of the request target, without query string.
+    pub path: String,
+    pub query: Vec<(String, String)>,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+impl Request {
+    /// Creates a request from a method and a request target, e.g. `/items/1?q=a`.
+    pub fn new(method: &str, target: &str) -> Self {
+        let (path, query) = target.split_once('?').unwrap_or((target, ""));
+        let query = form_urlencoded::parse(query.as_bytes())
+            .into_owned()
+            .collect();
+        Request {
+            method: method.to_ascii_uppercase(),
+            path: path.to_owned(),
+            query,
+            ..Default::default()
+        }
+    }
+
+    pub fn with_header(mut self, name: &str, value: &str) -> Self {
+        self.headers.push((name.to_owned(), value.to_owned()));
+        self
+    }
+
+    pub fn with_body<B: Into<Vec<u8>>>(mut self, body: B) -> Self {
+        self.body = body.into();
+        self
+    }
+
+    /// Returns the value of a header, ignoring the case of its name.
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(n, _)| n.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.as_str())
+    }
+
+    /// Returns the value of a query parameter.
+    pub fn param(&self, name: &str) -> Option<&str> {
+        self.query
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, v)| v.as_str())
+    }
+}
+
+/// An HTTP response produced by the mock.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Response {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+impl Response {
+    fn new(status: u16) -> Self {
+        Response {
+            status,
+            headers: Vec::new(),
+            body: Vec::new(),
+        }
+    }
+
+    fn with_header<V: Into<String>>(mut self, name: &str, value: V) -> Self {
+        self.headers.push((name.to_owned(), value.into()));
+        self
+    }
+
+    /// Creates an error response in the problem details format.
+    fn problem(status: u16, title: &str, violations: &[Violation]) -> Self {
+        let mut body = json!({ "title": title });
+        if !violations.is_empty() {
+            body["errors"] = json!(violations);
+        }
+        Response {
+            status,
+            headers: vec![("content-type".to_owned(), PROBLEM_MEDIA.to_owned())],
+            body: body.to_string().into_bytes(),
+        }
+    }
+
+    /// Returns the value of a header, ignoring the case of its name.
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(n, _)| n.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.as_str())
+    }
+}
+
+fn status_class(range: atom::HttpStatusRange) -> u16 {
+    match range {
+        atom::HttpStatusRange::Info => 1,
+        atom::HttpStatusRange::Success => 2,
+        atom::HttpStatusRange::Redirect => 3,
+        atom::HttpStatusRange::ClientError => 4,
+        atom::HttpStatusRange::ServerError => 5,
+    }
+}
+
+/// Returns the code answered for a declared status, the first of its class for a range.
+fn status_code(status: Option<&atom::HttpStatus>) -> u16 {
+    match status {
+        Some(atom::HttpStatus::Code(c)) => c.get(),
+        Some(atom::HttpStatus::Range(r)) => status_class(*r) * 100,
+        None => 200,
+    }
+}
+
+/// Checks whether a request body is to be validated as JSON.
+fn is_json(media: Option<&str>) -> bool {
+    media.is_none_or(|m| m.split(';').next().unwrap_or(m).trim().ends_with("json"))
+}
+
+/// An HTTP mock answering requests after a specification.
+pub struct Mock {
+    spec: spec::Spec,
+    /// The contents of the examples, by URL.
+    examples: HashMap<String, String>,
+}
+
+impl Mock {
+    pub fn new(spec: spec::Spec) -> Self {
+        Mock {
+            spec,
+            examples: HashMap::new(),
+        }
+    }
+
+    /// Loads the declared examples with the given loader, from their URLs.
+    ///
+    /// Examples the loader cannot provide are replaced by synthesized data.
+    pub fn with_examples<F>(mut self, mut loader: F) -> Self
+    where
+        F: FnMut(&str) -> Option<String>,
+    {
+        let mut urls = Vec::new();
+        for rel in self.spec.rels.iter() {
+            for xfer in rel.xfers.values().flatten() {
+                for content in xfer.ranges.values() {
+                    urls.extend(
+                        content_examples(content)
+                            .into_iter()
+                            .flat_map(|e| e.values()),
+                    );
+                }
+            }
+        }
+        urls.sort();
+        urls.dedup();
+        for url in urls {
+            if let Some(text) = loader(url) {
+                self.examples.insert(url.clone(), text);
+            }
+        }
+        self
+    }
+
+    /// Answers a request.
+    pub fn respond(&self, req: &Request) -> Response {
+        let contract = Contract::new(&self.spec);
+        let op = match contract.operation(&req.method, &req.path) {
+            Ok(op) => op,
+            Err(Error::NotFound { .. }) => {
+                return Response::problem(404, "no matching resource", &[]);
+            }
+            Err(Error::MethodNotAllowed { allowed, .. }) => {
+                let allow: Vec<_> = allowed.into_iter().map(method_label).collect();
+                return Response::problem(405, "method not allowed", &[])
+                    .with_header("allow", allow.join(", "));
+            }
+        };
+
+        let violations = self.validate(req, &op);
+        if !violations.is_empty() {
+            return Response::problem(400, "invalid request", &violations);
+        }
+
+        let requested = req
+            .header(STATUS_HEADER)
+            .or_else(|| req.param(STATUS_PARAM));
+        let selected = match requested {
+            Some(text) => match text.parse::<u16>() {
+                Ok(code) => op.responses(code).first().map(|c| (code, *c)),
+                Err(_) => None,
+            },
+            None => self.default_status(op.transfer),
+        };
+        let mut res = match selected {
+            Some((code, content)) => self.content_response(req, code, content),
+            None if requested.is_none() => Response::new(204),
+            None => {
+                let v = Violation::new(
+                    format!("/header/{STATUS_HEADER}"),
+                    "declared status",
+                    format!("status is not declared for {}", method_label(op.method)),
+                );
+                return Response::problem(400, "undeclared status", &[v]);
+            }
+        };
+        if op.method == atom::Method::Head {
+            res.body.clear();
+        }
+        res
+    }
+
+    fn validate(&self, req: &Request, op: &Operation) -> Vec<Violation> {
+        let mut invalid = None;
+        let body = if !req.body.is_empty() && is_json(req.header("content-type")) {
+            serde_json::from_slice::<Value>(&req.body)
+                .map_err(|e| {
+                    invalid = Some(Violation::new(
+                        "/body",
+                        "JSON",
+                        format!("invalid JSON: {e}"),
+                    ))
+                })
+                .ok()
+        } else {
+            None
+        };
+        let msg = Message {
+            headers: req.headers.clone(),
+            media: None,
+            body,
+        };
+        let mut out = op.validate_request(&req.query, &msg);
+        out.extend(invalid);
+        out
+    }
+
+    /// Returns the first successful response range, or else the first declared one.
+    fn default_status<'a>(&self, xfer: &'a spec::Transfer) -> Option<(u16, &'a spec::Content)> {
+        xfer.ranges
+            .iter()
+            .find(|((s, _), _)| s.as_ref().is_some_and(|s| status_code(Some(s)) / 100 == 2))
+            .or_else(|| xfer.ranges.first())
+            .map(|((s, _), c)| (status_code(s.as_ref()), c))
+    }
+
+    fn content_response(&self, req: &Request, code: u16, content: &spec::Content) -> Response {
+        let synthesizer = Synthesizer::new(&self.spec.refs);
+        let mut res = Response::new(code);
+
+        let headers = content.headers.as_ref();
+        for prop in headers.into_iter().flat_map(|o| o.props.iter()) {
+            if prop.is_required_param() {
+                let value = match synthesizer.value(&prop.schema) {
+                    Value::String(s) => s,
+                    v => v.to_string(),
+                };
+                res = res.with_header(prop.name.as_ref(), value);
+            }
+        }
+
+        let requested = req
+            .header(EXAMPLE_HEADER)
+            .or_else(|| req.param(EXAMPLE_PARAM));
+        let example = content_examples(content).and_then(|examples| {
+            let name = match requested {
+                Some(name) => name,
+                None if examples.contains_key("default") => "default",
+                None => examples.keys().min()?.as_str(),
+            };
+            examples.get(name).and_then(|url| self.examples.get(url))
+        });
+
+        let body = match (example, content.schema.as_ref()) {
+            (Some(text), _) => Some(text.clone()),
+            (None, Some(schema)) => Some(synthesizer.value(schema).to_string()),
+            (None, None) => None,
+        };
+        if let Some(body) = body {
+            let media = content.media.as_deref().unwrap_or(DEFAULT_MEDIA);
+            res = res.with_header("content-type", media);
+            res.body = body.into_bytes();
+        }
+        res
+    }
+}
+
+/// Returns the examples of a content, falling back to the examples of its schema.
+fn content_examples(content: &spec::Content) -> Option<&HashMap<String, String>> {
+    content
+        .examples
+        .as_ref()
+        .or_else(|| content.schema.as_ref().and_then(|s| s.examples.as_ref()))
+}
@@ -0,0 +1,59 @@
+use crate::errors::{Error, Result};
+use crate::{Mock, Request, Response};
+use std::time::Duration;
+
+/// An HTTP server answering requests with a mock.
+pub struct Server {
+    inner: tiny_http::Server,
+}
+
+impl Server {
+    /// Listens on the given address, e.g. `127.0.0.1:8080`.
+    pub fn bind(address: &str) -> Result<Self> {
+        let inner = tiny_http::Server::http(address).map_err(|e| Error::Bind {
+            address: address.to_owned(),
+            reason: e.to_string(),
+        })?;
+        Ok(Server { inner })
+    }
+
+    /// Returns the address the server listens on.
+    pub fn address(&self) -> String {
+        self.inner.server_addr().to_string()
+    }
+
+    /// Answers the next request with the mock, waiting for one at most for the given duration.
+    ///
+    /// Returns the request and the response, if a request was received.
+    pub fn serve(&self, mock: &Mock, timeout: Duration) -> Result<Option<(Request, Response)>> {
+        let Some(mut incoming) = self.inner.recv_timeout(timeout)? else {
+            return Ok(None);
+        };
+
+        let mut req = Request::new(incoming.method().as_str(), incoming.url());
+        req.headers = incoming
+            .headers()
+            .iter()
+            .map(|h| {
+                (
+                    h.field.as_str().as_str().to_owned(),
+                    h.value.as_str().to_owned(),
+                )
+            })
+            .collect();
+        incoming.as_reader().read_to_end(&mut req.body)?;
+
+        let res = mock.respond(&req);
+
+        let mut outgoing =
+            tiny_http::Response::from_data(res.body.clone()).with_status_code(res.status);
+        for (name, value) in res.headers.iter() {
+            if let Ok(h) = tiny_http::Header::from_bytes(name.as_bytes(), value.as_bytes()) {
+                outgoing.add_header(h);
+            }
+        }
+        incoming.respond(outgoing)?;
+
+        Ok(Some((req, res)))
+    }
+}
@@ -0,0 +1,127 @@
+use oal_compiler::spec::{self, SchemaExpr};
+use serde_json::{Map, Number, Value};
+
+/// The depth from which optional properties and array items are left out, to bound recursive schemas.
+const MAX_DEPTH: usize = 8;
+
+/// Synthesizes values conforming to the schemas of a specification.
+/// Values are deterministic, favoring examples, enumerations and lower bounds.
+pub struct Synthesizer<'a> {
+    refs: &'a spec::References,
+}
+
+impl<'a> Synthesizer<'a> {
+    pub fn new(refs: &'a spec::References) -> Self {
+        Synthesizer { refs }
+    }
+
+    /// Returns a value conforming to a schema.
+    pub fn value(&self, s: &spec::Schema) -> Value {
+        self.synthesize(s, 0)
+    }
+
+    fn synthesize(&self, s: &spec::Schema, depth: usize) -> Value {
+        match &s.expr {
+            SchemaExpr::Num(p) => {
+                let n = p.example.unwrap_or_else(|| {
+                    let mut n = p.minimum.unwrap_or(0.0).min(p.maximum.unwrap_or(f64::MAX));
+                    if let Some(m) = p.multiple_of.filter(|m| *m != 0.0) {
+                        n = (n / m).ceil() * m;
+                    }
+                    n
+                });
+                Number::from_f64(n).map_or(Value::Null, Value::Number)
+            }
+            SchemaExpr::Int(p) => {
+                let n = p.example.unwrap_or_else(|| {
+                    let mut n = p.minimum.unwrap_or(0).min(p.maximum.unwrap_or(i64::MAX));
+                    if let Some(m) = p.multiple_of.filter(|m| *m != 0) {
+                        n = n.div_euclid(m) * m + if n.rem_euclid(m) == 0 { 0 } else { m };
+                    }
+                    n
+                });
+                Value::from(n)
+            }
+            SchemaExpr::Bool(_) => Value::Bool(true),
+            SchemaExpr::Str(p) => Value::String(string(p)),
+            SchemaExpr::Uri(uri) => {
+                Value::String(uri.example.clone().unwrap_or_else(|| uri.pattern()))
+            }
+            SchemaExpr::Rel(rel) => Value::String(rel.uri.pattern()),
+            SchemaExpr::Array(a) => {
+                if depth < MAX_DEPTH {
+                    Value::Array(vec![self.synthesize(&a.item, depth + 1)])
+                } else {
+                    Value::Array(Vec::new())
+                }
+            }
+            SchemaExpr::Object(o) => {
+                let map = o
+                    .props
+                    .iter()
+                    .filter(|p| depth < MAX_DEPTH || p.is_required())
+                    .map(|p| {
+                        (
+                            p.name.as_ref().to_owned(),
+                            self.synthesize(&p.schema, depth + 1),
+                        )
+                    })
+                    .collect();
+                Value::Object(map)
+            }
+            SchemaExpr::Op(op) => match op.op {
+                oal_syntax::atom::VariadicOperator::Join => {
+                    let mut map = Map::new();
+                    for s in op.schemas.iter() {
+                        if let Value::Object(m) = self.synthesize(s, depth) {
+                            map.extend(m);
+                        }
+                    }
+                    Value::Object(map)
+                }
+                _ => op
+                    .schemas
+                    .first()
+                    .map_or(Value::Null, |s| self.synthesize(s, depth)),
+            },
+            SchemaExpr::Ref(r) => match self.refs.get(r) {
+                Some(spec::Reference::Schema(s)) if depth <= 2 * MAX_DEPTH => {
+                    self.synthesize(s, depth + 1)
+                }
+                _ => Value::Null,
+            },
+        }
+    }
+}
+
+/// Returns a string conforming to the constraints, except for the pattern.
+fn string(p: &spec::PrimString) -> String {
+    if let Some(example) = &p.example {
+        return example.clone();
+    }
+    if let Some(value) = p.enumeration.first() {
+        return value.clone();
+    }
+    let mut text = match p.format.as_deref() {
+        Some("date-time") => "1970-01-01T00:00:00Z",
+        Some("date") => "1970-01-01",
+        Some("time") => "00:00:00",
+        Some("uuid") => "00000000-0000-0000-0000-000000000000",
+        Some("email") => "user@example.com",
+        Some("uri" | "url") => "https://example.com/",
+        Some("hostname") => "example.com",
+        Some("ipv4") => "127.0.0.1",
+        Some("ipv6") => "::1",
+        _ => "string",
+    }
+    .to_owned();
+    if let Some(n) = p.min_length {
+        while text.chars().count() < n {
+            text.push('x');
+        }
+    }
+    if let Some(n) = p.max_length {
+        text = text.chars().take(n).collect();
+    }
+    text
+}
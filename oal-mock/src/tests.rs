@@ -0,0 +1,148 @@
+use crate::{Mock, Request};
+use oal_compiler::tests::eval;
+use serde_json::{json, Value};
+
+fn body(res: &crate::Response) -> Value {
+    serde_json::from_slice(&res.body).expect("expected a JSON body")
+}
+
+const CODE: &str = r#"
+    let @item = {
+      'id! int `minimum: 1`
+    , 'name! str `minLength: 3`
+    , 'kind str `enum: [small, large]`
+    , 'tags [str]
+    };
+    # examples: { default: "item.json", other: "other.json" }
+    let found = <status=200, headers={ 'etag! str `example: abc` }, @item>;
+    let update = put : <@item> -> found :: <status=404, { 'reason! str }>;
+    res /items/{ 'id int } on get -> found, update;
+    res /items/first on get -> <status=201, media="text/plain", str `example: first`>;
+    res /items ? { 'limit! int `maximum: 10` } on get -> [@item];
+"#;
+
+fn mock() -> anyhow::Result<Mock> {
+    let mock = Mock::new(eval(CODE)?).with_examples(|url| match url {
+        "item.json" => Some(r#"{"id":1,"name":"example"}"#.to_owned()),
+        _ => None,
+    });
+    Ok(mock)
+}
+
+#[test]
+fn mock_routing() -> anyhow::Result<()> {
+    let mock = mock()?;
+
+    let res = mock.respond(&Request::new("get", "/items/first"));
+    assert_eq!(res.status, 201);
+    assert_eq!(res.header("content-type"), Some("text/plain"));
+    assert_eq!(res.body, br#""first""#);
+
+    let res = mock.respond(&Request::new("GET", "/items/12"));
+    assert_eq!(res.status, 200);
+    assert_eq!(res.header("etag"), Some("abc"));
+    assert_eq!(body(&res), json!({ "id": 1, "name": "example" }));
+
+    let res = mock.respond(&Request::new("GET", "/other"));
+    assert_eq!(res.status, 404);
+
+    let res = mock.respond(&Request::new("DELETE", "/items/12"));
+    assert_eq!(res.status, 405);
+    assert_eq!(res.header("allow"), Some("GET, PUT"));
+
+    Ok(())
+}
+
+#[test]
+fn mock_validation() -> anyhow::Result<()> {
+    let mock = mock()?;
+
+    let res = mock.respond(&Request::new("GET", "/items/x"));
+    assert_eq!(res.status, 400);
+    assert_eq!(
+        body(&res)["errors"],
+        json!([{ "path": "/path/id", "expected": "int", "reason": "expected an integer" }])
+    );
+
+    let res = mock.respond(&Request::new("GET", "/items?limit=11"));
+    assert_eq!(res.status, 400);
+    assert_eq!(
+        body(&res)["errors"],
+        json!([{ "path": "/query/limit", "expected": "int", "reason": "expected at most 10" }])
+    );
+
+    let res = mock.respond(&Request::new("GET", "/items"));
+    assert_eq!(res.status, 400);
+    assert_eq!(
+        body(&res)["errors"],
+        json!([{ "path": "/query/limit", "expected": "int", "reason": "missing required parameter" }])
+    );
+
+    let req = Request::new("PUT", "/items/1")
+        .with_header("Content-Type", "application/json")
+        .with_body(r#"{"id":0,"kind":"medium","tags":["a",1],"size":2}"#);
+    let res = mock.respond(&req);
+    assert_eq!(res.status, 400);
+    assert_eq!(
+        body(&res)["errors"],
+        json!([
+            { "path": "/body/id", "expected": "int", "reason": "expected at least 1" },
+            { "path": "/body/name", "expected": "str", "reason": "missing required property" },
+            { "path": "/body/kind", "expected": "str", "reason": "expected one of small, large" },
+            { "path": "/body/tags/1", "expected": "str", "reason": "expected a string" },
+            { "path": "/body/size", "expected": "{ 'id, 'name, 'kind, 'tags }", "reason": "unknown property" },
+        ])
+    );
+
+    let req = Request::new("PUT", "/items/1").with_body(r#"{"id":1,"name":"abc"}"#);
+    assert_eq!(mock.respond(&req).status, 200);
+
+    Ok(())
+}
+
+#[test]
+fn mock_status_selection() -> anyhow::Result<()> {
+    let mock = mock()?;
+
+    let req = Request::new("PUT", "/items/1").with_header("X-Mock-Status", "404");
+    let res = mock.respond(&req);
+    assert_eq!(res.status, 404);
+    assert_eq!(res.header("content-type"), Some("application/json"));
+    assert_eq!(body(&res), json!({ "reason": "string" }));
+
+    let res = mock.respond(&Request::new("PUT", "/items/1?mock-status=404"));
+    assert_eq!(res.status, 404);
+
+    let res = mock.respond(&Request::new("PUT", "/items/1?mock-status=500"));
+    assert_eq!(res.status, 400);
+
+    let res = mock.respond(&Request::new("GET", "/items/1?mock-example=other"));
+    assert_eq!(res.status, 200);
+    assert_eq!(
+        body(&res),
+        json!({ "id": 1, "name": "string", "kind": "small", "tags": ["string"] })
+    );
+
+    Ok(())
+}
+
+#[test]
+fn mock_synthesis() -> anyhow::Result<()> {
+    let code = r#"
+        let @node = rec x { 'value! num `minimum: 2.5, multipleOf: 2`, 'next x };
+        let @when = str `format: date-time`;
+        res /nodes on get -> { 'head! @node, 'at! @when, 'ok! bool, 'count! int `minimum: 7, multipleOf: 5` };
+    "#;
+    let mock = Mock::new(eval(code)?);
+
+    let res = mock.respond(&Request::new("GET", "/nodes"));
+    assert_eq!(res.status, 200);
+    let value = body(&res);
+    assert_eq!(value["head"]["value"], json!(4.0));
+    assert_eq!(value["head"]["next"]["value"], json!(4.0));
+    assert_eq!(value["at"], json!("1970-01-01T00:00:00Z"));
+    assert_eq!(value["ok"], json!(true));
+    assert_eq!(value["count"], json!(10));
+
+    Ok(())
+}
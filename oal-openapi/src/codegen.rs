@@ -0,0 +1,134 @@
+//! Helpers shared by the generators of source code from specifications.
+
+use oal_compiler::spec::{self, SchemaExpr};
+use oal_syntax::atom;
+
+/// Splits a label into alphanumeric words, also at lowercase to uppercase transitions.
+pub fn words(label: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut word = String::new();
+    let mut prev: Option<char> = None;
+    for c in label.chars() {
+        if !c.is_ascii_alphanumeric() {
+            words.extend((!word.is_empty()).then(|| std::mem::take(&mut word)));
+        } else {
+            if c.is_ascii_uppercase()
+                && prev.is_some_and(|p| p.is_ascii_lowercase() || p.is_ascii_digit())
+            {
+                words.extend((!word.is_empty()).then(|| std::mem::take(&mut word)));
+            }
+            word.push(c);
+        }
+        prev = Some(c);
+    }
+    words.extend((!word.is_empty()).then_some(word));
+    words
+}
+
+/// Converts a label into a type name, e.g. `get-items` into `GetItems`.
+pub fn pascal(label: &str) -> String {
+    let name: String = words(label)
+        .iter()
+        .map(|w| {
+            let mut chars = w.chars();
+            match chars.next() {
+                Some(c) => c.to_ascii_uppercase().to_string() + &chars.as_str().to_lowercase(),
+                None => String::new(),
+            }
+        })
+        .collect();
+    if name.starts_with(|c: char| c.is_ascii_alphabetic()) {
+        name
+    } else {
+        format!("T{name}")
+    }
+}
+
+/// Converts a label into a field name, e.g. `firstName` into `first_name`.
+pub fn snake(label: &str) -> String {
+    let name = words(label)
+        .iter()
+        .map(|w| w.to_lowercase())
+        .collect::<Vec<_>>()
+        .join("_");
+    if name.is_empty() {
+        "field".to_owned()
+    } else if name.starts_with(|c: char| c.is_ascii_digit()) {
+        format!("_{name}")
+    } else {
+        name
+    }
+}
+
+/// Renders a string literal, with C-like escapes.
+pub fn literal(s: &str) -> String {
+    format!("{s:?}")
+}
+
+/// Returns the label to name the type of a schema reference after.
+///
+/// Anonymous recursive schemas are labelled after their title, if any.
+pub fn schema_label(ident: &atom::Ident, schema: &spec::Schema) -> String {
+    if ident.is_reference() {
+        ident.untagged()
+    } else {
+        schema
+            .title
+            .clone()
+            .unwrap_or_else(|| "recursive".to_owned())
+    }
+}
+
+/// Returns the documentation lines of a schema, i.e. its description and constraints.
+pub fn schema_doc(s: &spec::Schema, desc: Option<&str>) -> Vec<String> {
+    let mut lines: Vec<String> = desc
+        .or(s.desc.as_deref())
+        .map(str::to_owned)
+        .into_iter()
+        .collect();
+    let mut constraints = Vec::new();
+    match &s.expr {
+        SchemaExpr::Str(p) => {
+            if let Some(f) = &p.format {
+                constraints.push(format!("Format: `{f}`."));
+            }
+            if let Some(r) = &p.pattern {
+                constraints.push(format!("Pattern: `{r}`."));
+            }
+            if let Some(n) = p.min_length {
+                constraints.push(format!("Minimum length: {n}."));
+            }
+            if let Some(n) = p.max_length {
+                constraints.push(format!("Maximum length: {n}."));
+            }
+        }
+        SchemaExpr::Num(p) => {
+            if let Some(n) = p.minimum {
+                constraints.push(format!("Minimum: {n}."));
+            }
+            if let Some(n) = p.maximum {
+                constraints.push(format!("Maximum: {n}."));
+            }
+            if let Some(n) = p.multiple_of {
+                constraints.push(format!("Multiple of: {n}."));
+            }
+        }
+        SchemaExpr::Int(p) => {
+            if let Some(n) = p.minimum {
+                constraints.push(format!("Minimum: {n}."));
+            }
+            if let Some(n) = p.maximum {
+                constraints.push(format!("Maximum: {n}."));
+            }
+            if let Some(n) = p.multiple_of {
+                constraints.push(format!("Multiple of: {n}."));
+            }
+        }
+        _ => {}
+    }
+    if !constraints.is_empty() && !lines.is_empty() {
+        lines.push(String::new());
+    }
+    lines.append(&mut constraints);
+    lines
+}
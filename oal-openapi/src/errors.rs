@@ -0,0 +1,48 @@
+use crate::merge::Conflict;
+use oal_model::span::Span;
+
+/// The OpenAPI generation error type.
+#[derive(thiserror::Error, Debug)]
+pub enum Error {
+    #[error("method {method} on {path} cannot be represented in OpenAPI {version}")]
+    UnsupportedMethod {
+        method: String,
+        path: String,
+        version: String,
+    },
+    #[error("operation identifier '{id}' is not unique")]
+    DuplicateOperationId {
+        id: String,
+        span: Option<Span>,
+        previous: Option<Span>,
+    },
+    #[error("the generated definition conflicts with the base document")]
+    Conflicts(Vec<Conflict>),
+    #[error("reference '{0}' cannot be imported")]
+    UnsupportedReference(String),
+    #[error("reference '{0}' is not defined")]
+    UndefinedReference(String),
+}
+
+impl Error {
+    /// Returns the span of the erroneous definition, if any.
+    pub fn span(&self) -> Option<&Span> {
+        match self {
+            Error::DuplicateOperationId { span, .. } => span.as_ref(),
+            _ => None,
+        }
+    }
+
+    /// Returns the secondary labelled spans.
+    pub fn labels(&self) -> Vec<(Span, String)> {
+        match self {
+            Error::DuplicateOperationId {
+                previous: Some(span),
+                ..
+            } => vec![(span.clone(), "previously used here".to_owned())],
+            _ => Vec::new(),
+        }
+    }
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
@@ -0,0 +1,1072 @@
+use crate::errors::{Error, Result};
+use indexmap::{IndexMap, IndexSet};
+use oal_compiler::render::{is_property_name, sanitize, Annotations};
+use oal_syntax::lexer::is_keyword;
+use openapiv3::*;
+use serde_json::Value;
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+
+/// The media type of contents without explicit media type in the generated program.
+const DEFAULT_MEDIA_TYPE: &str = "application/json";
+
+/// The maximum number of indirections when resolving a component reference.
+const MAX_INDIRECTIONS: usize = 16;
+
+/// The result of importing an OpenAPI description.
+#[derive(Debug)]
+pub struct Import {
+    /// The source code of the generated program.
+    pub program: String,
+    /// The rest of the description, e.g. the info and server objects,
+    /// to be used as base document when compiling the program back.
+    pub base: OpenAPI,
+    /// The JSON pointers to the constructs imported as `x-` annotations,
+    /// which are not compiled back.
+    pub unsupported: Vec<String>,
+}
+
+fn is_path_segment(segment: &str) -> bool {
+    !segment.is_empty()
+        && segment
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "%~_.-".contains(c))
+}
+
+/// Escapes a name as a JSON pointer segment.
+fn pointer_escape(name: &str) -> String {
+    name.replace('~', "~0").replace('/', "~1")
+}
+
+fn format_name<T: serde::Serialize>(format: &VariantOrUnknownOrEmpty<T>) -> Option<String> {
+    let value = serde_json::to_value(format).ok()?;
+    value.as_str().map(str::to_owned)
+}
+
+fn status_label(code: &StatusCode) -> String {
+    match code {
+        StatusCode::Code(c) => c.to_string(),
+        StatusCode::Range(r) => format!("{r}XX"),
+    }
+}
+
+fn component_name<'r>(reference: &'r str, kind: &str) -> Result<&'r str> {
+    reference
+        .strip_prefix("#/components/")
+        .and_then(|r| r.strip_prefix(kind))
+        .and_then(|r| r.strip_prefix('/'))
+        .ok_or_else(|| Error::UnsupportedReference(reference.to_owned()))
+}
+
+/// The location of a parameter.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum ParamIn {
+    Path,
+    Query,
+    Header,
+}
+
+fn param_location(param: &Parameter) -> Option<ParamIn> {
+    match param {
+        Parameter::Path { .. } => Some(ParamIn::Path),
+        Parameter::Query { .. } => Some(ParamIn::Query),
+        Parameter::Header { .. } => Some(ParamIn::Header),
+        Parameter::Cookie { .. } => None,
+    }
+}
+
+/// The identifiers declared in the generated program.
+#[derive(Default)]
+struct Names(HashSet<String>);
+
+impl Names {
+    /// Returns an unused identifier based on the given name.
+    fn fresh(&mut self, base: &str) -> String {
+        let mut chars = sanitize(base).chars().collect::<Vec<_>>();
+        match chars.first_mut() {
+            Some(c) if c.is_ascii_alphabetic() => c.make_ascii_lowercase(),
+            Some('_') => {}
+            _ => chars.insert(0, '_'),
+        }
+        let base: String = chars.into_iter().collect();
+        let mut name = base.clone();
+        let mut n = 1;
+        while is_keyword(&name) || !self.0.insert(name.clone()) {
+            n += 1;
+            name = format!("{base}{n}");
+        }
+        name
+    }
+}
+
+/// A response content, shared as a declaration if used more than once.
+struct Range {
+    text: String,
+    /// The base name of the declaration, if shared.
+    name: String,
+}
+
+struct Transfer {
+    anns: Annotations,
+    method: &'static str,
+    params: Vec<String>,
+    domain: Option<String>,
+    ranges: Vec<Range>,
+}
+
+struct Relation {
+    uri: String,
+    xfers: Vec<Transfer>,
+}
+
+struct Importer<'a> {
+    api: &'a OpenAPI,
+    names: Names,
+    /// The identifiers of schema components, by component name.
+    schemas: IndexMap<&'a str, String>,
+    /// The identifiers of parameter components, by component name.
+    params: IndexMap<&'a str, String>,
+    /// The location in the description of the construct being imported, as JSON pointer segments.
+    scope: RefCell<Vec<String>>,
+    /// The JSON pointers to the constructs imported as `x-` annotations.
+    unsupported: RefCell<IndexSet<String>>,
+}
+
+impl<'a> Importer<'a> {
+    fn new(api: &'a OpenAPI) -> Self {
+        let mut names = Names::default();
+        let mut schemas = IndexMap::new();
+        let mut params = IndexMap::new();
+        if let Some(components) = &api.components {
+            let mut refs = HashSet::new();
+            for name in components.schemas.keys() {
+                let mut ident = format!("@{}", sanitize(name));
+                let mut n = 1;
+                while !refs.insert(ident.clone()) {
+                    n += 1;
+                    ident = format!("@{}{n}", sanitize(name));
+                }
+                schemas.insert(name.as_str(), ident);
+            }
+            for (name, param) in components.parameters.iter() {
+                if let ReferenceOr::Item(p) = param {
+                    if param_location(p).is_some() {
+                        params.insert(name.as_str(), names.fresh(name));
+                    }
+                }
+            }
+        }
+        Importer {
+            api,
+            names,
+            schemas,
+            params,
+            scope: Default::default(),
+            unsupported: Default::default(),
+        }
+    }
+
+    /// Imports a construct nested in the current one, at the given relative location.
+    fn scoped<T>(&self, segments: &[&str], f: impl FnOnce() -> T) -> T {
+        let depth = self.scope.borrow().len();
+        self.scope
+            .borrow_mut()
+            .extend(segments.iter().map(|s| pointer_escape(s)));
+        let result = f();
+        self.scope.borrow_mut().truncate(depth);
+        result
+    }
+
+    /// Imports a construct at the given absolute location.
+    fn at<T>(&self, segments: &[&str], f: impl FnOnce() -> T) -> T {
+        let outer = self.scope.take();
+        let result = self.scoped(segments, f);
+        self.scope.replace(outer);
+        result
+    }
+
+    /// Records a construct that is not compiled back, at the given relative location.
+    fn unsupported(&self, segments: &[&str]) {
+        let scope = self.scope.borrow();
+        let pointer = scope
+            .iter()
+            .cloned()
+            .chain(segments.iter().map(|s| pointer_escape(s)))
+            .fold("#".to_owned(), |p, s| format!("{p}/{s}"));
+        self.unsupported.borrow_mut().insert(pointer);
+    }
+
+    /// Adds an extension annotation and records it as unsupported.
+    fn extension<T: serde::Serialize>(&self, anns: &mut Annotations, key: &str, value: &T) {
+        anns.extension(key, value);
+        self.unsupported(&[key]);
+    }
+
+    /// Adds the specification extensions of an object, which are not compiled back either.
+    fn extend(&self, anns: &mut Annotations, extensions: &IndexMap<String, Value>) {
+        anns.extend(extensions);
+        for key in extensions.keys() {
+            self.unsupported(&[key]);
+        }
+    }
+
+    /// Resolves a component reference of the given kind.
+    fn resolve<T>(
+        &self,
+        mut item: &'a ReferenceOr<T>,
+        kind: &str,
+        get: fn(&'a Components) -> &'a IndexMap<String, ReferenceOr<T>>,
+    ) -> Result<&'a T> {
+        for _ in 0..MAX_INDIRECTIONS {
+            match item {
+                ReferenceOr::Item(t) => return Ok(t),
+                ReferenceOr::Reference { reference } => {
+                    let name = component_name(reference, kind)?;
+                    item = self
+                        .api
+                        .components
+                        .as_ref()
+                        .and_then(|c| get(c).get(name))
+                        .ok_or_else(|| Error::UndefinedReference(reference.clone()))?;
+                }
+            }
+        }
+        Err(Error::UndefinedReference(format!("#/components/{kind}")))
+    }
+
+    fn schema_ident(&self, reference: &str) -> Result<String> {
+        let name = component_name(reference, "schemas")?;
+        self.schemas
+            .get(name)
+            .cloned()
+            .ok_or_else(|| Error::UndefinedReference(reference.to_owned()))
+    }
+
+    fn schema_or_ref(&self, schema: &ReferenceOr<Schema>) -> Result<String> {
+        match schema {
+            ReferenceOr::Reference { reference } => self.schema_ident(reference),
+            ReferenceOr::Item(s) => self.inline_schema(s),
+        }
+    }
+
+    fn boxed_schema_or_ref(&self, schema: &ReferenceOr<Box<Schema>>) -> Result<String> {
+        match schema {
+            ReferenceOr::Reference { reference } => self.schema_ident(reference),
+            ReferenceOr::Item(s) => self.inline_schema(s),
+        }
+    }
+
+    fn inline_schema(&self, schema: &Schema) -> Result<String> {
+        let (expr, anns) = self.schema(schema, false)?;
+        Ok(format!("{expr}{}", anns.inline()))
+    }
+
+    /// Returns the expression of a schema and its annotations.
+    ///
+    /// Top-level objects are laid out over several lines.
+    fn schema(&self, schema: &Schema, top: bool) -> Result<(String, Annotations)> {
+        let data = &schema.schema_data;
+        let mut anns = Annotations::default();
+        if let Some(title) = &data.title {
+            anns.push("title", title.as_str());
+        }
+        if let Some(desc) = &data.description {
+            anns.push("description", desc.as_str());
+        }
+        let expr = match &schema.schema_kind {
+            SchemaKind::Type(Type::String(t)) => self.string_type(t, &mut anns),
+            SchemaKind::Type(Type::Number(t)) => self.number_type(t, &mut anns),
+            SchemaKind::Type(Type::Integer(t)) => self.integer_type(t, &mut anns),
+            SchemaKind::Type(Type::Boolean(t)) => {
+                if !t.enumeration.is_empty() {
+                    self.extension(&mut anns, "enum", &t.enumeration);
+                }
+                "bool".to_owned()
+            }
+            SchemaKind::Type(Type::Object(t)) => self.object_type(t, &mut anns, top)?,
+            SchemaKind::Type(Type::Array(t)) => self.array_type(t, &mut anns)?,
+            SchemaKind::AllOf { all_of } => self.operation("allOf", all_of, " & ", top)?,
+            SchemaKind::OneOf { one_of } => self.operation("oneOf", one_of, " | ", top)?,
+            SchemaKind::AnyOf { any_of } => self.operation("anyOf", any_of, " ~ ", top)?,
+            SchemaKind::Not { not } => {
+                self.extension(&mut anns, "not", not);
+                "{}".to_owned()
+            }
+            SchemaKind::Any(any) => {
+                if *any != AnySchema::default() {
+                    self.extension(&mut anns, "schema", any);
+                }
+                "{}".to_owned()
+            }
+        };
+        if let Some(example) = &data.example {
+            let accepted = match (&schema.schema_kind, example) {
+                (SchemaKind::Type(Type::String(_)), Value::String(_)) => true,
+                (SchemaKind::Type(Type::Number(_)), Value::Number(_)) => true,
+                (SchemaKind::Type(Type::Integer(_)), Value::Number(n)) => n.is_i64(),
+                _ => false,
+            };
+            if accepted {
+                anns.push("example", example.clone());
+            } else {
+                self.extension(&mut anns, "example", example);
+            }
+        }
+        if data.nullable {
+            self.extension(&mut anns, "nullable", &true);
+        }
+        if data.read_only {
+            self.extension(&mut anns, "readOnly", &true);
+        }
+        if data.write_only {
+            self.extension(&mut anns, "writeOnly", &true);
+        }
+        if data.deprecated {
+            self.extension(&mut anns, "deprecated", &true);
+        }
+        if let Some(default) = &data.default {
+            self.extension(&mut anns, "default", default);
+        }
+        if let Some(discriminator) = &data.discriminator {
+            self.extension(&mut anns, "discriminator", discriminator);
+        }
+        if let Some(docs) = &data.external_docs {
+            self.extension(&mut anns, "externalDocs", docs);
+        }
+        self.extend(&mut anns, &data.extensions);
+        Ok((expr, anns))
+    }
+
+    fn string_type(&self, t: &StringType, anns: &mut Annotations) -> String {
+        let format = format_name(&t.format);
+        // Strings formatted as URI references without further constraints are URIs.
+        if format.as_deref() == Some("uri-reference")
+            && t.pattern.is_none()
+            && t.enumeration.is_empty()
+            && t.min_length.is_none()
+            && t.max_length.is_none()
+        {
+            return "uri".to_owned();
+        }
+        if let Some(format) = format {
+            anns.push("format", format);
+        }
+        if let Some(pattern) = &t.pattern {
+            anns.push("pattern", pattern.as_str());
+        }
+        let values: Vec<_> = t.enumeration.iter().flatten().cloned().collect();
+        if !values.is_empty() {
+            anns.push("enum", values);
+        }
+        if let Some(n) = t.min_length {
+            anns.push("minLength", n);
+        }
+        if let Some(n) = t.max_length {
+            anns.push("maxLength", n);
+        }
+        "str".to_owned()
+    }
+
+    fn number_type(&self, t: &NumberType, anns: &mut Annotations) -> String {
+        if let Some(n) = t.minimum {
+            anns.push("minimum", n);
+        }
+        if let Some(n) = t.maximum {
+            anns.push("maximum", n);
+        }
+        if let Some(n) = t.multiple_of {
+            anns.push("multipleOf", n);
+        }
+        if t.exclusive_minimum {
+            self.extension(anns, "exclusiveMinimum", &true);
+        }
+        if t.exclusive_maximum {
+            self.extension(anns, "exclusiveMaximum", &true);
+        }
+        if let Some(format) = format_name(&t.format) {
+            self.extension(anns, "format", &format);
+        }
+        if !t.enumeration.is_empty() {
+            self.extension(anns, "enum", &t.enumeration);
+        }
+        "num".to_owned()
+    }
+
+    fn integer_type(&self, t: &IntegerType, anns: &mut Annotations) -> String {
+        if let Some(n) = t.minimum {
+            anns.push("minimum", n);
+        }
+        if let Some(n) = t.maximum {
+            anns.push("maximum", n);
+        }
+        if let Some(n) = t.multiple_of {
+            anns.push("multipleOf", n);
+        }
+        if t.exclusive_minimum {
+            self.extension(anns, "exclusiveMinimum", &true);
+        }
+        if t.exclusive_maximum {
+            self.extension(anns, "exclusiveMaximum", &true);
+        }
+        if let Some(format) = format_name(&t.format) {
+            self.extension(anns, "format", &format);
+        }
+        if !t.enumeration.is_empty() {
+            self.extension(anns, "enum", &t.enumeration);
+        }
+        "int".to_owned()
+    }
+
+    fn object_type(&self, t: &ObjectType, anns: &mut Annotations, top: bool) -> Result<String> {
+        let mut props = Vec::new();
+        let mut unsupported = IndexMap::new();
+        for (name, schema) in t.properties.iter() {
+            if is_property_name(name) {
+                let mark = if t.required.contains(name) { "!" } else { "" };
+                let schema =
+                    self.scoped(&["properties", name], || self.boxed_schema_or_ref(schema))?;
+                props.push(format!("'{name}{mark} {schema}"));
+            } else {
+                unsupported.insert(name, schema);
+            }
+        }
+        if !unsupported.is_empty() {
+            self.extension(anns, "properties", &unsupported);
+        }
+        if let Some(additional) = &t.additional_properties {
+            self.extension(anns, "additionalProperties", additional);
+        }
+        if let Some(n) = t.min_properties {
+            self.extension(anns, "minProperties", &n);
+        }
+        if let Some(n) = t.max_properties {
+            self.extension(anns, "maxProperties", &n);
+        }
+        let expr = if props.is_empty() {
+            "{}".to_owned()
+        } else if top && props.len() > 1 {
+            format!("{{\n  {}\n}}", props.join("\n, "))
+        } else {
+            format!("{{ {} }}", props.join(", "))
+        };
+        Ok(expr)
+    }
+
+    fn array_type(&self, t: &ArrayType, anns: &mut Annotations) -> Result<String> {
+        let item = match &t.items {
+            Some(item) => self.scoped(&["items"], || self.boxed_schema_or_ref(item))?,
+            None => "{}".to_owned(),
+        };
+        if let Some(n) = t.min_items {
+            self.extension(anns, "minItems", &n);
+        }
+        if let Some(n) = t.max_items {
+            self.extension(anns, "maxItems", &n);
+        }
+        if t.unique_items {
+            self.extension(anns, "uniqueItems", &true);
+        }
+        Ok(format!("[{item}]"))
+    }
+
+    fn operation(
+        &self,
+        keyword: &str,
+        schemas: &[ReferenceOr<Schema>],
+        op: &str,
+        top: bool,
+    ) -> Result<String> {
+        let terms = schemas
+            .iter()
+            .enumerate()
+            .map(|(i, s)| self.scoped(&[keyword, &i.to_string()], || self.schema_or_ref(s)))
+            .collect::<Result<Vec<_>>>()?;
+        let expr = if terms.is_empty() {
+            "{}".to_owned()
+        } else if top {
+            terms.join(op)
+        } else {
+            format!("({})", terms.join(op))
+        };
+        Ok(expr)
+    }
+
+    /// Returns the property expression of a parameter and its annotations.
+    fn parameter_property(
+        &self,
+        data: &ParameterData,
+        loc: ParamIn,
+    ) -> Result<(String, Annotations)> {
+        let mut anns = Annotations::default();
+        if let Some(desc) = &data.description {
+            anns.push("description", desc.as_str());
+        }
+        let schema = match &data.format {
+            ParameterSchemaOrContent::Schema(s) => {
+                self.scoped(&["schema"], || self.schema_or_ref(s))?
+            }
+            ParameterSchemaOrContent::Content(c) => {
+                self.extension(&mut anns, "content", c);
+                "{}".to_owned()
+            }
+        };
+        if data.deprecated == Some(true) {
+            self.extension(&mut anns, "deprecated", &true);
+        }
+        if let Some(example) = &data.example {
+            self.extension(&mut anns, "example", example);
+        }
+        self.extend(&mut anns, &data.extensions);
+        let mark = if data.required && loc != ParamIn::Path {
+            "!"
+        } else {
+            ""
+        };
+        Ok((format!("'{}{mark} {schema}", data.name), anns))
+    }
+
+    /// Returns the location and name of a parameter, with the expression to use it.
+    fn parameter(
+        &self,
+        param: &'a ReferenceOr<Parameter>,
+    ) -> Result<(Option<ParamIn>, &'a Parameter, String)> {
+        let resolved = self.resolve(param, "parameters", |c| &c.parameters)?;
+        let loc = param_location(resolved);
+        if let ReferenceOr::Reference { reference } = param {
+            let name = component_name(reference, "parameters")?;
+            if let Some(ident) = self.params.get(name) {
+                return Ok((loc, resolved, ident.clone()));
+            }
+        }
+        let text = match loc {
+            Some(l) if is_property_name(&resolved.parameter_data_ref().name) => {
+                let (prop, anns) = self.parameter_property(resolved.parameter_data_ref(), l)?;
+                if anns.is_empty() {
+                    prop
+                } else {
+                    format!("({prop}){}", anns.inline())
+                }
+            }
+            _ => String::new(),
+        };
+        let loc = loc.filter(|_| !text.is_empty());
+        if loc.is_none() {
+            self.unsupported(&[]);
+        }
+        Ok((loc, resolved, text))
+    }
+
+    fn header(&self, name: &str, header: &Header) -> Result<String> {
+        let mut anns = Annotations::default();
+        if let Some(desc) = &header.description {
+            anns.push("description", desc.as_str());
+        }
+        let schema = match &header.format {
+            ParameterSchemaOrContent::Schema(s) => {
+                self.scoped(&["schema"], || self.schema_or_ref(s))?
+            }
+            ParameterSchemaOrContent::Content(c) => {
+                self.extension(&mut anns, "content", c);
+                "{}".to_owned()
+            }
+        };
+        if header.deprecated == Some(true) {
+            self.extension(&mut anns, "deprecated", &true);
+        }
+        self.extend(&mut anns, &header.extensions);
+        let mark = if header.required { "!" } else { "" };
+        let prop = format!("'{name}{mark} {schema}");
+        if anns.is_empty() {
+            Ok(prop)
+        } else {
+            Ok(format!("({prop}){}", anns.inline()))
+        }
+    }
+
+    fn content(
+        status: Option<&str>,
+        media: Option<&str>,
+        headers: &[String],
+        schema: Option<String>,
+        anns: &Annotations,
+    ) -> String {
+        let mut parts = Vec::new();
+        if let Some(s) = status {
+            parts.push(format!("status={s}"));
+        }
+        if let Some(m) = media.filter(|m| *m != DEFAULT_MEDIA_TYPE) {
+            parts.push(format!("media=\"{m}\""));
+        }
+        if !headers.is_empty() {
+            parts.push(format!("headers={{ {} }}", headers.join(", ")));
+        }
+        parts.extend(schema);
+        format!("<{}>{}", parts.join(", "), anns.inline())
+    }
+
+    /// Adds the annotations of a media type to those of a content.
+    fn media_annotations(&self, media: &MediaType, anns: &mut Annotations) {
+        let mut examples = IndexMap::new();
+        let mut unsupported = IndexMap::new();
+        for (name, example) in media.examples.iter() {
+            match example {
+                ReferenceOr::Item(Example {
+                    external_value: Some(url),
+                    ..
+                }) => {
+                    examples.insert(name.clone(), Value::from(url.as_str()));
+                }
+                _ => {
+                    unsupported.insert(name, example);
+                }
+            }
+        }
+        if !examples.is_empty() {
+            anns.push("examples", Value::Object(examples.into_iter().collect()));
+        }
+        if !unsupported.is_empty() {
+            self.extension(anns, "examples", &unsupported);
+        }
+        if let Some(example) = &media.example {
+            self.extension(anns, "example", example);
+        }
+        if !media.encoding.is_empty() {
+            self.extension(anns, "encoding", &media.encoding);
+        }
+        self.extend(anns, &media.extensions);
+    }
+
+    fn response(&self, status: Option<&str>, res: &'a ReferenceOr<Response>) -> Result<Vec<Range>> {
+        let name = match res {
+            ReferenceOr::Reference { reference } => component_name(reference, "responses")?,
+            ReferenceOr::Item(_) => "",
+        };
+        let name = if name.is_empty() {
+            format!("status{}", status.unwrap_or("default").to_lowercase())
+        } else {
+            name.to_owned()
+        };
+        let res = self.resolve(res, "responses", |c| &c.responses)?;
+
+        let mut anns = Annotations::default();
+        if !res.description.is_empty() {
+            anns.push("description", res.description.as_str());
+        }
+        let mut headers = Vec::new();
+        let mut unsupported = IndexMap::new();
+        for (name, header) in res.headers.iter() {
+            let resolved = self.resolve(header, "headers", |c| &c.headers)?;
+            if is_property_name(name) {
+                headers.push(self.scoped(&["headers", name], || self.header(name, resolved))?);
+            } else {
+                unsupported.insert(name, header);
+            }
+        }
+        if !unsupported.is_empty() {
+            self.extension(&mut anns, "headers", &unsupported);
+        }
+        if !res.links.is_empty() {
+            self.extension(&mut anns, "links", &res.links);
+        }
+        self.extend(&mut anns, &res.extensions);
+
+        if res.content.is_empty() {
+            let text = Self::content(status, None, &headers, None, &anns);
+            return Ok(vec![Range { text, name }]);
+        }
+        let mut ranges = Vec::new();
+        for (media, content) in res.content.iter() {
+            let mut anns = anns.clone();
+            let schema = self.scoped(&["content", media], || {
+                self.media_annotations(content, &mut anns);
+                content
+                    .schema
+                    .as_ref()
+                    .map(|s| self.scoped(&["schema"], || self.schema_or_ref(s)))
+                    .transpose()
+            })?;
+            let text = Self::content(status, Some(media), &headers, schema, &anns);
+            ranges.push(Range {
+                text,
+                name: name.clone(),
+            });
+        }
+        Ok(ranges)
+    }
+
+    fn transfer(
+        &self,
+        path: &str,
+        method: &'static str,
+        op: &'a Operation,
+        shared: &[(usize, &'a ReferenceOr<Parameter>)],
+        vars: &mut HashMap<&'a str, String>,
+        query: &HashSet<&'a str>,
+    ) -> Result<Transfer> {
+        let mut anns = Annotations::default();
+        if let Some(summary) = &op.summary {
+            anns.push("summary", summary.as_str());
+        }
+        if let Some(desc) = &op.description {
+            anns.push("description", desc.as_str());
+        }
+        if !op.tags.is_empty() {
+            anns.push("tags", op.tags.clone());
+        }
+        if let Some(id) = &op.operation_id {
+            anns.push("operationId", id.as_str());
+        }
+        if op.deprecated {
+            self.extension(&mut anns, "deprecated", &true);
+        }
+
+        let mut params = Vec::new();
+        let mut headers = Vec::new();
+        let mut unsupported = Vec::new();
+        // Operation parameters override the parameters shared by all operations of the path.
+        let names: HashSet<_> = op
+            .parameters
+            .iter()
+            .filter_map(|p| self.resolve(p, "parameters", |c| &c.parameters).ok())
+            .map(|p| p.parameter_data_ref().name.as_str())
+            .collect();
+        let shared = shared
+            .iter()
+            .copied()
+            .filter(|(_, p)| {
+                self.resolve(p, "parameters", |c| &c.parameters)
+                    .is_ok_and(|p| !names.contains(p.parameter_data_ref().name.as_str()))
+            })
+            .map(|(i, p)| {
+                let i = i.to_string();
+                (
+                    p,
+                    self.at(&["paths", path, "parameters", &i], || self.parameter(p)),
+                )
+            });
+        let own = op.parameters.iter().enumerate().map(|(i, p)| {
+            let i = i.to_string();
+            (p, self.scoped(&["parameters", &i], || self.parameter(p)))
+        });
+        for (param, result) in shared.chain(own) {
+            let (loc, resolved, text) = result?;
+            let name = resolved.parameter_data_ref().name.as_str();
+            match loc {
+                Some(ParamIn::Path) => {
+                    vars.entry(name).or_insert(text);
+                }
+                Some(ParamIn::Query) if query.contains(name) => {}
+                Some(ParamIn::Query) => params.push(text),
+                Some(ParamIn::Header) => headers.push(text),
+                None => unsupported.push(param),
+            }
+        }
+        if !unsupported.is_empty() {
+            // The location of each parameter is already recorded as unsupported.
+            anns.extension("parameters", &unsupported);
+        }
+
+        let domain = match &op.request_body {
+            Some(body) => self.scoped(&["requestBody"], || {
+                let body = self.resolve(body, "requestBodies", |c| &c.request_bodies)?;
+                let mut content_anns = Annotations::default();
+                if let Some(desc) = &body.description {
+                    content_anns.push("description", desc.as_str());
+                }
+                if body.required {
+                    self.extension(&mut content_anns, "required", &true);
+                }
+                self.extend(&mut content_anns, &body.extensions);
+                let mut contents = body.content.iter();
+                let (media, schema) = match contents.next() {
+                    Some((media, content)) => self.scoped(&["content", media], || {
+                        self.media_annotations(content, &mut content_anns);
+                        let schema = content
+                            .schema
+                            .as_ref()
+                            .map(|s| self.scoped(&["schema"], || self.schema_or_ref(s)))
+                            .transpose()?;
+                        Ok((Some(media.as_str()), schema))
+                    })?,
+                    None => (None, None),
+                };
+                // A request has a single content.
+                let others: IndexMap<_, _> = contents.collect();
+                if !others.is_empty() {
+                    anns.extension("requestBody", &serde_json::json!({ "content": others }));
+                    for media in others.keys() {
+                        self.unsupported(&["content", media]);
+                    }
+                }
+                Ok(Some(Self::content(
+                    None,
+                    media,
+                    &headers,
+                    schema,
+                    &content_anns,
+                )))
+            })?,
+            None if !headers.is_empty() => Some(Self::content(
+                None,
+                None,
+                &headers,
+                None,
+                &Annotations::default(),
+            )),
+            None => None,
+        };
+
+        let mut ranges = Vec::new();
+        for (code, res) in op.responses.responses.iter() {
+            let label = status_label(code);
+            let range = self.scoped(&["responses", &label], || self.response(Some(&label), res))?;
+            ranges.extend(range);
+        }
+        if let Some(res) = &op.responses.default {
+            ranges.extend(self.scoped(&["responses", "default"], || self.response(None, res))?);
+        }
+
+        if let Some(security) = &op.security {
+            self.extension(&mut anns, "security", security);
+        }
+        if !op.callbacks.is_empty() {
+            self.extension(&mut anns, "callbacks", &op.callbacks);
+        }
+        if !op.servers.is_empty() {
+            self.extension(&mut anns, "servers", &op.servers);
+        }
+        if let Some(docs) = &op.external_docs {
+            self.extension(&mut anns, "externalDocs", docs);
+        }
+        self.extend(&mut anns, &op.extensions);
+
+        Ok(Transfer {
+            anns,
+            method,
+            params,
+            domain,
+            ranges,
+        })
+    }
+
+    /// Returns the relation of a path, unless the path cannot be represented.
+    fn relation(&self, path: &str, item: &'a PathItem) -> Result<Option<Relation>> {
+        let mut vars = HashMap::new();
+        let mut query = Vec::new();
+        let mut query_names = HashSet::new();
+        let mut shared = Vec::new();
+        for (i, param) in item.parameters.iter().enumerate() {
+            let (loc, resolved, text) =
+                self.scoped(&["parameters", &i.to_string()], || self.parameter(param))?;
+            let name = resolved.parameter_data_ref().name.as_str();
+            match loc {
+                Some(ParamIn::Path) => {
+                    vars.insert(name, text);
+                }
+                Some(ParamIn::Query) => {
+                    query.push(text);
+                    query_names.insert(name);
+                }
+                _ => shared.push((i, param)),
+            }
+        }
+
+        let ops = [
+            ("get", &item.get),
+            ("put", &item.put),
+            ("post", &item.post),
+            ("patch", &item.patch),
+            ("delete", &item.delete),
+            ("options", &item.options),
+            ("head", &item.head),
+            ("trace", &item.trace),
+        ];
+        let mut xfers = Vec::new();
+        for (method, op) in ops {
+            if let Some(op) = op {
+                let xfer = self.scoped(&[method], || {
+                    self.transfer(path, method, op, &shared, &mut vars, &query_names)
+                })?;
+                xfers.push(xfer);
+            }
+        }
+
+        let mut uri = String::new();
+        if path != "/" {
+            let Some(rest) = path.strip_prefix('/') else {
+                return Ok(None);
+            };
+            for segment in rest.split('/') {
+                let var = segment.strip_prefix('{').and_then(|s| s.strip_suffix('}'));
+                match var {
+                    Some(name) if is_property_name(name) => {
+                        let text = vars
+                            .get(name)
+                            .cloned()
+                            .unwrap_or_else(|| format!("'{name} str"));
+                        uri.push_str(&format!("/{{ {text} }}"));
+                    }
+                    None if is_path_segment(segment) => {
+                        uri.push('/');
+                        uri.push_str(segment);
+                    }
+                    _ => return Ok(None),
+                }
+            }
+        } else {
+            uri.push('/');
+        }
+        if !query.is_empty() {
+            uri.push_str(&format!("?{{ {} }}", query.join(", ")));
+        }
+        Ok(Some(Relation { uri, xfers }))
+    }
+
+    fn import(mut self) -> Result<Import> {
+        let mut program = String::new();
+        let mut skipped = Vec::new();
+        let mut rels = Vec::new();
+        for (path, item) in self.api.paths.paths.iter() {
+            let rel = match item {
+                ReferenceOr::Item(item) => {
+                    let recorded = self.unsupported.borrow().len();
+                    let rel = self.at(&["paths", path], || self.relation(path, item))?;
+                    if rel.is_none() {
+                        // Skipped paths are kept as they are in the base document.
+                        self.unsupported.borrow_mut().truncate(recorded);
+                    }
+                    rel
+                }
+                ReferenceOr::Reference { .. } => None,
+            };
+            match rel {
+                Some(rel) => rels.push(rel),
+                None => skipped.push(path),
+            }
+        }
+        for path in skipped.iter() {
+            program.push_str(&format!(
+                "// The path '{path}' cannot be represented and was not imported.\n"
+            ));
+        }
+
+        if let Some(components) = &self.api.components {
+            let mut decls = String::new();
+            for (name, ident) in self.params.iter() {
+                let param = self.resolve(&components.parameters[*name], "parameters", |c| {
+                    &c.parameters
+                })?;
+                if let Some(loc) = param_location(param) {
+                    let (prop, anns) = self.at(&["components", "parameters", name], || {
+                        self.parameter_property(param.parameter_data_ref(), loc)
+                    })?;
+                    decls.push_str(&format!("{}let {ident} = {prop};\n", anns.lines("")));
+                }
+            }
+            if !decls.is_empty() {
+                program.push_str(&format!("\n{decls}"));
+            }
+            for (name, ident) in self.schemas.iter() {
+                let (expr, anns) = match &components.schemas[*name] {
+                    ReferenceOr::Reference { reference } => {
+                        (self.schema_ident(reference)?, Annotations::default())
+                    }
+                    ReferenceOr::Item(s) => {
+                        self.at(&["components", "schemas", name], || self.schema(s, true))?
+                    }
+                };
+                program.push_str(&format!("\n{}let {ident} = {expr};\n", anns.lines("")));
+            }
+        }
+
+        // Response contents used more than once become shared declarations.
+        let mut counts = HashMap::new();
+        for range in rels
+            .iter()
+            .flat_map(|r| r.xfers.iter().flat_map(|x| x.ranges.iter()))
+        {
+            *counts.entry(range.text.as_str()).or_insert(0) += 1;
+        }
+        let mut shared: IndexMap<String, String> = IndexMap::new();
+        for range in rels
+            .iter()
+            .flat_map(|r| r.xfers.iter().flat_map(|x| x.ranges.iter()))
+        {
+            if counts[range.text.as_str()] > 1 && !shared.contains_key(&range.text) {
+                let ident = self.names.fresh(&range.name);
+                shared.insert(range.text.clone(), ident);
+            }
+        }
+        if !shared.is_empty() {
+            program.push('\n');
+            for (text, ident) in shared.iter() {
+                program.push_str(&format!("let {ident} = {text};\n"));
+            }
+        }
+
+        for rel in rels.iter() {
+            let xfers: Vec<_> = rel
+                .xfers
+                .iter()
+                .map(|x| {
+                    let mut text = format!("{}  {}", x.anns.lines("  "), x.method);
+                    if !x.params.is_empty() {
+                        text.push_str(&format!(" {{ {} }}", x.params.join(", ")));
+                    }
+                    if let Some(domain) = &x.domain {
+                        text.push_str(&format!(" : {domain}"));
+                    }
+                    let ranges: Vec<_> = x
+                        .ranges
+                        .iter()
+                        .map(|r| shared.get(&r.text).unwrap_or(&r.text).as_str())
+                        .collect();
+                    let ranges = if ranges.is_empty() {
+                        "<>".to_owned()
+                    } else {
+                        ranges.join("\n    :: ")
+                    };
+                    text.push_str(&format!(" -> {ranges}"));
+                    text
+                })
+                .collect();
+            if xfers.is_empty() {
+                program.push_str(&format!("\n// The path '{}' has no operation.\n", rel.uri));
+            } else {
+                program.push_str(&format!("\nres {} on\n{};\n", rel.uri, xfers.join(",\n")));
+            }
+        }
+
+        let base = self.base(&skipped);
+        let unsupported = self.unsupported.take().into_iter().collect();
+        Ok(Import {
+            program,
+            base,
+            unsupported,
+        })
+    }
+
+    /// Returns the rest of the description, without the imported paths and components.
+    fn base(&self, skipped: &[&String]) -> OpenAPI {
+        let mut base = self.api.clone();
+        base.paths.paths.retain(|path, _| skipped.contains(&path));
+        if let Some(components) = &mut base.components {
+            components.schemas.clear();
+            components.responses.clear();
+            components.request_bodies.clear();
+            components.headers.clear();
+            components
+                .parameters
+                .retain(|name, _| !self.params.contains_key(name.as_str()));
+        }
+        base
+    }
+}
+
+/// Imports an OpenAPI description as an Oxlip program.
+///
+/// Component schemas become reference declarations, paths become resources,
+/// and constructs without equivalent in the language become `x-` annotations, listed as unsupported.
+pub fn import(api: &OpenAPI) -> Result<Import> {
+    Importer::new(api).import()
+}
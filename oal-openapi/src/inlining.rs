@@ -0,0 +1,46 @@
+use oal_compiler::spec::{Schema, SchemaExpr, Uri, UriSegment};
+
+/// The policy for inlining references to schemas.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Inlining {
+    /// Named references are always kept as components,
+    /// while implicit references to atomic schemas are inlined.
+    #[default]
+    AlwaysRef,
+    /// As with [`Inlining::AlwaysRef`], and references to schemas without references
+    /// and with less than the given number of nodes are inlined.
+    BelowSize(usize),
+    /// References are never inlined.
+    NeverInline,
+}
+
+fn uri_size(uri: &Uri) -> Option<usize> {
+    let mut size = 1;
+    for s in uri.path.iter() {
+        if let UriSegment::Variable(p) = s {
+            size += schema_size(&p.schema)?;
+        }
+    }
+    for p in uri.params.iter().flat_map(|o| o.props.iter()) {
+        size += schema_size(&p.schema)?;
+    }
+    Some(size)
+}
+
+fn schemas_size<'a, I: IntoIterator<Item = &'a Schema>>(schemas: I) -> Option<usize> {
+    schemas.into_iter().map(schema_size).sum()
+}
+
+/// Returns the number of nodes of a schema, or `None` if the schema contains references.
+pub fn schema_size(schema: &Schema) -> Option<usize> {
+    let inner = match &schema.expr {
+        SchemaExpr::Num(_) | SchemaExpr::Str(_) | SchemaExpr::Bool(_) | SchemaExpr::Int(_) => 0,
+        SchemaExpr::Rel(r) => uri_size(&r.uri)?,
+        SchemaExpr::Uri(u) => uri_size(u)?,
+        SchemaExpr::Array(a) => schema_size(&a.item)?,
+        SchemaExpr::Object(o) => schemas_size(o.props.iter().map(|p| &p.schema))?,
+        SchemaExpr::Op(o) => schemas_size(o.schemas.iter())?,
+        SchemaExpr::Ref(_) => return None,
+    };
+    Some(1 + inner)
+}
@@ -1,30 +1,154 @@
+pub mod codegen;
+pub mod errors;
+mod import;
+mod inlining;
+mod merge;
+mod naming;
 mod oas;
 
+#[cfg(test)]
+mod tests;
+
+use crate::errors::{Error, Result};
+pub use crate::import::{import, Import};
+pub use crate::inlining::Inlining;
+pub use crate::merge::{Conflict, Merge};
+pub use crate::naming::Naming;
 use crate::oas::into_box_ref;
 use indexmap::{indexmap, IndexMap};
 use oal_compiler::spec;
 use oal_compiler::spec::SchemaExpr;
+use oal_model::span::Span;
 use oal_syntax::atom;
 use openapiv3::*;
-use std::iter::once;
+use std::collections::{HashMap, HashSet};
 
 pub struct Builder {
     spec: spec::Spec,
     base: Option<OpenAPI>,
+    naming: Naming,
+    merge: Merge,
+    media_type: String,
+    inlining: Inlining,
+    usage: Usage,
+}
+
+/// The location of a parameter.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum ParamIn {
+    Path,
+    Query,
+    Header,
+}
+
+/// The use sites of references that become non-schema components.
+#[derive(Default)]
+struct Usage {
+    responses: HashSet<atom::Ident>,
+    bodies: HashSet<atom::Ident>,
+    headers: HashSet<atom::Ident>,
+    params: IndexMap<atom::Ident, ParamIn>,
+}
+
+impl Usage {
+    fn new(spec: &spec::Spec) -> Self {
+        let mut usage = Usage::default();
+        for rel in spec.rels.iter() {
+            for s in rel.uri.path.iter() {
+                if let spec::UriSegment::Variable(p) = s {
+                    usage.param(p, ParamIn::Path);
+                }
+            }
+            usage.params(rel.uri.params.as_ref(), ParamIn::Query);
+            for xfer in rel.xfers.values().flatten() {
+                usage.params(xfer.params.as_ref(), ParamIn::Query);
+                usage.params(xfer.domain.headers.as_ref(), ParamIn::Header);
+                if let Some(r) = &xfer.domain.reference {
+                    usage.bodies.insert(r.clone());
+                }
+                for ((status, _), content) in xfer.ranges.iter() {
+                    if let Some(r) = response_reference(&xfer.ranges, status, content) {
+                        usage.responses.insert(r.clone());
+                    }
+                    let headers = content.headers.iter().flat_map(|h| h.props.iter());
+                    usage
+                        .headers
+                        .extend(headers.filter_map(|p| p.reference.clone()));
+                }
+            }
+        }
+        usage
+    }
+
+    fn param(&mut self, prop: &spec::Property, loc: ParamIn) {
+        if let Some(r) = &prop.reference {
+            // A parameter component has a single location, given by its first use site.
+            self.params.entry(r.clone()).or_insert(loc);
+        }
+    }
+
+    fn params(&mut self, obj: Option<&spec::Object>, loc: ParamIn) {
+        for p in obj.iter().flat_map(|o| o.props.iter()) {
+            self.param(p, loc);
+        }
+    }
+}
+
+/// Returns the reference of a response content, if the content is the only one for its status.
+fn response_reference<'a>(
+    ranges: &spec::Ranges,
+    status: &Option<atom::HttpStatus>,
+    content: &'a spec::Content,
+) -> Option<&'a atom::Ident> {
+    let count = ranges.keys().filter(|(s, _)| s == status).count();
+    content.reference.as_ref().filter(|_| count == 1)
+}
+
+fn component_ref<T>(kind: &str, name: &atom::Ident) -> ReferenceOr<T> {
+    ReferenceOr::Reference {
+        reference: format!("#/components/{kind}/{}", name.untagged()),
+    }
 }
 
 type Headers = IndexMap<String, ReferenceOr<Header>>;
 type Examples = IndexMap<String, ReferenceOr<Example>>;
 
-impl From<Builder> for OpenAPI {
-    fn from(b: Builder) -> Self {
+impl TryFrom<Builder> for OpenAPI {
+    type Error = Error;
+
+    fn try_from(b: Builder) -> Result<Self> {
         b.into_openapi()
     }
 }
 
+/// Checks whether the given OpenAPI version supports the QUERY method.
+///
+/// The QUERY method was introduced with OpenAPI 3.2.
+fn supports_query(version: &str) -> bool {
+    let mut parts = version.split('.').map(|p| p.parse::<u32>().unwrap_or(0));
+    let major = parts.next().unwrap_or(0);
+    let minor = parts.next().unwrap_or(0);
+    (major, minor) >= (3, 2)
+}
+
 impl Builder {
     pub fn new(spec: spec::Spec) -> Builder {
-        Builder { spec, base: None }
+        let usage = Usage::new(&spec);
+        Builder {
+            spec,
+            base: None,
+            naming: Naming::default(),
+            merge: Merge::default(),
+            media_type: "application/json".to_owned(),
+            inlining: Inlining::default(),
+            usage,
+        }
+    }
+
+    /// Sets the naming strategy for operation identifiers.
+    pub fn with_naming(mut self, naming: Naming) -> Self {
+        self.naming = naming;
+        self
     }
 
     pub fn with_base(mut self, base: OpenAPI) -> Self {
@@ -32,21 +156,60 @@ impl Builder {
         self
     }
 
-    pub fn into_openapi(self) -> OpenAPI {
-        let paths = self.all_paths();
-        let components = self.all_components();
-        let mut definition = if let Some(base) = self.base {
+    /// Sets the strategy for combining the generated definition with the base document.
+    pub fn with_merge(mut self, merge: Merge) -> Self {
+        self.merge = merge;
+        self
+    }
+
+    /// Sets the media type of contents without explicit media type.
+    pub fn with_media_type<S: Into<String>>(mut self, media_type: S) -> Self {
+        self.media_type = media_type.into();
+        self
+    }
+
+    /// Sets the policy for inlining references to schemas.
+    pub fn with_inlining(mut self, inlining: Inlining) -> Self {
+        self.inlining = inlining;
+        self
+    }
+
+    pub fn into_openapi(self) -> Result<OpenAPI> {
+        self.build().map(|(definition, _)| definition)
+    }
+
+    /// Builds the OpenAPI definition. Returns the conflicts with the base document, if any.
+    pub fn build(mut self) -> Result<(OpenAPI, Vec<Conflict>)> {
+        let mut definition = if let Some(base) = self.base.take() {
             base
         } else {
             self.default_base()
         };
-        definition.paths = paths;
-        // Keep non-schema components
-        definition
-            .components
-            .get_or_insert(Default::default())
-            .schemas = components.schemas;
-        definition
+        let paths = self.all_paths(&definition.openapi)?;
+        let components = self.all_components();
+        let target = definition.components.get_or_insert(Default::default());
+        let conflicts = if self.merge == Merge::Replace {
+            definition.paths = paths;
+            target.schemas = components.schemas;
+            // Keep other components from the base.
+            target.responses.extend(components.responses);
+            target.request_bodies.extend(components.request_bodies);
+            target.headers.extend(components.headers);
+            target.parameters.extend(components.parameters);
+            Vec::new()
+        } else {
+            let span = |path: &str| {
+                let rel = self.spec.rels.iter().find(|r| r.uri.pattern() == path);
+                rel.and_then(|r| r.span.clone())
+            };
+            let mut conflicts = merge::merge_paths(&mut definition.paths, paths, span);
+            conflicts.extend(merge::merge_components(target, components));
+            conflicts
+        };
+        if self.merge == Merge::ErrorOnConflict && !conflicts.is_empty() {
+            return Err(Error::Conflicts(conflicts));
+        }
+        Ok((definition, conflicts))
     }
 
     fn default_base(&self) -> OpenAPI {
@@ -66,7 +229,7 @@ impl Builder {
     }
 
     fn media_type(&self) -> String {
-        "application/json".to_owned()
+        self.media_type.clone()
     }
 
     fn uri_example_default(&self, uri: &spec::Uri) -> String {
@@ -189,8 +352,11 @@ impl Builder {
             .iter()
             .map(|p| {
                 let ident = p.name.as_ref().into();
-                let expr = into_box_ref(self.schema(&p.schema));
-                (ident, expr)
+                let mut schema = self.schema(&p.schema);
+                if let (true, ReferenceOr::Item(s)) = (p.deprecated, &mut schema) {
+                    s.schema_data.deprecated = true;
+                }
+                (ident, into_box_ref(schema))
             })
             .collect();
         let required = obj
@@ -249,11 +415,22 @@ impl Builder {
     }
 
     fn maybe_inline(&self, name: &atom::Ident) -> Option<&spec::Schema> {
+        if self.inlining == Inlining::NeverInline {
+            return None;
+        }
+        let Some(spec::Reference::Schema(s)) = self.spec.refs.get(name) else {
+            return None;
+        };
+        // Small schemas without references can be inlined, whether named or not.
+        if let Inlining::BelowSize(n) = self.inlining {
+            if inlining::schema_size(s).is_some_and(|size| size < n) {
+                return Some(s);
+            }
+        }
         // Implicit and atomic references should be inlined.
         if name.is_reference() {
             return None;
         }
-        let spec::Reference::Schema(s) = self.spec.refs.get(name).expect("reference should exist");
         match s.expr {
             spec::SchemaExpr::Num(_)
             | spec::SchemaExpr::Str(_)
@@ -295,6 +472,7 @@ impl Builder {
         };
         sch.schema_data.description = s.desc.clone();
         sch.schema_data.title = s.title.clone();
+        sch.schema_data.deprecated = s.deprecated;
         ReferenceOr::Item(sch)
     }
 
@@ -311,7 +489,7 @@ impl Builder {
             name: prop.name.as_ref().into(),
             description: prop.desc.clone(),
             required,
-            deprecated: None,
+            deprecated: prop.deprecated.then_some(true),
             format: ParameterSchemaOrContent::Schema(self.schema(&prop.schema)),
             example: None,
             examples: Default::default(),
@@ -348,7 +526,7 @@ impl Builder {
             description: prop.desc.clone(),
             style: Default::default(),
             required: prop.required.unwrap_or(false),
-            deprecated: None,
+            deprecated: prop.deprecated.then_some(true),
             format: ParameterSchemaOrContent::Schema(self.schema(&prop.schema)),
             example: None,
             examples: Default::default(),
@@ -356,16 +534,31 @@ impl Builder {
         }
     }
 
+    fn prop_param(&self, prop: &spec::Property, loc: ParamIn) -> Parameter {
+        match loc {
+            ParamIn::Path => self.prop_path_param(prop),
+            ParamIn::Query => self.prop_query_param(prop),
+            ParamIn::Header => self.prop_header_param(prop),
+        }
+    }
+
+    fn param(&self, prop: &spec::Property, loc: ParamIn) -> ReferenceOr<Parameter> {
+        match &prop.reference {
+            Some(r) if self.usage.params.get(r) == Some(&loc) => component_ref("parameters", r),
+            _ => ReferenceOr::Item(self.prop_param(prop, loc)),
+        }
+    }
+
     fn xfer_params(&self, xfer: &spec::Transfer) -> Vec<ReferenceOr<Parameter>> {
         let mut params = Vec::new();
         if let Some(o) = xfer.params.as_ref() {
             for p in o.props.iter() {
-                params.push(ReferenceOr::Item(self.prop_query_param(p)));
+                params.push(self.param(p, ParamIn::Query));
             }
         }
         if let Some(o) = xfer.domain.headers.as_ref() {
             for p in o.props.iter() {
-                params.push(ReferenceOr::Item(self.prop_header_param(p)));
+                params.push(self.param(p, ParamIn::Header));
             }
         }
         params
@@ -375,32 +568,38 @@ impl Builder {
         let mut params = Vec::new();
         for s in uri.path.iter() {
             if let spec::UriSegment::Variable(p) = s {
-                params.push(ReferenceOr::Item(self.prop_path_param(p)));
+                params.push(self.param(p, ParamIn::Path));
             }
         }
         if let Some(o) = uri.params.as_ref() {
             for p in o.props.iter() {
-                params.push(ReferenceOr::Item(self.prop_query_param(p)));
+                params.push(self.param(p, ParamIn::Query));
             }
         }
         params
     }
 
-    fn domain_request(&self, domain: &spec::Content) -> Option<ReferenceOr<RequestBody>> {
-        let media = domain.media.clone().unwrap_or_else(|| self.media_type());
-        domain.schema.as_ref().map(|schema| {
-            ReferenceOr::Item(RequestBody {
-                content: indexmap! { media => MediaType {
-                    schema: Some(self.schema(schema)),
-                    examples: self.content_examples(domain),
-                    ..Default::default()
-                }},
-                description: domain.desc.clone(),
+    fn content_request(&self, content: &spec::Content) -> Option<RequestBody> {
+        let media = content.media.clone().unwrap_or_else(|| self.media_type());
+        content.schema.as_ref().map(|schema| RequestBody {
+            content: indexmap! { media => MediaType {
+                schema: Some(self.schema(schema)),
+                examples: self.content_examples(content),
                 ..Default::default()
-            })
+            }},
+            description: content.desc.clone(),
+            ..Default::default()
         })
     }
 
+    fn domain_request(&self, domain: &spec::Content) -> Option<ReferenceOr<RequestBody>> {
+        let body = self.content_request(domain)?;
+        match &domain.reference {
+            Some(r) => Some(component_ref("requestBodies", r)),
+            None => Some(ReferenceOr::Item(body)),
+        }
+    }
+
     fn xfer_request(&self, xfer: &spec::Transfer) -> Option<ReferenceOr<RequestBody>> {
         self.domain_request(&xfer.domain)
     }
@@ -423,10 +622,11 @@ impl Builder {
             h.props
                 .iter()
                 .map(|p| {
-                    (
-                        p.name.as_ref().to_owned(),
-                        ReferenceOr::Item(self.prop_header(p)),
-                    )
+                    let header = match &p.reference {
+                        Some(r) => component_ref("headers", r),
+                        None => ReferenceOr::Item(self.prop_header(p)),
+                    };
+                    (p.name.as_ref().to_owned(), header)
                 })
                 .collect()
         })
@@ -452,11 +652,35 @@ impl Builder {
         }
     }
 
+    /// Adds a content to a response.
+    fn content_response(&self, res: &mut Response, content: &spec::Content) {
+        if let Some(schema) = content.schema.as_ref() {
+            let media_type = content.media.clone().unwrap_or_else(|| self.media_type());
+            let media_schema = MediaType {
+                schema: Some(self.schema(schema)),
+                examples: self.content_examples(content),
+                ..Default::default()
+            };
+            res.content.insert(media_type, media_schema);
+        }
+        res.headers = self.content_headers(content);
+        res.description = content.desc.clone().unwrap_or_else(|| "".to_owned());
+    }
+
     fn xfer_responses(&self, xfer: &spec::Transfer) -> Responses {
         let mut default = None;
         let mut responses = IndexMap::new();
 
-        for ((status, media), content) in xfer.ranges.iter() {
+        for ((status, _), content) in xfer.ranges.iter() {
+            if let Some(r) = response_reference(&xfer.ranges, status, content) {
+                let reference = component_ref("responses", r);
+                if let Some(s) = status {
+                    responses.insert(self.http_status_code(s), reference);
+                } else {
+                    default = Some(reference);
+                }
+                continue;
+            }
             let response = if let Some(s) = status {
                 responses
                     .entry(self.http_status_code(s))
@@ -465,17 +689,7 @@ impl Builder {
                 default.insert(ReferenceOr::Item(Response::default()))
             };
             if let ReferenceOr::Item(res) = response {
-                if let Some(schema) = content.schema.as_ref() {
-                    let media_type = media.clone().unwrap_or_else(|| self.media_type());
-                    let media_schema = MediaType {
-                        schema: Some(self.schema(schema)),
-                        examples: self.content_examples(content),
-                        ..Default::default()
-                    };
-                    res.content.insert(media_type, media_schema);
-                }
-                res.headers = self.content_headers(content);
-                res.description = content.desc.clone().unwrap_or_else(|| "".to_owned());
+                self.content_response(res, content);
             } else {
                 unreachable!();
             }
@@ -488,50 +702,12 @@ impl Builder {
         }
     }
 
-    fn method_label(&self, m: atom::Method) -> &str {
-        match m {
-            atom::Method::Get => "get",
-            atom::Method::Put => "put",
-            atom::Method::Post => "post",
-            atom::Method::Patch => "patch",
-            atom::Method::Delete => "delete",
-            atom::Method::Options => "options",
-            atom::Method::Head => "head",
-        }
-    }
-
-    fn uri_segment_label(&self, s: &spec::UriSegment) -> String {
-        match s {
-            spec::UriSegment::Literal(l) => {
-                let l = l.as_ref();
-                if l.is_empty() {
-                    "root".to_owned()
-                } else {
-                    l.to_lowercase()
-                }
-            }
-            spec::UriSegment::Variable(t) => t.name.as_ref().to_lowercase(),
-        }
-    }
-
-    fn xfer_id(
+    fn relation_path_item(
         &self,
-        xfer: &spec::Transfer,
-        method: atom::Method,
-        uri: &spec::Uri,
-    ) -> Option<String> {
-        if xfer.id.is_some() {
-            return xfer.id.clone();
-        }
-        let prefix = self.method_label(method).to_owned();
-        let label = once(prefix)
-            .chain(uri.path.iter().map(|s| self.uri_segment_label(s)))
-            .collect::<Vec<_>>()
-            .join("-");
-        Some(label)
-    }
-
-    fn relation_path_item(&self, rel: &spec::Relation) -> PathItem {
+        rel: &spec::Relation,
+        version: &str,
+        ids: &mut HashMap<String, Option<Span>>,
+    ) -> Result<PathItem> {
         let mut path_item = PathItem {
             parameters: self.uri_params(&rel.uri),
             ..Default::default()
@@ -543,7 +719,17 @@ impl Builder {
             .filter_map(|(m, x)| x.as_ref().map(|x| (m, x)));
 
         for (method, xfer) in xfers {
-            let operation_id = self.xfer_id(xfer, method, &rel.uri);
+            let operation_id = Some(self.naming.transfer_id(xfer, method, &rel.uri));
+            if let Some(id) = &operation_id {
+                let span = xfer.span.clone().or_else(|| rel.span.clone());
+                if let Some(previous) = ids.insert(id.clone(), span.clone()) {
+                    return Err(Error::DuplicateOperationId {
+                        id: id.clone(),
+                        span,
+                        previous,
+                    });
+                }
+            }
             let summary = xfer
                 .summary
                 .clone()
@@ -559,6 +745,7 @@ impl Builder {
                 request_body: self.xfer_request(xfer),
                 responses: self.xfer_responses(xfer),
                 tags: xfer.tags.clone(),
+                deprecated: xfer.deprecated,
                 ..Default::default()
             };
 
@@ -570,41 +757,92 @@ impl Builder {
                 atom::Method::Delete => path_item.delete = Some(op),
                 atom::Method::Options => path_item.options = Some(op),
                 atom::Method::Head => path_item.head = Some(op),
+                atom::Method::Trace => path_item.trace = Some(op),
+                atom::Method::Query => {
+                    if !supports_query(version) {
+                        return Err(Error::UnsupportedMethod {
+                            method: "QUERY".to_owned(),
+                            path: rel.uri.pattern(),
+                            version: version.to_owned(),
+                        });
+                    }
+                    // The OpenAPI data model predates the QUERY method,
+                    // hence the operation is appended as an additional field.
+                    let op = serde_json::to_value(op).expect("operation should serialize");
+                    path_item.extensions.insert("query".to_owned(), op);
+                }
             }
         }
 
-        path_item
+        Ok(path_item)
     }
 
-    fn all_paths(&self) -> Paths {
+    fn all_paths(&self, version: &str) -> Result<Paths> {
+        // Operation identifiers must be unique across the whole definition.
+        let mut ids = HashMap::new();
         let paths = self
             .spec
             .rels
             .iter()
             .map(|rel| {
-                (
-                    rel.uri.pattern(),
-                    ReferenceOr::Item(self.relation_path_item(rel)),
-                )
+                let path_item = self.relation_path_item(rel, version, &mut ids)?;
+                Ok((rel.uri.pattern(), ReferenceOr::Item(path_item)))
             })
-            .collect();
-        Paths {
+            .collect::<Result<_>>()?;
+        Ok(Paths {
             paths,
             extensions: Default::default(),
+        })
+    }
+
+    /// Adds a content component, depending on whether it is used as a response or a request body.
+    fn content_component(&self, c: &mut Components, name: &atom::Ident, content: &spec::Content) {
+        if self.usage.responses.contains(name) {
+            let mut res = Response::default();
+            self.content_response(&mut res, content);
+            c.responses.insert(name.untagged(), ReferenceOr::Item(res));
+        }
+        if self.usage.bodies.contains(name) {
+            if let Some(body) = self.content_request(content) {
+                c.request_bodies
+                    .insert(name.untagged(), ReferenceOr::Item(body));
+            }
         }
     }
 
     fn all_components(&self) -> Components {
-        let mut schemas = IndexMap::new();
-        for (name, spec::Reference::Schema(s)) in self.spec.refs.iter() {
-            // Only keep components that couldn't be inlined.
-            if self.maybe_inline(name).is_none() {
-                schemas.insert(name.untagged(), self.schema(s));
+        let mut c = Components::default();
+        for (name, reference) in self.spec.refs.iter() {
+            match reference {
+                spec::Reference::Schema(s) => {
+                    // Only keep components that couldn't be inlined.
+                    if self.maybe_inline(name).is_none() {
+                        c.schemas.insert(name.untagged(), self.schema(s));
+                    }
+                }
+                spec::Reference::Content(content) => self.content_component(&mut c, name, content),
+                spec::Reference::Ranges(ranges) => {
+                    for content in ranges.values() {
+                        if let Some(r) = &content.reference {
+                            // Contents that are references themselves have their own components.
+                            if !self.spec.refs.contains_key(r) {
+                                self.content_component(&mut c, r, content);
+                            }
+                        }
+                    }
+                }
+                spec::Reference::Property(prop) => {
+                    if self.usage.headers.contains(name) {
+                        let header = ReferenceOr::Item(self.prop_header(prop));
+                        c.headers.insert(name.untagged(), header);
+                    }
+                    if let Some(loc) = self.usage.params.get(name) {
+                        let param = ReferenceOr::Item(self.prop_param(prop, *loc));
+                        c.parameters.insert(name.untagged(), param);
+                    }
+                }
             }
         }
-        Components {
-            schemas,
-            ..Default::default()
-        }
+        c
     }
 }
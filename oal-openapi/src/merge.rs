@@ -0,0 +1,202 @@
+use indexmap::IndexMap;
+use oal_model::span::Span;
+use openapiv3::{Components, Operation, PathItem, Paths, ReferenceOr};
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+/// The strategy for combining the generated definition with the base document.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Merge {
+    /// The generated paths and schemas replace those of the base document.
+    #[default]
+    Replace,
+    /// The base paths and components are kept, the generated ones taking precedence on conflicts.
+    Merge,
+    /// The base paths and components are kept, conflicts being reported as errors.
+    ErrorOnConflict,
+}
+
+impl FromStr for Merge {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "replace" => Ok(Merge::Replace),
+            "merge" => Ok(Merge::Merge),
+            "error-on-conflict" => Ok(Merge::ErrorOnConflict),
+            _ => Err(format!(
+                "unknown merge mode '{s}', expected replace, merge or error-on-conflict"
+            )),
+        }
+    }
+}
+
+/// A conflict between the generated definition and the base document.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Conflict {
+    /// The base document defines the path by reference.
+    Path { path: String, span: Option<Span> },
+    /// Both definitions have an operation for the same path and method.
+    Operation {
+        path: String,
+        method: String,
+        span: Option<Span>,
+    },
+    /// Both definitions have a component of the same kind and name.
+    Component { kind: &'static str, name: String },
+}
+
+impl Conflict {
+    /// Returns the span of the generated definition in conflict, if any.
+    pub fn span(&self) -> Option<&Span> {
+        match self {
+            Conflict::Path { span, .. } | Conflict::Operation { span, .. } => span.as_ref(),
+            Conflict::Component { .. } => None,
+        }
+    }
+}
+
+impl Display for Conflict {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Conflict::Path { path, .. } => {
+                write!(f, "path {path} is a reference in the base document")
+            }
+            Conflict::Operation { path, method, .. } => {
+                write!(
+                    f,
+                    "operation {method} {path} is already in the base document"
+                )
+            }
+            Conflict::Component { kind, name } => {
+                write!(f, "{kind} '{name}' is already in the base document")
+            }
+        }
+    }
+}
+
+fn operation_mut<'a>(item: &'a mut PathItem, method: &str) -> &'a mut Option<Operation> {
+    match method {
+        "get" => &mut item.get,
+        "put" => &mut item.put,
+        "post" => &mut item.post,
+        "delete" => &mut item.delete,
+        "options" => &mut item.options,
+        "head" => &mut item.head,
+        "patch" => &mut item.patch,
+        "trace" => &mut item.trace,
+        _ => unreachable!("unknown method {method}"),
+    }
+}
+
+/// Merges a generated path item into a base path item.
+fn merge_path_item(
+    base: &mut PathItem,
+    generated: PathItem,
+    path: &str,
+    span: &Option<Span>,
+    conflicts: &mut Vec<Conflict>,
+) {
+    let conflict = |method: &str| Conflict::Operation {
+        path: path.to_owned(),
+        method: method.to_uppercase(),
+        span: span.clone(),
+    };
+    for p in generated.parameters.iter() {
+        if !base.parameters.contains(p) {
+            base.parameters.push(p.clone());
+        }
+    }
+    for (key, op) in generated.extensions.iter() {
+        // The QUERY method is represented as an extension.
+        if base.extensions.insert(key.clone(), op.clone()).is_some() {
+            conflicts.push(conflict(key));
+        }
+    }
+    for (method, op) in generated.into_iter() {
+        if operation_mut(base, method).replace(op).is_some() {
+            conflicts.push(conflict(method));
+        }
+    }
+}
+
+/// Merges generated paths into base paths. Returns the conflicts.
+///
+/// The span of the resource defining each generated path is given by `span`.
+pub fn merge_paths<F>(base: &mut Paths, generated: Paths, span: F) -> Vec<Conflict>
+where
+    F: Fn(&str) -> Option<Span>,
+{
+    let mut conflicts = Vec::new();
+    for (path, item) in generated.paths {
+        let ReferenceOr::Item(item) = item else {
+            unreachable!("generated paths should not be references")
+        };
+        match base.paths.get_mut(&path) {
+            None => {
+                base.paths.insert(path, ReferenceOr::Item(item));
+            }
+            Some(ReferenceOr::Item(base_item)) => {
+                merge_path_item(base_item, item, &path, &span(&path), &mut conflicts);
+            }
+            Some(base_ref) => {
+                conflicts.push(Conflict::Path {
+                    path: path.clone(),
+                    span: span(&path),
+                });
+                *base_ref = ReferenceOr::Item(item);
+            }
+        }
+    }
+    conflicts
+}
+
+/// Merges generated components of the given kind into base components. Returns the conflicts.
+fn merge_kind<T>(
+    base: &mut IndexMap<String, ReferenceOr<T>>,
+    generated: IndexMap<String, ReferenceOr<T>>,
+    kind: &'static str,
+    conflicts: &mut Vec<Conflict>,
+) {
+    for (name, component) in generated {
+        if base.insert(name.clone(), component).is_some() {
+            conflicts.push(Conflict::Component { kind, name });
+        }
+    }
+}
+
+/// Merges generated components into base components. Returns the conflicts.
+pub fn merge_components(base: &mut Components, generated: Components) -> Vec<Conflict> {
+    let mut conflicts = Vec::new();
+    merge_kind(
+        &mut base.schemas,
+        generated.schemas,
+        "schema",
+        &mut conflicts,
+    );
+    merge_kind(
+        &mut base.responses,
+        generated.responses,
+        "response",
+        &mut conflicts,
+    );
+    merge_kind(
+        &mut base.request_bodies,
+        generated.request_bodies,
+        "request body",
+        &mut conflicts,
+    );
+    merge_kind(
+        &mut base.headers,
+        generated.headers,
+        "header",
+        &mut conflicts,
+    );
+    merge_kind(
+        &mut base.parameters,
+        generated.parameters,
+        "parameter",
+        &mut conflicts,
+    );
+    conflicts
+}
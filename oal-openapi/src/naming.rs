@@ -0,0 +1,129 @@
+use oal_compiler::spec::{method_label, Transfer, Uri, UriSegment};
+use oal_syntax::atom;
+use std::str::FromStr;
+
+/// The naming strategy for operation identifiers.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub enum Naming {
+    /// Lowercase words separated by hyphens, e.g. `get-items-id`.
+    #[default]
+    Kebab,
+    /// Lowercase words separated by underscores, e.g. `get_items_id`.
+    Snake,
+    /// Capitalized words except the first one, e.g. `getItemsId`.
+    Camel,
+    /// A template with `{method}`, `{path}` and `{tag}` placeholders, e.g. `{tag}-{method}-{path}`.
+    Template(String),
+}
+
+impl FromStr for Naming {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "kebab-case" => Ok(Naming::Kebab),
+            "snake_case" => Ok(Naming::Snake),
+            "camelCase" => Ok(Naming::Camel),
+            _ if s.contains('{') => Ok(Naming::Template(s.to_owned())),
+            _ => Err(format!(
+                "unknown naming strategy '{s}', expected kebab-case, snake_case, camelCase or a template"
+            )),
+        }
+    }
+}
+
+/// Splits a label into lowercase alphanumeric words.
+fn words(label: &str) -> impl Iterator<Item = String> + '_ {
+    label
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .map(str::to_lowercase)
+}
+
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(c) => c.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+fn uri_segment_label(s: &UriSegment) -> String {
+    match s {
+        UriSegment::Literal(l) => {
+            let l = l.as_ref();
+            if l.is_empty() {
+                "root".to_owned()
+            } else {
+                l.to_lowercase()
+            }
+        }
+        UriSegment::Variable(t) => t.name.as_ref().to_lowercase(),
+    }
+}
+
+/// Removes a placeholder from a template along with its separator,
+/// e.g. `{tag}` from `{tag}-{method}` gives `{method}`.
+fn drop_placeholder(template: &str, placeholder: &str) -> String {
+    let is_separator = |c: char| !c.is_alphanumeric() && c != '{' && c != '}';
+    let mut id = template.to_owned();
+    while let Some(start) = id.find(placeholder) {
+        let end = start + placeholder.len();
+        let after = id[end..].trim_start_matches(is_separator);
+        if after.is_empty() {
+            // The trailing placeholder takes the preceding separator.
+            let before = id[..start].trim_end_matches(is_separator).len();
+            id.replace_range(before..end, "");
+        } else {
+            let next = id.len() - after.len();
+            id.replace_range(start..next, "");
+        }
+    }
+    id
+}
+
+impl Naming {
+    /// Returns the operation identifier for the given method label, path segment labels and tags.
+    pub fn operation_id(&self, method: &str, segments: &[String], tags: &[String]) -> String {
+        match self {
+            Naming::Kebab => {
+                let mut parts = vec![method.to_owned()];
+                parts.extend(segments.iter().cloned());
+                parts.join("-")
+            }
+            Naming::Snake => {
+                let parts = words(method).chain(segments.iter().flat_map(|s| words(s)));
+                parts.collect::<Vec<_>>().join("_")
+            }
+            Naming::Camel => {
+                let mut parts = words(method).chain(segments.iter().flat_map(|s| words(s)));
+                let first = parts.next().unwrap_or_default();
+                std::iter::once(first)
+                    .chain(parts.map(|w| capitalize(&w)))
+                    .collect()
+            }
+            Naming::Template(t) => {
+                let path = segments.join("-");
+                let tag = tags.first().map(String::as_str).unwrap_or_default();
+                [("{method}", method), ("{path}", &path), ("{tag}", tag)]
+                    .into_iter()
+                    .fold(t.clone(), |id, (placeholder, value)| {
+                        if value.is_empty() {
+                            drop_placeholder(&id, placeholder)
+                        } else {
+                            id.replace(placeholder, value)
+                        }
+                    })
+            }
+        }
+    }
+
+    /// Returns the operation identifier of a transfer, explicit or derived from its method and URI.
+    pub fn transfer_id(&self, xfer: &Transfer, method: atom::Method, uri: &Uri) -> String {
+        if let Some(id) = &xfer.id {
+            return id.clone();
+        }
+        let segments = uri.path.iter().map(uri_segment_label).collect::<Vec<_>>();
+        self.operation_id(&method_label(method).to_lowercase(), &segments, &xfer.tags)
+    }
+}
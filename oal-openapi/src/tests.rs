@@ -0,0 +1,551 @@
+use crate::errors::Error;
+use crate::{Builder, Inlining, Merge, Naming};
+use oal_compiler::tests::eval;
+use openapiv3::{OpenAPI, ReferenceOr};
+
+fn base(version: &str) -> OpenAPI {
+    OpenAPI {
+        openapi: version.to_owned(),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn openapi_trace_method() -> anyhow::Result<()> {
+    let spec = eval("res /a on trace -> <>;")?;
+    let api = Builder::new(spec).into_openapi()?;
+
+    let path = api.paths.paths.get("/a").expect("expected a path");
+    let item = path.as_item().expect("expected a path item");
+    assert!(item.trace.is_some());
+
+    Ok(())
+}
+
+#[test]
+fn openapi_query_method() -> anyhow::Result<()> {
+    let code = "res /a on query : <{}> -> <{}>;";
+
+    let err = Builder::new(eval(code)?)
+        .with_base(base("3.0.3"))
+        .into_openapi()
+        .expect_err("QUERY should not be supported by OpenAPI 3.0");
+    assert!(matches!(err, Error::UnsupportedMethod { .. }));
+
+    let api = Builder::new(eval(code)?)
+        .with_base(base("3.2.0"))
+        .into_openapi()?;
+    let path = api.paths.paths.get("/a").expect("expected a path");
+    let item = path.as_item().expect("expected a path item");
+    assert!(item.extensions.contains_key("query"));
+
+    let yaml = serde_yaml::to_string(&api)?;
+    assert!(yaml.contains("query:"));
+
+    Ok(())
+}
+
+#[test]
+fn openapi_operation_id_naming() -> anyhow::Result<()> {
+    let code = r#"
+        # tags: [stuff]
+        let op = get -> {};
+        res /my_items/{ 'id str } on op;
+    "#;
+
+    let cases = [
+        (Naming::Kebab, "get-my_items-id"),
+        (Naming::Snake, "get_my_items_id"),
+        (Naming::Camel, "getMyItemsId"),
+        (
+            Naming::Template("{tag}.{method}.{path}".to_owned()),
+            "stuff.get.my_items-id",
+        ),
+    ];
+
+    for (naming, id) in cases {
+        let api = Builder::new(eval(code)?)
+            .with_naming(naming)
+            .into_openapi()?;
+        let path = api
+            .paths
+            .paths
+            .get("/my_items/{id}")
+            .expect("expected a path");
+        let item = path.as_item().expect("expected a path item");
+        let op = item.get.as_ref().expect("expected an operation");
+        assert_eq!(op.operation_id.as_deref(), Some(id));
+    }
+
+    // Placeholders without value are dropped along with their separator.
+    let code = "res /items on get -> {};";
+    let cases = [
+        ("{tag}.{method}.{path}", "get.items"),
+        ("{method}_{path}_{tag}", "get_items"),
+        ("{method}-{tag}-{path}", "get-items"),
+    ];
+    for (template, id) in cases {
+        let api = Builder::new(eval(code)?)
+            .with_naming(Naming::Template(template.to_owned()))
+            .into_openapi()?;
+        let path = api.paths.paths.get("/items").expect("expected a path");
+        let item = path.as_item().expect("expected a path item");
+        let op = item.get.as_ref().expect("expected an operation");
+        assert_eq!(op.operation_id.as_deref(), Some(id));
+    }
+
+    assert!("camelCase".parse::<Naming>().is_ok());
+    assert!("PascalCase".parse::<Naming>().is_err());
+
+    Ok(())
+}
+
+#[test]
+fn openapi_duplicate_operation_id() -> anyhow::Result<()> {
+    let cases = [
+        "res /Items on get -> {}; res /items on put -> {}, get -> {};",
+        "res /a on (get -> {}) `operationId: x`; res /b on put -> {}, (get -> {}) `operationId: x`;",
+    ];
+
+    for code in cases {
+        let err = Builder::new(eval(code)?)
+            .into_openapi()
+            .expect_err("expected a duplicate operation identifier");
+        assert!(matches!(err, Error::DuplicateOperationId { .. }));
+        // Both the duplicate and the previous use are located at the transfers.
+        let span = err.span().expect("expected a span");
+        assert_eq!(span.range().start, code.rfind("get -> {}").unwrap());
+        assert_eq!(&code[span.range()], "get -> {}");
+        let labels = err.labels();
+        assert_eq!(labels.len(), 1);
+        assert_eq!(labels[0].0.range().start, code.find("get -> {}").unwrap());
+    }
+
+    Ok(())
+}
+
+#[test]
+fn openapi_reference_components() -> anyhow::Result<()> {
+    let code = r#"
+        let @id = 'id str;
+        let @trace = 'x-trace str;
+        let @item = <{ 'name str }>;
+        let @err = <status=404, headers={ @trace }, { 'msg str }>;
+        let @errs = <status=500, {}> :: <status=503, {}>;
+        res /a/{ @id } on put : @item -> @item :: @err :: @errs;
+        res /b/{ @id } on get -> <status=200, {}> :: @err;
+    "#;
+
+    let api = Builder::new(eval(code)?).into_openapi()?;
+    let yaml = serde_yaml::to_string(&api)?;
+
+    let components = api.components.expect("expected components");
+    assert_eq!(components.parameters.keys().collect::<Vec<_>>(), ["id"]);
+    assert_eq!(components.headers.keys().collect::<Vec<_>>(), ["trace"]);
+    assert_eq!(
+        components.request_bodies.keys().collect::<Vec<_>>(),
+        ["item"]
+    );
+    assert_eq!(
+        components.responses.keys().collect::<Vec<_>>(),
+        ["item", "err", "errs-500", "errs-503"]
+    );
+
+    let path = api.paths.paths.get("/b/{id}").expect("expected a path");
+    let item = path.as_item().expect("expected a path item");
+    let op = item.get.as_ref().expect("expected an operation");
+    let res = op
+        .responses
+        .responses
+        .get(&openapiv3::StatusCode::Code(404))
+        .expect("expected a response");
+    assert!(
+        matches!(res, ReferenceOr::Reference { reference } if reference == "#/components/responses/err")
+    );
+    assert!(matches!(
+        &item.parameters[0],
+        ReferenceOr::Reference { reference } if reference == "#/components/parameters/id"
+    ));
+
+    assert!(yaml.contains("$ref: '#/components/requestBodies/item'"));
+    assert!(yaml.contains("$ref: '#/components/headers/trace'"));
+    assert!(yaml.contains("$ref: '#/components/responses/errs-503'"));
+
+    Ok(())
+}
+
+#[test]
+fn openapi_merge_base() -> anyhow::Result<()> {
+    let base: OpenAPI = serde_yaml::from_str(
+        r#"
+openapi: 3.0.3
+info:
+  title: Test
+  version: 0.1.0
+paths:
+  /a:
+    get:
+      responses: {}
+  /manual:
+    get:
+      responses: {}
+components:
+  schemas:
+    x:
+      type: string
+    manual:
+      type: string
+"#,
+    )?;
+    let code = "let @x = {}; res /a on get -> @x; res /a on put : <@x> -> <>;";
+
+    let api = Builder::new(eval(code)?)
+        .with_base(base.clone())
+        .into_openapi()?;
+    assert_eq!(api.paths.paths.keys().collect::<Vec<_>>(), ["/a"]);
+
+    let (api, conflicts) = Builder::new(eval(code)?)
+        .with_base(base.clone())
+        .with_merge(Merge::Merge)
+        .build()?;
+    assert_eq!(
+        api.paths.paths.keys().collect::<Vec<_>>(),
+        ["/a", "/manual"]
+    );
+    let item = api.paths.paths["/a"]
+        .as_item()
+        .expect("expected a path item");
+    assert!(item.get.is_some() && item.put.is_some());
+    let schemas = api.components.expect("expected components").schemas;
+    assert_eq!(schemas.keys().collect::<Vec<_>>(), ["x", "manual"]);
+    assert_eq!(
+        conflicts
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>(),
+        [
+            "operation GET /a is already in the base document",
+            "schema 'x' is already in the base document"
+        ]
+    );
+    assert!(conflicts[0].span().is_some());
+
+    let err = Builder::new(eval(code)?)
+        .with_base(base)
+        .with_merge(Merge::ErrorOnConflict)
+        .build()
+        .expect_err("expected conflicts");
+    assert!(matches!(err, Error::Conflicts(c) if c.len() == 2));
+
+    Ok(())
+}
+
+#[test]
+fn openapi_merge_components() -> anyhow::Result<()> {
+    let base: OpenAPI = serde_yaml::from_str(
+        r#"
+openapi: 3.0.3
+info:
+  title: Test
+  version: 0.1.0
+paths: {}
+components:
+  requestBodies:
+    item:
+      content: {}
+  headers:
+    manual:
+      schema:
+        type: string
+"#,
+    )?;
+    let code = "let @item = <{}>; res /a on put : @item -> <>;";
+
+    let (api, conflicts) = Builder::new(eval(code)?)
+        .with_base(base.clone())
+        .with_merge(Merge::Merge)
+        .build()?;
+    let components = api.components.expect("expected components");
+    assert_eq!(
+        components.request_bodies.keys().collect::<Vec<_>>(),
+        ["item"]
+    );
+    assert_eq!(components.headers.keys().collect::<Vec<_>>(), ["manual"]);
+    assert_eq!(
+        conflicts
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>(),
+        ["request body 'item' is already in the base document"]
+    );
+
+    let err = Builder::new(eval(code)?)
+        .with_base(base)
+        .with_merge(Merge::ErrorOnConflict)
+        .build()
+        .expect_err("expected conflicts");
+    assert!(matches!(err, Error::Conflicts(c) if c.len() == 1));
+
+    Ok(())
+}
+
+#[test]
+fn openapi_media_type_and_inlining() -> anyhow::Result<()> {
+    let code = r#"
+        let @id = str;
+        let @obj = { 'id @id, 'name str, 'tags [str] };
+        res /a on get -> @obj;
+    "#;
+
+    let cases = [
+        (Inlining::AlwaysRef, vec!["obj", "id"]),
+        (Inlining::BelowSize(2), vec!["obj"]),
+        // Schemas with references are never inlined.
+        (Inlining::BelowSize(10), vec!["obj"]),
+        (Inlining::NeverInline, vec!["obj", "id"]),
+    ];
+
+    for (inlining, schemas) in cases {
+        let api = Builder::new(eval(code)?)
+            .with_media_type("application/vnd.api+json")
+            .with_inlining(inlining)
+            .into_openapi()?;
+        let components = api.components.expect("expected components");
+        assert_eq!(components.schemas.keys().collect::<Vec<_>>(), schemas);
+        let path = api.paths.paths.get("/a").expect("expected a path");
+        let op = path.as_item().and_then(|i| i.get.as_ref()).unwrap();
+        let res = op.responses.default.as_ref().unwrap().as_item().unwrap();
+        assert!(res.content.contains_key("application/vnd.api+json"));
+    }
+
+    Ok(())
+}
+
+#[test]
+fn openapi_deprecated() -> anyhow::Result<()> {
+    let code = r#"
+        # deprecatedSince: 2
+        let op = get : { 'q str `deprecatedSince: 1` } -> { 'a int `deprecatedSince: 1` };
+        res /a on op;
+    "#;
+    let mut spec = eval(code)?;
+    oal_compiler::versioning::select(&mut spec, &"2".into())?;
+    let api = Builder::new(spec).into_openapi()?;
+
+    let path = api.paths.paths.get("/a").expect("expected a path");
+    let op = path.as_item().unwrap().get.as_ref().unwrap();
+    assert!(op.deprecated);
+
+    let yaml = serde_yaml::to_string(&api)?;
+    assert_eq!(yaml.matches("deprecated: true").count(), 3);
+
+    Ok(())
+}
+
+const PETSTORE: &str = r##"
+openapi: 3.0.3
+info:
+  title: Petstore
+  version: 1.0.0
+paths:
+  /pets:
+    get:
+      summary: List all pets
+      operationId: listPets
+      tags: [pets]
+      parameters:
+        - $ref: '#/components/parameters/limit'
+      responses:
+        '200':
+          description: A paged array of pets
+          headers:
+            x-next:
+              description: A link to the next page
+              schema:
+                type: string
+          content:
+            application/json:
+              schema:
+                $ref: '#/components/schemas/Pets'
+        default:
+          $ref: '#/components/responses/Error'
+    post:
+      operationId: createPet
+      requestBody:
+        content:
+          application/json:
+            schema:
+              $ref: '#/components/schemas/Pet'
+      responses:
+        '201':
+          description: Created
+        default:
+          $ref: '#/components/responses/Error'
+  /pets/{petId}:
+    parameters:
+      - name: petId
+        in: path
+        required: true
+        schema:
+          type: string
+    get:
+      operationId: showPetById
+      parameters:
+        - name: X-Request-Id
+          in: header
+          schema:
+            type: string
+            format: uuid
+        - name: session
+          in: cookie
+          schema:
+            type: string
+      responses:
+        '200':
+          description: Expected response to a valid request
+          content:
+            application/json:
+              schema:
+                $ref: '#/components/schemas/Pet'
+        4XX:
+          description: Client error
+          content:
+            text/plain:
+              schema:
+                type: string
+        default:
+          $ref: '#/components/responses/Error'
+components:
+  parameters:
+    limit:
+      name: limit
+      in: query
+      description: How many items to return at one time
+      schema:
+        type: integer
+        maximum: 100
+  responses:
+    Error:
+      description: Unexpected error
+      content:
+        application/json:
+          schema:
+            $ref: '#/components/schemas/Error'
+  schemas:
+    Pet:
+      type: object
+      required: [id, name]
+      properties:
+        id:
+          type: integer
+          minimum: 1
+        name:
+          type: string
+          pattern: '^[a-z`]+$'
+        tag:
+          type: string
+          enum: [cat, dog]
+          nullable: true
+        parent:
+          $ref: '#/components/schemas/Pet'
+    Pets:
+      type: array
+      items:
+        $ref: '#/components/schemas/Pet'
+    Error:
+      description: An error
+      allOf:
+        - type: object
+          properties:
+            code:
+              type: integer
+        - type: object
+          properties:
+            message:
+              type: string
+"##;
+
+#[test]
+fn openapi_import() -> anyhow::Result<()> {
+    let source: OpenAPI = serde_yaml::from_str(PETSTORE)?;
+    let import = crate::import(&source)?;
+
+    assert!(import
+        .program
+        .contains("let limit = 'limit int `maximum: 100`;"));
+    assert!(import.program.contains("let @Pets = [@Pet];"));
+    assert!(import
+        .program
+        .contains("let @Error = { 'code int } & { 'message str };"));
+    assert!(import
+        .program
+        .contains("let error = <@Error> `description: \"Unexpected error\"`;"));
+    assert!(import
+        .program
+        .contains("# x-parameters: [{\"in\":\"cookie\""));
+    assert!(import.program.contains("`pattern: \"^[a-z\\u0060]+$\"`"));
+    assert_eq!(
+        import.unsupported,
+        [
+            "#/paths/~1pets~1{petId}/get/parameters/1",
+            "#/components/schemas/Pet/properties/tag/nullable"
+        ]
+    );
+
+    let spec = eval(&import.program)?;
+    let api = Builder::new(spec).with_base(import.base).into_openapi()?;
+
+    assert_eq!(api.info, source.info);
+    let paths = |api: &OpenAPI| api.paths.paths.keys().cloned().collect::<Vec<_>>();
+    assert_eq!(paths(&api), paths(&source));
+    for (path, item) in source.paths.paths.iter() {
+        let expected = item.as_item().expect("expected a path item");
+        let actual = api.paths.paths[path]
+            .as_item()
+            .expect("expected a path item");
+        for ((_, op), (_, other)) in expected.iter().zip(actual.iter()) {
+            assert_eq!(op.operation_id, other.operation_id);
+            assert_eq!(op.request_body.is_some(), other.request_body.is_some());
+            let mut codes: Vec<_> = op.responses.responses.keys().collect();
+            let mut other_codes: Vec<_> = other.responses.responses.keys().collect();
+            codes.sort_by_key(|c| c.to_string());
+            other_codes.sort_by_key(|c| c.to_string());
+            assert_eq!(codes, other_codes);
+            assert_eq!(
+                op.responses.default.is_some(),
+                other.responses.default.is_some()
+            );
+        }
+    }
+
+    let schemas = |api: &OpenAPI| {
+        let mut names: Vec<_> = api
+            .components
+            .as_ref()
+            .unwrap()
+            .schemas
+            .keys()
+            .cloned()
+            .collect();
+        names.sort();
+        names
+    };
+    assert_eq!(schemas(&api), schemas(&source));
+    let pet = |api: &OpenAPI| match &api.components.as_ref().unwrap().schemas["Pet"] {
+        ReferenceOr::Item(s) => s.clone(),
+        _ => panic!("expected a schema"),
+    };
+    let (expected, actual) = (pet(&source), pet(&api));
+    let (
+        openapiv3::SchemaKind::Type(openapiv3::Type::Object(expected)),
+        openapiv3::SchemaKind::Type(openapiv3::Type::Object(actual)),
+    ) = (expected.schema_kind, actual.schema_kind)
+    else {
+        panic!("expected objects")
+    };
+    assert_eq!(expected.required, actual.required);
+    assert_eq!(expected.properties["id"], actual.properties["id"]);
+    assert_eq!(expected.properties["name"], actual.properties["name"]);
+
+    Ok(())
+}
@@ -0,0 +1,47 @@
+use oal_model::span::Span;
+use std::fmt::{Display, Formatter};
+
+/// A kind of construct without equivalent in Protocol Buffers.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Construct {
+    /// An array of arrays or of objects without properties, as repeated fields cannot nest.
+    NestedCollection,
+    /// An intersection with an operand which is not an object.
+    Intersection,
+    /// An alternative which is an array or an object without properties, as oneof fields cannot be repeated.
+    Alternative,
+    /// A reference defined in terms of itself without being a message.
+    RecursiveAlias,
+}
+
+/// A construct replaced by a dynamic value in the generated definitions.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Unrepresentable {
+    pub construct: Construct,
+    /// The generated field holding the dynamic value, e.g. `Item.tags`.
+    pub field: String,
+    pub span: Option<Span>,
+}
+
+impl Unrepresentable {
+    /// Returns the span of the relation in which the construct occurs, if any.
+    pub fn span(&self) -> Option<&Span> {
+        self.span.as_ref()
+    }
+}
+
+impl Display for Unrepresentable {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let construct = match self.construct {
+            Construct::NestedCollection => "nested collection",
+            Construct::Intersection => "intersection with a non-object",
+            Construct::Alternative => "collection as alternative",
+            Construct::RecursiveAlias => "recursive alias",
+        };
+        write!(
+            f,
+            "{construct} in {} has no Protocol Buffers equivalent, replaced by google.protobuf.Value",
+            self.field
+        )
+    }
+}
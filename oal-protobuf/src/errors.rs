@@ -0,0 +1,34 @@
+use oal_model::span::Span;
+
+/// The Protocol Buffers generation error type.
+#[derive(thiserror::Error, Debug)]
+pub enum Error {
+    #[error("operation identifier '{id}' is not unique")]
+    DuplicateOperationId {
+        id: String,
+        span: Option<Span>,
+        previous: Option<Span>,
+    },
+}
+
+impl Error {
+    /// Returns the span of the erroneous definition, if any.
+    pub fn span(&self) -> Option<&Span> {
+        match self {
+            Error::DuplicateOperationId { span, .. } => span.as_ref(),
+        }
+    }
+
+    /// Returns the secondary labelled spans.
+    pub fn labels(&self) -> Vec<(Span, String)> {
+        match self {
+            Error::DuplicateOperationId {
+                previous: Some(span),
+                ..
+            } => vec![(span.clone(), "previously used here".to_owned())],
+            _ => Vec::new(),
+        }
+    }
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
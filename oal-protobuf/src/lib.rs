@@ -0,0 +1,661 @@
+pub mod diagnostics;
+pub mod errors;
+
+#[cfg(test)]
+mod tests;
+
+pub use crate::diagnostics::{Construct, Unrepresentable};
+use crate::errors::{Error, Result};
+use oal_compiler::spec;
+use oal_compiler::spec::{status_label, SchemaExpr};
+use oal_model::span::Span;
+use oal_openapi::codegen::{literal, pascal, schema_doc, schema_label, snake, words};
+use oal_openapi::Naming;
+use oal_syntax::atom;
+use std::collections::{HashMap, HashSet};
+
+/// The type of dynamic values, e.g. values of objects without properties.
+const VALUE: &str = "google.protobuf.Value";
+
+/// The import declaring dynamic values.
+const VALUE_IMPORT: &str = "google/protobuf/struct.proto";
+
+/// The name of the generated service.
+const SERVICE: &str = "Api";
+
+/// Converts a label into an enumeration value name, e.g. `in-stock` into `IN_STOCK`.
+fn upper_snake(label: &str) -> String {
+    words(label)
+        .iter()
+        .map(|w| w.to_uppercase())
+        .collect::<Vec<_>>()
+        .join("_")
+}
+
+/// Returns a name not used yet, numbered after the base name if needed.
+fn fresh(used: &mut HashSet<String>, base: &str, sep: &str) -> String {
+    let mut name = base.to_owned();
+    let mut n = 1;
+    while !used.insert(name.clone()) {
+        n += 1;
+        name = format!("{base}{sep}{n}");
+    }
+    name
+}
+
+/// Renders comments, one per line.
+fn comment(lines: &[String], indent: &str) -> String {
+    lines
+        .iter()
+        .flat_map(|l| l.split('\n'))
+        .map(|l| {
+            if l.is_empty() {
+                format!("{indent}//\n")
+            } else {
+                format!("{indent}// {l}\n")
+            }
+        })
+        .collect()
+}
+
+/// Checks whether a schema is declared as a message or an enumeration rather than inlined.
+fn is_declared(s: &spec::Schema) -> bool {
+    match &s.expr {
+        SchemaExpr::Object(o) => !o.props.is_empty(),
+        SchemaExpr::Str(p) => !p.enumeration.is_empty(),
+        SchemaExpr::Op(op) if op.schemas.len() == 1 => is_declared(&op.schemas[0]),
+        SchemaExpr::Op(_) => true,
+        _ => false,
+    }
+}
+
+/// Merges properties along with the names of their declaring messages, the last ones prevailing.
+fn merge(props: &mut Vec<(spec::Property, String)>, others: Vec<(spec::Property, String)>) {
+    for p in others {
+        props.retain(|q| q.0.name != p.0.name);
+        props.push(p);
+    }
+}
+
+/// The type of a field.
+#[derive(Clone, Debug)]
+enum FieldType {
+    Single(String),
+    Repeated(String),
+    Map(String),
+}
+
+/// A field of a generated message.
+struct Field {
+    name: String,
+    /// The name of the property in JSON.
+    label: String,
+    ty: FieldType,
+    optional: bool,
+    deprecated: bool,
+    doc: Vec<String>,
+}
+
+impl Field {
+    fn render(&self, number: usize, indent: &str) -> String {
+        let ty = match &self.ty {
+            FieldType::Single(t) if self.optional => format!("optional {t}"),
+            FieldType::Single(t) => t.clone(),
+            FieldType::Repeated(t) => format!("repeated {t}"),
+            FieldType::Map(t) => format!("map<string, {t}>"),
+        };
+        let mut options = Vec::new();
+        if self.name != self.label {
+            options.push(format!("json_name = {}", literal(&self.label)));
+        }
+        if self.deprecated {
+            options.push("deprecated = true".to_owned());
+        }
+        let options = if options.is_empty() {
+            String::new()
+        } else {
+            format!(" [{}]", options.join(", "))
+        };
+        format!(
+            "{}{indent}{ty} {} = {number}{options};\n",
+            comment(&self.doc, indent),
+            self.name
+        )
+    }
+}
+
+/// A member of a generated message, i.e. a field or a group of alternative fields.
+enum Member {
+    Field(Field),
+    OneOf(String, Vec<Field>),
+}
+
+/// The state of a generation.
+#[derive(Default)]
+struct Output {
+    items: Vec<String>,
+    used: HashSet<String>,
+    /// The field types of the references inlined rather than declared.
+    aliases: HashMap<atom::Ident, FieldType>,
+    /// The inlined references being resolved, to detect cycles.
+    pending: HashSet<atom::Ident>,
+    /// The nested messages and enumerations declared for each name hint, to share them.
+    nested: HashMap<String, (spec::Schema, String)>,
+    diagnostics: Vec<Unrepresentable>,
+    /// The span of the relation being generated, if any.
+    span: Option<Span>,
+    /// Whether dynamic values are used.
+    dynamic: bool,
+}
+
+impl Output {
+    /// Reserves a fresh message or enumeration name.
+    fn fresh(&mut self, base: &str) -> String {
+        fresh(&mut self.used, base, "")
+    }
+
+    /// Returns the type of dynamic values.
+    fn value(&mut self) -> String {
+        self.dynamic = true;
+        VALUE.to_owned()
+    }
+
+    /// Reports an unrepresentable construct and returns the type of dynamic values instead.
+    fn unrepresentable(&mut self, construct: Construct, field: &str) -> String {
+        self.diagnostics.push(Unrepresentable {
+            construct,
+            field: field.to_owned(),
+            span: self.span.clone(),
+        });
+        self.value()
+    }
+}
+
+/// Generates Protocol Buffers definitions from a specification.
+pub struct Builder {
+    spec: spec::Spec,
+    naming: Naming,
+    /// The names of the messages and enumerations declared for schema references.
+    names: HashMap<atom::Ident, String>,
+}
+
+impl Builder {
+    pub fn new(spec: spec::Spec) -> Builder {
+        Builder {
+            spec,
+            naming: Naming::default(),
+            names: HashMap::new(),
+        }
+    }
+
+    /// Sets the naming strategy for operation identifiers.
+    pub fn with_naming(mut self, naming: Naming) -> Self {
+        self.naming = naming;
+        self
+    }
+
+    /// Returns the type of a schema used as the given field,
+    /// generating the messages and enumerations it needs under the given name.
+    fn field_type(&self, out: &mut Output, s: &spec::Schema, hint: &str, field: &str) -> FieldType {
+        let ty = match &s.expr {
+            SchemaExpr::Num(_) => "double".to_owned(),
+            SchemaExpr::Int(_) => "int64".to_owned(),
+            SchemaExpr::Bool(_) => "bool".to_owned(),
+            SchemaExpr::Str(p) if p.enumeration.is_empty() => "string".to_owned(),
+            SchemaExpr::Uri(_) | SchemaExpr::Rel(_) => "string".to_owned(),
+            SchemaExpr::Array(a) => {
+                return match self.field_type(out, &a.item, &format!("{hint}Item"), field) {
+                    FieldType::Single(t) => FieldType::Repeated(t),
+                    _ => {
+                        FieldType::Repeated(out.unrepresentable(Construct::NestedCollection, field))
+                    }
+                };
+            }
+            SchemaExpr::Object(o) if o.props.is_empty() => return FieldType::Map(out.value()),
+            SchemaExpr::Op(op) if op.schemas.len() == 1 => {
+                return self.field_type(out, &op.schemas[0], hint, field)
+            }
+            SchemaExpr::Ref(r) => match self.names.get(r) {
+                Some(name) => name.clone(),
+                None => return self.alias(out, r, field),
+            },
+            SchemaExpr::Str(_) | SchemaExpr::Object(_) | SchemaExpr::Op(_) => {
+                match out.nested.get(hint) {
+                    // Intersections share the nested declarations of their operands.
+                    Some((nested, name)) if nested == s => name.clone(),
+                    _ => {
+                        let name = out.fresh(hint);
+                        out.nested
+                            .insert(hint.to_owned(), (s.clone(), name.clone()));
+                        self.declare(out, &name, s);
+                        name
+                    }
+                }
+            }
+        };
+        FieldType::Single(ty)
+    }
+
+    /// Returns the type of a reference inlined rather than declared.
+    fn alias(&self, out: &mut Output, r: &atom::Ident, field: &str) -> FieldType {
+        if let Some(ty) = out.aliases.get(r) {
+            return ty.clone();
+        }
+        let Some(spec::Reference::Schema(s)) = self.spec.refs.get(r) else {
+            return FieldType::Single(out.value());
+        };
+        if !out.pending.insert(r.clone()) {
+            return FieldType::Single(out.unrepresentable(Construct::RecursiveAlias, field));
+        }
+        let ty = self.field_type(out, s, &pascal(&schema_label(r, s)), field);
+        out.pending.remove(r);
+        out.aliases.insert(r.clone(), ty.clone());
+        ty
+    }
+
+    /// Returns the schema documenting a field, i.e. the schema itself or the inlined reference.
+    /// The description of a declared reference documents the message or enumeration instead.
+    fn documented<'a>(&'a self, s: &'a spec::Schema) -> Option<&'a spec::Schema> {
+        match &s.expr {
+            SchemaExpr::Ref(r) if self.names.contains_key(r) => None,
+            SchemaExpr::Ref(r) => match self.spec.refs.get(r) {
+                Some(spec::Reference::Schema(s)) => Some(s),
+                _ => None,
+            },
+            _ => Some(s),
+        }
+    }
+
+    /// Returns the fields of properties.
+    fn fields(
+        &self,
+        out: &mut Output,
+        props: &[spec::Property],
+        parent: &str,
+        names: &mut HashSet<String>,
+        required: fn(&spec::Property) -> bool,
+    ) -> Vec<Field> {
+        props
+            .iter()
+            .map(|p| self.field(out, p, parent, parent, names, required))
+            .collect()
+    }
+
+    /// Returns the field of a property,
+    /// its nested declarations being named after the message declaring the property.
+    fn field(
+        &self,
+        out: &mut Output,
+        p: &spec::Property,
+        parent: &str,
+        owner: &str,
+        names: &mut HashSet<String>,
+        required: fn(&spec::Property) -> bool,
+    ) -> Field {
+        let label = p.name.as_ref();
+        let name = fresh(names, &snake(label), "_");
+        let hint = format!("{owner}{}", pascal(label));
+        let ty = self.field_type(out, &p.schema, &hint, &format!("{parent}.{name}"));
+        let (doc, deprecated) = match self.documented(&p.schema) {
+            Some(s) => (
+                schema_doc(s, p.desc.as_deref()),
+                p.deprecated || s.deprecated,
+            ),
+            None => (p.desc.iter().cloned().collect(), p.deprecated),
+        };
+        Field {
+            name,
+            label: label.to_owned(),
+            ty,
+            optional: !required(p),
+            deprecated,
+            doc,
+        }
+    }
+
+    /// Returns the field of an alternative in a oneof group.
+    fn alternative(&self, out: &mut Output, parent: &str, name: String, s: &spec::Schema) -> Field {
+        let path = format!("{parent}.{name}");
+        let hint = format!("{parent}{}", pascal(&name));
+        let ty = match self.field_type(out, s, &hint, &path) {
+            FieldType::Single(t) => t,
+            _ => out.unrepresentable(Construct::Alternative, &path),
+        };
+        let doc = self
+            .documented(s)
+            .map(|s| schema_doc(s, None))
+            .unwrap_or_default();
+        Field {
+            label: name.clone(),
+            name,
+            ty: FieldType::Single(ty),
+            optional: false,
+            deprecated: false,
+            doc,
+        }
+    }
+
+    /// Returns the properties of an object or of an intersection of objects, if any,
+    /// along with the names of the messages declaring them.
+    fn properties<'a>(
+        &'a self,
+        s: &'a spec::Schema,
+        owner: &str,
+        seen: &mut HashSet<&'a atom::Ident>,
+    ) -> Option<Vec<(spec::Property, String)>> {
+        match &s.expr {
+            SchemaExpr::Object(o) => Some(
+                o.props
+                    .iter()
+                    .map(|p| (p.clone(), owner.to_owned()))
+                    .collect(),
+            ),
+            SchemaExpr::Op(op) if op.schemas.len() == 1 => {
+                self.properties(&op.schemas[0], owner, seen)
+            }
+            SchemaExpr::Op(op) if op.op == atom::VariadicOperator::Join => {
+                let mut props = Vec::new();
+                for s in op.schemas.iter() {
+                    merge(&mut props, self.properties(s, owner, seen)?);
+                }
+                Some(props)
+            }
+            SchemaExpr::Ref(r) if seen.insert(r) => match self.spec.refs.get(r) {
+                Some(spec::Reference::Schema(s)) => {
+                    let owner = self.names.get(r).map_or(owner, String::as_str);
+                    self.properties(s, owner, seen)
+                }
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    /// Generates the declaration of a message or an enumeration.
+    fn declare(&self, out: &mut Output, name: &str, s: &spec::Schema) {
+        let item = match &s.expr {
+            SchemaExpr::Op(op) if op.schemas.len() == 1 => {
+                return self.declare(out, name, &op.schemas[0]);
+            }
+            SchemaExpr::Str(p) => self.enumeration(name, &p.enumeration, s.deprecated),
+            SchemaExpr::Object(o) => {
+                let fields = self.fields(
+                    out,
+                    &o.props,
+                    name,
+                    &mut HashSet::new(),
+                    spec::Property::is_required,
+                );
+                let members: Vec<_> = fields.into_iter().map(Member::Field).collect();
+                self.message(name, &members, s.deprecated)
+            }
+            SchemaExpr::Op(op) if op.op == atom::VariadicOperator::Join => {
+                // Operands which are objects contribute their properties, the last ones prevailing.
+                let mut props = Vec::new();
+                let mut parts = Vec::new();
+                for (i, schema) in op.schemas.iter().enumerate() {
+                    match self.properties(schema, name, &mut HashSet::new()) {
+                        Some(others) => merge(&mut props, others),
+                        None => parts.push(i + 1),
+                    }
+                }
+                let mut names = HashSet::new();
+                let mut fields: Vec<_> = props
+                    .iter()
+                    .map(|(p, owner)| {
+                        self.field(out, p, name, owner, &mut names, spec::Property::is_required)
+                    })
+                    .collect();
+                for i in parts {
+                    let field = fresh(&mut names, &format!("part{i}"), "_");
+                    let ty =
+                        out.unrepresentable(Construct::Intersection, &format!("{name}.{field}"));
+                    fields.push(Field {
+                        label: field.clone(),
+                        name: field,
+                        ty: FieldType::Single(ty),
+                        optional: false,
+                        deprecated: false,
+                        doc: Vec::new(),
+                    });
+                }
+                let members: Vec<_> = fields.into_iter().map(Member::Field).collect();
+                self.message(name, &members, s.deprecated)
+            }
+            SchemaExpr::Op(op) => {
+                let mut names = HashSet::new();
+                let fields = op
+                    .schemas
+                    .iter()
+                    .enumerate()
+                    .map(|(i, schema)| {
+                        let base = match &schema.expr {
+                            SchemaExpr::Ref(r) => match self.names.get(r) {
+                                Some(name) => snake(name),
+                                None => snake(&r.untagged()),
+                            },
+                            SchemaExpr::Num(_) => "number".to_owned(),
+                            SchemaExpr::Int(_) => "integer".to_owned(),
+                            SchemaExpr::Bool(_) => "boolean".to_owned(),
+                            SchemaExpr::Str(_) | SchemaExpr::Uri(_) | SchemaExpr::Rel(_) => {
+                                "string".to_owned()
+                            }
+                            SchemaExpr::Array(_) => "array".to_owned(),
+                            _ => format!("variant{}", i + 1),
+                        };
+                        let field = fresh(&mut names, &base, "_");
+                        self.alternative(out, name, field, schema)
+                    })
+                    .collect();
+                let members = [Member::OneOf("value".to_owned(), fields)];
+                self.message(name, &members, s.deprecated)
+            }
+            _ => unreachable!("schema should be inlined"),
+        };
+        out.items
+            .push(format!("{}{item}", comment(&schema_doc(s, None), "")));
+    }
+
+    fn message(&self, name: &str, members: &[Member], deprecated: bool) -> String {
+        let mut body = String::new();
+        if deprecated {
+            body.push_str("  option deprecated = true;\n");
+        }
+        let mut number = 0;
+        for member in members {
+            match member {
+                Member::Field(f) => {
+                    number += 1;
+                    body.push_str(&f.render(number, "  "));
+                }
+                Member::OneOf(group, fields) => {
+                    body.push_str(&format!("  oneof {group} {{\n"));
+                    for f in fields {
+                        number += 1;
+                        body.push_str(&f.render(number, "    "));
+                    }
+                    body.push_str("  }\n");
+                }
+            }
+        }
+        format!("message {name} {{\n{body}}}\n")
+    }
+
+    /// Returns the declaration of a string enumeration.
+    /// Values are prefixed with the name of the enumeration, as they share its scope.
+    fn enumeration(&self, name: &str, values: &[String], deprecated: bool) -> String {
+        let prefix = upper_snake(name);
+        let unspecified = format!("{prefix}_UNSPECIFIED");
+        let mut used = HashSet::from([unspecified.clone()]);
+        let mut body = String::new();
+        if deprecated {
+            body.push_str("  option deprecated = true;\n");
+        }
+        body.push_str(&format!("  {unspecified} = 0;\n"));
+        for (i, value) in values.iter().enumerate() {
+            let base = match upper_snake(value) {
+                v if v.is_empty() => format!("{prefix}_VALUE"),
+                v => format!("{prefix}_{v}"),
+            };
+            let ident = fresh(&mut used, &base, "_");
+            body.push_str(&format!("  {ident} = {}; // {}\n", i + 1, literal(value)));
+        }
+        format!("enum {name} {{\n{body}}}\n")
+    }
+
+    /// Generates the request message of an operation,
+    /// with path variables, query parameters and body as fields.
+    /// Headers are left to metadata.
+    fn request(&self, out: &mut Output, name: &str, uri: &spec::Uri, xfer: &spec::Transfer) {
+        let mut names = HashSet::new();
+        let vars: Vec<_> = uri
+            .path
+            .iter()
+            .filter_map(|s| match s {
+                spec::UriSegment::Variable(p) => Some(p.as_ref().clone()),
+                spec::UriSegment::Literal(_) => None,
+            })
+            .collect();
+        let mut fields = self.fields(out, &vars, name, &mut names, |_| true);
+        for params in [uri.params.as_ref(), xfer.params.as_ref()]
+            .into_iter()
+            .flatten()
+        {
+            fields.extend(self.fields(
+                out,
+                &params.props,
+                name,
+                &mut names,
+                spec::Property::is_required_param,
+            ));
+        }
+        if let Some(schema) = &xfer.domain.schema {
+            let field = fresh(&mut names, "body", "_");
+            let path = format!("{name}.{field}");
+            let ty = self.field_type(out, schema, &format!("{name}Body"), &path);
+            fields.push(Field {
+                label: field.clone(),
+                name: field,
+                ty,
+                optional: false,
+                deprecated: false,
+                doc: Vec::new(),
+            });
+        }
+        let members: Vec<_> = fields.into_iter().map(Member::Field).collect();
+        let item = self.message(name, &members, false);
+        out.items.push(item);
+    }
+
+    /// Generates the response message of an operation,
+    /// with the body of each response as alternatives named after its status.
+    fn response(&self, out: &mut Output, name: &str, xfer: &spec::Transfer) {
+        let bodies: Vec<_> = xfer
+            .ranges
+            .values()
+            .filter_map(|c| c.schema.as_deref().map(|s| (c.status, s)))
+            .collect();
+        let members = match bodies.as_slice() {
+            [] => Vec::new(),
+            [(_, schema)] => {
+                let path = format!("{name}.body");
+                let ty = self.field_type(out, schema, &format!("{name}Body"), &path);
+                vec![Member::Field(Field {
+                    name: "body".to_owned(),
+                    label: "body".to_owned(),
+                    ty,
+                    optional: false,
+                    deprecated: false,
+                    doc: Vec::new(),
+                })]
+            }
+            bodies => {
+                let mut names = HashSet::new();
+                let fields = bodies
+                    .iter()
+                    .map(|(status, schema)| {
+                        let base = format!("body_{}", status_label(*status).to_lowercase());
+                        let field = fresh(&mut names, &base, "_");
+                        self.alternative(out, name, field, schema)
+                    })
+                    .collect();
+                vec![Member::OneOf("body".to_owned(), fields)]
+            }
+        };
+        let item = self.message(name, &members, false);
+        out.items.push(item);
+    }
+
+    /// Generates the Protocol Buffers definitions,
+    /// along with the constructs replaced by dynamic values.
+    pub fn build(mut self) -> Result<(String, Vec<Unrepresentable>)> {
+        let mut out = Output::default();
+        // The service shares the scope of the messages and enumerations.
+        out.used.insert(SERVICE.to_owned());
+        for (ident, reference) in self.spec.refs.iter() {
+            if let spec::Reference::Schema(schema) = reference {
+                if is_declared(schema) {
+                    let name = out.fresh(&pascal(&schema_label(ident, schema)));
+                    self.names.insert(ident.clone(), name);
+                }
+            }
+        }
+        for (ident, reference) in self.spec.refs.iter() {
+            if let spec::Reference::Schema(schema) = reference {
+                if let Some(name) = self.names.get(ident) {
+                    self.declare(&mut out, name, schema);
+                }
+            }
+        }
+
+        let mut ids: HashMap<String, Option<Span>> = HashMap::new();
+        let mut rpcs = Vec::new();
+        for rel in self.spec.rels.iter() {
+            out.span = rel.span.clone();
+            let xfers = rel
+                .xfers
+                .iter()
+                .filter_map(|(m, x)| x.as_ref().map(|x| (m, x)));
+            for (method, xfer) in xfers {
+                let id = self.naming.transfer_id(xfer, method, &rel.uri);
+                // Distinct identifiers can still map to the same method names.
+                let rpc = pascal(&id);
+                let span = xfer.span.clone().or_else(|| rel.span.clone());
+                if let Some(previous) = ids.insert(rpc.clone(), span.clone()) {
+                    return Err(Error::DuplicateOperationId { id, span, previous });
+                }
+                let request = out.fresh(&format!("{rpc}Request"));
+                let response = out.fresh(&format!("{rpc}Response"));
+                self.request(&mut out, &request, &rel.uri, xfer);
+                self.response(&mut out, &response, xfer);
+                let desc: Vec<_> = xfer
+                    .summary
+                    .iter()
+                    .chain(&xfer.desc)
+                    .take(1)
+                    .cloned()
+                    .collect();
+                let options = if xfer.deprecated {
+                    " {\n    option deprecated = true;\n  }\n"
+                } else {
+                    ";\n"
+                };
+                rpcs.push(format!(
+                    "{}  rpc {rpc}({request}) returns ({response}){options}",
+                    comment(&desc, "  ")
+                ));
+            }
+        }
+
+        let mut proto =
+            "// Generated from an Oxlip program.\n\nsyntax = \"proto3\";\n\n".to_owned();
+        if out.dynamic {
+            proto.push_str(&format!("import {};\n\n", literal(VALUE_IMPORT)));
+        }
+        proto.push_str(&out.items.join("\n"));
+        if !rpcs.is_empty() {
+            proto.push_str(&format!("\nservice {SERVICE} {{\n{}}}\n", rpcs.concat()));
+        }
+        Ok((proto, out.diagnostics))
+    }
+}
@@ -0,0 +1,179 @@
+use crate::errors::Error;
+use crate::{Builder, Construct};
+use oal_compiler::tests::eval;
+
+#[test]
+fn protobuf_messages() -> anyhow::Result<()> {
+    let code = r#"
+        let @id = int `minimum: 1`;
+        let @kind = str `enum: [small, in-stock]`;
+        let @named = { 'name! str };
+        # description: "An item of the catalog."
+        let @item = @named & {
+          'id! @id
+        , 'kind @kind
+        , 'tags [str]
+        , 'firstName str `format: name`
+        , 'meta {}
+        };
+        let @choice = @named ~ bool;
+        let @node = { 'children [@node] };
+        res /items on get -> @item, put : @choice -> <>;
+        res /nodes on get -> @node;
+    "#;
+
+    let (proto, diags) = Builder::new(eval(code)?).build()?;
+
+    assert!(diags.is_empty(), "{diags:?}");
+    assert!(proto.starts_with(
+        "// Generated from an Oxlip program.\n\n\
+         syntax = \"proto3\";\n\n\
+         import \"google/protobuf/struct.proto\";\n\n"
+    ));
+    assert!(proto.contains(
+        "enum Kind {\n  KIND_UNSPECIFIED = 0;\n  KIND_SMALL = 1; // \"small\"\n  KIND_IN_STOCK = 2; // \"in-stock\"\n}\n"
+    ));
+    assert!(proto.contains("message Named {\n  string name = 1;\n}\n"));
+    assert!(proto.contains(
+        "// An item of the catalog.\n\
+         message Item {\n  \
+           string name = 1;\n  \
+           // Minimum: 1.\n  \
+           int64 id = 2;\n  \
+           optional Kind kind = 3;\n  \
+           repeated string tags = 4;\n  \
+           // Format: `name`.\n  \
+           optional string first_name = 5 [json_name = \"firstName\"];\n  \
+           map<string, google.protobuf.Value> meta = 6;\n\
+         }\n"
+    ));
+    assert!(proto.contains(
+        "message Choice {\n  oneof value {\n    Named named = 1;\n    bool boolean = 2;\n  }\n}\n"
+    ));
+    assert!(proto.contains("message Node {\n  repeated Node children = 1;\n}\n"));
+
+    Ok(())
+}
+
+#[test]
+fn protobuf_service() -> anyhow::Result<()> {
+    let code = r#"
+        let @item = { 'id! int };
+        res /items/{ 'id int } ? { 'q str } on
+          # summary: "Update an item"
+          put { 'dry! bool } : @item -> <status=200, @item> :: <status=404, str>;
+        res /items on get -> [@item];
+    "#;
+
+    let (proto, diags) = Builder::new(eval(code)?).build()?;
+
+    assert!(diags.is_empty(), "{diags:?}");
+    assert!(proto.contains(
+        "message PutItemsIdRequest {\n  \
+           int64 id = 1;\n  \
+           optional string q = 2;\n  \
+           bool dry = 3;\n  \
+           Item body = 4;\n\
+         }\n"
+    ));
+    assert!(proto.contains(
+        "message PutItemsIdResponse {\n  oneof body {\n    Item body_200 = 1;\n    string body_404 = 2;\n  }\n}\n"
+    ));
+    assert!(proto.contains("message GetItemsRequest {\n}\n"));
+    assert!(proto.contains("message GetItemsResponse {\n  repeated Item body = 1;\n}\n"));
+    assert!(proto.contains(
+        "service Api {\n  \
+           // Update an item\n  \
+           rpc PutItemsId(PutItemsIdRequest) returns (PutItemsIdResponse);\n  \
+           rpc GetItems(GetItemsRequest) returns (GetItemsResponse);\n\
+         }\n"
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn protobuf_unrepresentable() -> anyhow::Result<()> {
+    let code = r#"
+        let @matrix = { 'rows [[num]] };
+        let @mixed = { 'id int } & ({ 'a str } | { 'b str });
+        let @either = [int] ~ {};
+        res /matrix on get -> @matrix, put : @mixed -> @either;
+    "#;
+
+    let (proto, diags) = Builder::new(eval(code)?).build()?;
+
+    let constructs: Vec<_> = diags
+        .iter()
+        .map(|d| (d.construct, d.field.as_str()))
+        .collect();
+    assert_eq!(
+        constructs,
+        [
+            (Construct::NestedCollection, "Matrix.rows"),
+            (Construct::Intersection, "Mixed.part2"),
+            (Construct::Alternative, "Either.array"),
+            (Construct::Alternative, "Either.variant2"),
+        ]
+    );
+    assert!(diags.iter().all(|d| d.span().is_none()));
+    assert!(proto.contains("repeated google.protobuf.Value rows = 1;"));
+    assert!(proto.contains("google.protobuf.Value part2 = 2;"));
+
+    Ok(())
+}
+
+#[test]
+fn protobuf_duplicate_operation_id() -> anyhow::Result<()> {
+    let code = r#"
+        # operationId: "list"
+        let a = get -> {};
+        # operationId: "list"
+        let b = get -> {};
+        res /a on a;
+        res /b on b;
+    "#;
+
+    let err = Builder::new(eval(code)?)
+        .build()
+        .expect_err("expected an error");
+
+    assert!(matches!(err, Error::DuplicateOperationId { ref id, .. } if id == "list"));
+
+    Ok(())
+}
+
+#[test]
+fn protobuf_reserved_service() -> anyhow::Result<()> {
+    let code = r#"
+        let @api = { 'id! int };
+        res /api on get -> @api;
+    "#;
+
+    let (proto, _) = Builder::new(eval(code)?).build()?;
+
+    assert!(proto.contains("message Api2 {\n  int64 id = 1;\n}\n"));
+    assert!(proto.contains("message GetApiResponse {\n  Api2 body = 1;\n}\n"));
+    assert!(proto.contains("service Api {\n"));
+
+    Ok(())
+}
+
+#[test]
+fn protobuf_intersection_nested_enum() -> anyhow::Result<()> {
+    let code = r#"
+        let @a = { 'kind str `enum: [small, large]` };
+        let @j = @a & { 'id! int };
+        res /j on get -> @j;
+    "#;
+
+    let (proto, diags) = Builder::new(eval(code)?).build()?;
+
+    assert!(diags.is_empty(), "{diags:?}");
+    assert_eq!(proto.matches("enum ").count(), 1, "{proto}");
+    assert!(proto.contains("enum AKind {\n"));
+    assert!(proto.contains("message A {\n  optional AKind kind = 1;\n}\n"));
+    assert!(proto.contains("message J {\n  optional AKind kind = 1;\n  int64 id = 2;\n}\n"));
+
+    Ok(())
+}
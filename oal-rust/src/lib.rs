@@ -0,0 +1,463 @@
+#[cfg(test)]
+mod tests;
+
+use oal_compiler::spec;
+use oal_compiler::spec::SchemaExpr;
+use oal_openapi::codegen::{literal, pascal, schema_doc, schema_label, snake};
+use oal_syntax::atom;
+use std::collections::{HashMap, HashSet};
+
+/// The type of values without a more specific type, e.g. objects without properties.
+const ANY_VALUE: &str = "serde_json::Value";
+
+/// The derivations of the generated types.
+const DERIVE: &str = "#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]";
+
+/// The keywords that can be used as raw identifiers.
+const KEYWORDS: &[&str] = &[
+    "abstract", "as", "async", "await", "become", "box", "break", "const", "continue", "do", "dyn",
+    "else", "enum", "extern", "false", "final", "fn", "for", "gen", "if", "impl", "in", "let",
+    "loop", "macro", "match", "mod", "move", "mut", "override", "priv", "pub", "ref", "return",
+    "static", "struct", "trait", "true", "try", "type", "typeof", "unsafe", "unsized", "use",
+    "virtual", "where", "while", "yield",
+];
+
+/// The keywords that cannot be used as raw identifiers, nor as type or variant names.
+const RESERVED: &[&str] = &["crate", "self", "Self", "super"];
+
+/// The names used by the generated code, which generated types must not shadow.
+const PRELUDE: &[&str] = &["Box", "Deserialize", "Option", "Serialize", "String", "Vec"];
+
+/// Converts a label into a field name, escaping keywords, e.g. `type` into `r#type`.
+fn field_name(label: &str) -> String {
+    let name = snake(label);
+    if KEYWORDS.contains(&name.as_str()) {
+        format!("r#{name}")
+    } else if RESERVED.contains(&name.as_str()) {
+        format!("{name}_")
+    } else {
+        name
+    }
+}
+
+/// Returns the set of the reserved names, to allocate variant names from.
+fn reserved() -> HashSet<String> {
+    RESERVED.iter().map(|s| s.to_string()).collect()
+}
+
+/// Returns the documentation lines of a schema, including its deprecation.
+fn doc_lines(s: &spec::Schema, desc: Option<&str>) -> Vec<String> {
+    let mut lines = schema_doc(s, desc);
+    if s.deprecated {
+        lines.push(String::new());
+        lines.push("Deprecated.".to_owned());
+    }
+    lines
+}
+
+/// Renders documentation comments, one per line.
+fn doc(lines: &[String], indent: &str) -> String {
+    lines
+        .iter()
+        .flat_map(|l| l.split('\n'))
+        .map(|l| {
+            if l.is_empty() {
+                format!("{indent}///\n")
+            } else {
+                format!("{indent}/// {l}\n")
+            }
+        })
+        .collect()
+}
+
+/// Collects the references a schema depends on.
+fn dependencies<'a>(s: &'a spec::Schema, deps: &mut Vec<&'a atom::Ident>) {
+    match &s.expr {
+        SchemaExpr::Ref(r) => deps.push(r),
+        SchemaExpr::Array(a) => dependencies(&a.item, deps),
+        SchemaExpr::Object(o) => o.props.iter().for_each(|p| dependencies(&p.schema, deps)),
+        SchemaExpr::Op(op) => op.schemas.iter().for_each(|s| dependencies(s, deps)),
+        _ => {}
+    }
+}
+
+/// Returns the references that depend on themselves, directly or not.
+fn cyclic_references(spec: &spec::Spec) -> HashSet<atom::Ident> {
+    let edges: HashMap<&atom::Ident, Vec<&atom::Ident>> = spec
+        .refs
+        .iter()
+        .filter_map(|(ident, r)| match r {
+            spec::Reference::Schema(s) => {
+                let mut deps = Vec::new();
+                dependencies(s, &mut deps);
+                Some((ident, deps))
+            }
+            _ => None,
+        })
+        .collect();
+    let reaches = |from: &atom::Ident| {
+        let mut seen = HashSet::new();
+        let mut stack = edges.get(from).cloned().unwrap_or_default();
+        while let Some(next) = stack.pop() {
+            if next == from {
+                return true;
+            }
+            if seen.insert(next) {
+                stack.extend(edges.get(next).into_iter().flatten());
+            }
+        }
+        false
+    };
+    edges
+        .keys()
+        .filter(|i| reaches(i))
+        .map(|i| (*i).clone())
+        .collect()
+}
+
+/// A field of a generated structure.
+struct Field {
+    name: String,
+    ty: String,
+    attrs: Vec<String>,
+    doc: Vec<String>,
+}
+
+impl Field {
+    fn render(&self, indent: &str, visibility: &str) -> String {
+        let attrs: String = self
+            .attrs
+            .iter()
+            .map(|a| format!("{indent}#[serde({a})]\n"))
+            .collect();
+        format!(
+            "{}{attrs}{indent}{visibility}{}: {},\n",
+            doc(&self.doc, indent),
+            self.name,
+            self.ty
+        )
+    }
+}
+
+/// Generates Rust types with serde derivations from a specification.
+pub struct Builder {
+    spec: spec::Spec,
+    /// The type names of the schema references.
+    names: HashMap<atom::Ident, String>,
+    /// The references to box when used directly as fields, to break cycles.
+    cyclic: HashSet<atom::Ident>,
+}
+
+/// The state of a generation, i.e. the items generated so far.
+struct Output {
+    items: Vec<String>,
+    used: HashSet<String>,
+}
+
+impl Default for Output {
+    fn default() -> Self {
+        Output {
+            items: Vec::new(),
+            used: PRELUDE
+                .iter()
+                .chain(RESERVED)
+                .map(|s| s.to_string())
+                .collect(),
+        }
+    }
+}
+
+impl Output {
+    /// Reserves a fresh type name.
+    fn fresh(&mut self, base: &str) -> String {
+        let mut name = base.to_owned();
+        let mut n = 1;
+        while !self.used.insert(name.clone()) {
+            n += 1;
+            name = format!("{base}{n}");
+        }
+        name
+    }
+}
+
+impl Builder {
+    pub fn new(spec: spec::Spec) -> Builder {
+        let cyclic = cyclic_references(&spec);
+        Builder {
+            spec,
+            names: HashMap::new(),
+            cyclic,
+        }
+    }
+
+    /// Returns the type of a schema, generating the items it needs under the given name.
+    fn ty(&self, out: &mut Output, s: &spec::Schema, hint: &str) -> String {
+        match &s.expr {
+            SchemaExpr::Num(_) => "f64".to_owned(),
+            SchemaExpr::Int(_) => "i64".to_owned(),
+            SchemaExpr::Bool(_) => "bool".to_owned(),
+            SchemaExpr::Str(p) if p.enumeration.is_empty() => "String".to_owned(),
+            SchemaExpr::Uri(_) | SchemaExpr::Rel(_) => "String".to_owned(),
+            SchemaExpr::Array(a) => {
+                format!("Vec<{}>", self.ty(out, &a.item, &format!("{hint}Item")))
+            }
+            SchemaExpr::Object(o) if o.props.is_empty() => ANY_VALUE.to_owned(),
+            SchemaExpr::Op(op) if op.schemas.len() == 1 => self.ty(out, &op.schemas[0], hint),
+            SchemaExpr::Ref(r) => self
+                .names
+                .get(r)
+                .cloned()
+                .unwrap_or_else(|| ANY_VALUE.to_owned()),
+            SchemaExpr::Str(_) | SchemaExpr::Object(_) | SchemaExpr::Op(_) => {
+                let name = out.fresh(hint);
+                self.declare(out, &name, s);
+                name
+            }
+        }
+    }
+
+    /// Returns the type of a schema used directly as a field or variant, boxed if cyclic.
+    fn member_ty(&self, out: &mut Output, s: &spec::Schema, hint: &str) -> String {
+        let ty = self.ty(out, s, hint);
+        match &s.expr {
+            SchemaExpr::Ref(r) if self.cyclic.contains(r) => format!("Box<{ty}>"),
+            _ => ty,
+        }
+    }
+
+    /// Returns the fields of the properties of an object.
+    fn fields(&self, out: &mut Output, o: &spec::Object, parent: &str) -> Vec<Field> {
+        let mut names = HashSet::new();
+        o.props
+            .iter()
+            .map(|p| {
+                let label = p.name.as_ref();
+                let mut name = field_name(label);
+                let mut n = 1;
+                while !names.insert(name.clone()) {
+                    n += 1;
+                    name = format!("{}_{n}", field_name(label).trim_start_matches("r#"));
+                }
+                let ty = self.member_ty(out, &p.schema, &format!("{parent}{}", pascal(label)));
+                let mut attrs = Vec::new();
+                if name.trim_start_matches("r#") != label {
+                    attrs.push(format!("rename = {}", literal(label)));
+                }
+                let ty = if p.is_required() {
+                    ty
+                } else {
+                    attrs.push(r#"default, skip_serializing_if = "Option::is_none""#.to_owned());
+                    format!("Option<{ty}>")
+                };
+                let mut doc = match &p.schema.expr {
+                    // The description of a reference documents the referenced type instead.
+                    SchemaExpr::Ref(_) => p.desc.iter().cloned().collect(),
+                    _ => doc_lines(&p.schema, p.desc.as_deref()),
+                };
+                if p.deprecated && !p.schema.deprecated {
+                    doc.push("Deprecated.".to_owned());
+                }
+                Field {
+                    name,
+                    ty,
+                    attrs,
+                    doc,
+                }
+            })
+            .collect()
+    }
+
+    /// Generates the item of a named type.
+    fn declare(&self, out: &mut Output, name: &str, s: &spec::Schema) {
+        let item = match &s.expr {
+            SchemaExpr::Op(op) if op.schemas.len() == 1 => {
+                return self.declare(out, name, &op.schemas[0]);
+            }
+            SchemaExpr::Object(o) if !o.props.is_empty() => {
+                let fields = self.fields(out, o, name);
+                self.structure(name, &fields)
+            }
+            SchemaExpr::Str(p) if !p.enumeration.is_empty() => {
+                let mut used = reserved();
+                let variants: String = p
+                    .enumeration
+                    .iter()
+                    .map(|value| {
+                        let mut variant = pascal(value);
+                        let mut n = 1;
+                        while !used.insert(variant.clone()) {
+                            n += 1;
+                            variant = format!("{}{n}", pascal(value));
+                        }
+                        format!(
+                            "    #[serde(rename = {})]\n    {variant},\n",
+                            literal(value)
+                        )
+                    })
+                    .collect();
+                format!("{DERIVE}\npub enum {name} {{\n{variants}}}\n")
+            }
+            SchemaExpr::Op(op) if op.op == atom::VariadicOperator::Join => {
+                let mut fields = Vec::new();
+                // Inline objects contribute their properties directly, the last one prevailing.
+                let mut props = spec::Object::default();
+                for (i, schema) in op.schemas.iter().enumerate() {
+                    if let SchemaExpr::Object(o) = &schema.expr {
+                        for p in o.props.iter() {
+                            props.props.retain(|q| q.name != p.name);
+                            props.props.push(p.clone());
+                        }
+                        continue;
+                    }
+                    let ty = self.member_ty(out, schema, &format!("{name}Part{}", i + 1));
+                    let field = match &schema.expr {
+                        SchemaExpr::Ref(r) if self.names.contains_key(r) => {
+                            field_name(&self.names[r])
+                        }
+                        _ => format!("part{}", i + 1),
+                    };
+                    fields.push(Field {
+                        name: field,
+                        ty,
+                        attrs: vec!["flatten".to_owned()],
+                        doc: Vec::new(),
+                    });
+                }
+                fields.extend(self.fields(out, &props, name));
+                self.structure(name, &fields)
+            }
+            SchemaExpr::Op(op) => self.enumeration(out, name, &op.schemas),
+            _ => format!("pub type {name} = {};\n", self.ty(out, s, name)),
+        };
+        out.items
+            .push(format!("{}{item}", doc(&doc_lines(s, None), "")));
+    }
+
+    fn structure(&self, name: &str, fields: &[Field]) -> String {
+        let body: String = fields.iter().map(|f| f.render("    ", "pub ")).collect();
+        format!("{DERIVE}\npub struct {name} {{\n{body}}}\n")
+    }
+
+    /// Returns the tag of a sum of objects, i.e. a common required property with a constant value.
+    fn tag<'a>(&self, schemas: &'a [spec::Schema]) -> Option<(&'a str, Vec<&'a str>)> {
+        let objects: Vec<&spec::Object> = schemas
+            .iter()
+            .map(|s| match &s.expr {
+                SchemaExpr::Object(o) => Some(o),
+                _ => None,
+            })
+            .collect::<Option<_>>()?;
+        let constant = |o: &'a spec::Object, name: &str| {
+            o.props.iter().find_map(|p| match &p.schema.expr {
+                SchemaExpr::Str(s) if p.name.as_ref() == name && p.is_required() => {
+                    match s.enumeration.as_slice() {
+                        [value] => Some(value.as_str()),
+                        _ => None,
+                    }
+                }
+                _ => None,
+            })
+        };
+        objects.first()?.props.iter().find_map(|p| {
+            let name = p.name.as_ref();
+            let values = objects
+                .iter()
+                .map(|o| constant(o, name))
+                .collect::<Option<Vec<_>>>()?;
+            let distinct: HashSet<_> = values.iter().collect();
+            (distinct.len() == values.len()).then_some((name, values))
+        })
+    }
+
+    /// Generates an enumeration of alternative schemas,
+    /// tagged by a common property if all alternatives are objects, untagged otherwise.
+    fn enumeration(&self, out: &mut Output, name: &str, schemas: &[spec::Schema]) -> String {
+        let mut used = reserved();
+        let mut fresh = |base: String| {
+            let mut variant = base.clone();
+            let mut n = 1;
+            while !used.insert(variant.clone()) {
+                n += 1;
+                variant = format!("{base}{n}");
+            }
+            variant
+        };
+        if let Some((tag, values)) = self.tag(schemas) {
+            let mut body = String::new();
+            for (schema, value) in schemas.iter().zip(values) {
+                let SchemaExpr::Object(o) = &schema.expr else {
+                    unreachable!()
+                };
+                let variant = fresh(pascal(value));
+                let props = spec::Object {
+                    props: o
+                        .props
+                        .iter()
+                        .filter(|p| p.name.as_ref() != tag)
+                        .cloned()
+                        .collect(),
+                };
+                let fields: String = self
+                    .fields(out, &props, &format!("{name}{variant}"))
+                    .iter()
+                    .map(|f| f.render("        ", ""))
+                    .collect();
+                body.push_str(&doc(&doc_lines(schema, None), "    "));
+                body.push_str(&format!(
+                    "    #[serde(rename = {})]\n    {variant} {{\n{fields}    }},\n",
+                    literal(value)
+                ));
+            }
+            format!(
+                "{DERIVE}\n#[serde(tag = {})]\npub enum {name} {{\n{body}}}\n",
+                literal(tag)
+            )
+        } else {
+            let mut body = String::new();
+            for (i, schema) in schemas.iter().enumerate() {
+                let base = match &schema.expr {
+                    SchemaExpr::Ref(r) => self
+                        .names
+                        .get(r)
+                        .cloned()
+                        .unwrap_or_else(|| "Value".to_owned()),
+                    SchemaExpr::Num(_) => "Number".to_owned(),
+                    SchemaExpr::Int(_) => "Integer".to_owned(),
+                    SchemaExpr::Bool(_) => "Boolean".to_owned(),
+                    SchemaExpr::Str(_) | SchemaExpr::Uri(_) | SchemaExpr::Rel(_) => {
+                        "String".to_owned()
+                    }
+                    SchemaExpr::Array(_) => "Array".to_owned(),
+                    _ => format!("Variant{}", i + 1),
+                };
+                let variant = fresh(base);
+                let ty = self.member_ty(out, schema, &format!("{name}{variant}"));
+                body.push_str(&doc(&doc_lines(schema, None), "    "));
+                body.push_str(&format!("    {variant}({ty}),\n"));
+            }
+            format!("{DERIVE}\n#[serde(untagged)]\npub enum {name} {{\n{body}}}\n")
+        }
+    }
+
+    /// Generates the Rust module.
+    pub fn build(mut self) -> String {
+        let mut out = Output::default();
+        for (ident, reference) in self.spec.refs.iter() {
+            let spec::Reference::Schema(schema) = reference else {
+                continue;
+            };
+            let base = pascal(&schema_label(ident, schema));
+            self.names.insert(ident.clone(), out.fresh(&base));
+        }
+        for (ident, reference) in self.spec.refs.iter() {
+            if let spec::Reference::Schema(schema) = reference {
+                let name = &self.names[ident];
+                self.declare(&mut out, name, schema);
+            }
+        }
+        format!(
+            "// Generated from an Oxlip program.\n\nuse serde::{{Deserialize, Serialize}};\n\n{}",
+            out.items.join("\n")
+        )
+    }
+}
@@ -0,0 +1,180 @@
+use crate::Builder;
+use oal_compiler::tests::eval;
+
+/// Compiles a generated module as a crate of the given name,
+/// without the serde derivations and attributes.
+fn assert_compiles(name: &str, rs: &str) {
+    let items: String = rs
+        .lines()
+        .filter(|l| !l.trim_start().starts_with("#[") && !l.starts_with("use serde"))
+        .map(|l| format!("{l}\n"))
+        .collect();
+    let source = format!("mod serde_json {{\n    pub type Value = ();\n}}\n{items}");
+
+    let dir = std::env::temp_dir().join(format!("oal-rust-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).expect("should create a directory");
+    let path = dir.join(format!("{name}.rs"));
+    std::fs::write(&path, &source).expect("should write the module");
+    let output = std::process::Command::new("rustc")
+        .args([
+            "--crate-type",
+            "lib",
+            "--edition",
+            "2021",
+            "--emit",
+            "metadata",
+        ])
+        .arg("--out-dir")
+        .arg(&dir)
+        .arg(&path)
+        .output()
+        .expect("should run rustc");
+    assert!(
+        output.status.success(),
+        "generated module does not compile:\n{}\n{source}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
+
+#[test]
+fn rust_structs() -> anyhow::Result<()> {
+    let code = r#"
+        let @kind = str `enum: [small, extra-large]`;
+        # description: "An item of the catalog."
+        let @item = {
+          'id! int `minimum: 1`
+        , 'firstName str `description: "The first name.", maxLength: 64`
+        , 'type @kind
+        , 'tags [{ 'name! str }]
+        , 'x-parent @item
+        };
+        res /items on get -> @item;
+    "#;
+
+    let rs = Builder::new(eval(code)?).build();
+
+    assert!(rs.contains(
+        r#"#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Kind {
+    #[serde(rename = "small")]
+    Small,
+    #[serde(rename = "extra-large")]
+    ExtraLarge,
+}
+"#
+    ));
+    assert!(rs.contains(
+        r#"/// An item of the catalog.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Item {
+    /// Minimum: 1.
+    pub id: i64,
+    /// The first name.
+    ///
+    /// Maximum length: 64.
+    #[serde(rename = "firstName")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub first_name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub r#type: Option<Kind>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tags: Option<Vec<ItemTagsItem>>,
+    #[serde(rename = "x-parent")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub x_parent: Option<Box<Item>>,
+}
+"#
+    ));
+    assert!(rs.contains("pub struct ItemTagsItem {\n    pub name: String,\n}\n"));
+    assert_compiles("rust_structs", &rs);
+
+    Ok(())
+}
+
+#[test]
+fn rust_enums() -> anyhow::Result<()> {
+    let code = r#"
+        let @circle = { 'shape! str `enum: [circle]`, 'radius! num };
+        let @square = { 'shape! str `enum: [square]`, 'side! num };
+        let @shape = { 'shape! str `enum: [circle]`, 'radius! num }
+                   | { 'shape! str `enum: [square]`, 'side! num };
+        let @value = @circle ~ str ~ [int];
+        let @named = @circle & { 'name! str };
+        res /shapes on get -> @shape, put : @value -> @named;
+    "#;
+
+    let rs = Builder::new(eval(code)?).build();
+
+    assert!(rs.contains(
+        r#"#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "shape")]
+pub enum Shape {
+    #[serde(rename = "circle")]
+    Circle {
+        radius: f64,
+    },
+    #[serde(rename = "square")]
+    Square {
+        side: f64,
+    },
+}
+"#
+    ));
+    assert!(rs.contains(
+        r#"#[serde(untagged)]
+pub enum Value {
+    Circle(Circle),
+    String(String),
+    Array(Vec<i64>),
+}
+"#
+    ));
+    assert!(rs.contains(
+        r#"pub struct Named {
+    #[serde(flatten)]
+    pub circle: Circle,
+    pub name: String,
+}
+"#
+    ));
+    assert_compiles("rust_enums", &rs);
+
+    Ok(())
+}
+
+#[test]
+fn rust_prelude_names() -> anyhow::Result<()> {
+    let code = r#"
+        let @string = { 'a str };
+        let @vec = { 'b [@string] };
+        let @option = { 'c @vec };
+        let @box = { 'd @box, 'e {} };
+        res /a on get -> @option, put : @box -> @string;
+    "#;
+
+    let rs = Builder::new(eval(code)?).build();
+
+    assert!(rs.contains("pub struct String2 {\n"));
+    assert!(rs.contains("pub struct Vec2 {\n"));
+    assert!(rs.contains("pub struct Option2 {\n"));
+    assert!(rs.contains("pub struct Box2 {\n"));
+    assert_compiles("rust_prelude_names", &rs);
+
+    Ok(())
+}
+
+#[test]
+fn rust_reserved_names() -> anyhow::Result<()> {
+    let code = r#"
+        let @self = { 'kind str `enum: [self, other]` };
+        res /a on get -> @self;
+    "#;
+
+    let rs = Builder::new(eval(code)?).build();
+
+    assert!(rs.contains("pub struct Self2 {\n"));
+    assert!(rs.contains("    Self2,\n"));
+    assert_compiles("rust_reserved_names", &rs);
+
+    Ok(())
+}
@@ -152,6 +152,8 @@ pub enum Method {
     Delete,
     Options,
     Head,
+    Trace,
+    Query,
 }
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
@@ -13,6 +13,10 @@ pub enum TokenKind {
     CommentLine,
     #[regex(r"/\*([^*]|\*[^/])*\*/")]
     CommentBlock,
+    #[regex(r"///[^\r\n]*[\r\n]*")]
+    DocLine,
+    #[regex(r"/\*\*([^*]|\*[^/])*\*/")]
+    DocBlock,
     #[token("num")]
     PrimitiveNum,
     #[token("str")]
@@ -41,6 +45,10 @@ pub enum TokenKind {
     MethodOptions,
     #[token("head")]
     MethodHead,
+    #[token("trace")]
+    MethodTrace,
+    #[token("query")]
+    MethodQuery,
     #[token("media")]
     ContentMedia,
     #[token("headers")]
@@ -122,6 +130,9 @@ fn test_lexer() {
     let cases = [
         ("// comment", TokenKind::CommentLine),
         ("/* comment */", TokenKind::CommentBlock),
+        ("/// doc", TokenKind::DocLine),
+        ("/** doc */", TokenKind::DocBlock),
+        ("/**/", TokenKind::CommentBlock),
         ("\"string\"", TokenKind::LiteralString),
         ("499", TokenKind::LiteralNumber),
         ("4XX", TokenKind::LiteralHttpStatus),
@@ -155,9 +166,30 @@ impl TokenKind {
     pub fn is_comment(&self) -> bool {
         matches!(self, TokenKind::CommentLine | TokenKind::CommentBlock)
     }
+    pub fn is_doc(&self) -> bool {
+        matches!(self, TokenKind::DocLine | TokenKind::DocBlock)
+    }
     pub fn is_trivia(&self) -> bool {
         self.is_comment() || *self == TokenKind::Space
     }
+    /// Checks whether a token can start a construct to which doc comments are attached,
+    /// i.e. a declaration, a transfer or a term.
+    pub fn is_documentable(&self) -> bool {
+        *self == TokenKind::KeywordLet
+            || self.is_method()
+            || self.is_literal()
+            || self.is_primitive()
+            || self.is_path_element()
+            || self.is_identifier()
+            || matches!(
+                self,
+                TokenKind::Property
+                    | TokenKind::ControlBracketLeft
+                    | TokenKind::ControlBraceLeft
+                    | TokenKind::ControlChevronLeft
+                    | TokenKind::ControlParenLeft
+            )
+    }
     pub fn is_identifier(&self) -> bool {
         matches!(
             self,
@@ -190,6 +222,8 @@ impl TokenKind {
                 | TokenKind::MethodDelete
                 | TokenKind::MethodOptions
                 | TokenKind::MethodHead
+                | TokenKind::MethodTrace
+                | TokenKind::MethodQuery
         )
     }
     pub fn is_literal(&self) -> bool {
@@ -319,16 +353,162 @@ fn test_prefixed_string() {
     assert_eq!(parse_prefixed_string("'prop"), "prop");
 }
 
+fn parse_doc_line(input: &str) -> &str {
+    let line = input.trim_end_matches(['\r', '\n']);
+    assert!(line.len() >= 3, "should be a doc comment line");
+    let text = &line[3..];
+    text.strip_prefix(' ').unwrap_or(text)
+}
+
+#[test]
+fn test_parse_doc_line() {
+    assert_eq!(parse_doc_line("/// some *text*\r\n"), "some *text*");
+    assert_eq!(parse_doc_line("///    indented"), "   indented");
+    assert_eq!(parse_doc_line("///"), "");
+}
+
+fn parse_doc_block(input: &str) -> String {
+    let len = input.len();
+    assert!(len >= 5, "should be a doc comment block");
+    let inner = &input[3..len - 2];
+    // Strip the optional leading asterisk of each line.
+    let lines: Vec<_> = inner
+        .lines()
+        .map(|l| match l.trim_start().strip_prefix('*') {
+            Some(rest) => rest.strip_prefix(' ').unwrap_or(rest),
+            None => l,
+        })
+        .collect();
+    // Remove the indentation common to all non-blank lines,
+    // preserving any relative indentation meaningful to Markdown.
+    let indent = lines
+        .iter()
+        .filter(|l| !l.trim().is_empty())
+        .map(|l| l.len() - l.trim_start().len())
+        .min()
+        .unwrap_or(0);
+    let lines: Vec<_> = lines
+        .iter()
+        .map(|l| if l.len() >= indent { &l[indent..] } else { "" })
+        .map(str::trim_end)
+        .skip_while(|l| l.is_empty())
+        .collect();
+    let end = lines
+        .iter()
+        .rposition(|l| !l.is_empty())
+        .map_or(0, |i| i + 1);
+    lines[..end].join("\n")
+}
+
+#[test]
+fn test_parse_doc_block() {
+    assert_eq!(parse_doc_block("/** single line */"), "single line");
+    assert_eq!(
+        parse_doc_block("/**\n * Title\n *\n * - item\n *     code\n */"),
+        "Title\n\n- item\n    code"
+    );
+    assert_eq!(
+        parse_doc_block("/**\n    Title\n      indented\n*/"),
+        "Title\n  indented"
+    );
+}
+
+/// Checks whether a doc comment documents the construct that follows it.
+///
+/// Doc comments are otherwise plain comments, as are lines starting with four slashes
+/// and blocks starting with three asterisks, and comments in positions where the grammar
+/// takes no annotations, e.g. between the methods of a transfer or in a declaration head.
+fn is_attached<E>(
+    slice: &str,
+    prev: &[(Result<TokenKind, E>, std::ops::Range<usize>)],
+    next: &[(Result<TokenKind, E>, std::ops::Range<usize>)],
+) -> bool {
+    if slice.starts_with("////") || slice.starts_with("/***") {
+        return false;
+    }
+    let is_significant =
+        |k: &&TokenKind| !k.is_trivia() && !k.is_doc() && **k != TokenKind::AnnotationLine;
+    let before = &mut prev
+        .iter()
+        .rev()
+        .filter_map(|(r, _)| r.as_ref().ok())
+        .filter(is_significant);
+    let is_detached = match before.next() {
+        Some(k) if k.is_method() => true,
+        Some(TokenKind::ControlComma) => before.next().is_some_and(TokenKind::is_method),
+        Some(TokenKind::OperatorQuestionMark) => {
+            before.next().is_some_and(TokenKind::is_path_element)
+        }
+        Some(
+            TokenKind::KeywordLet
+            | TokenKind::KeywordRec
+            | TokenKind::KeywordUse
+            | TokenKind::KeywordAs
+            | TokenKind::ControlFullStop,
+        ) => true,
+        Some(k) if k.is_identifier() => before
+            .find(|k| !k.is_identifier())
+            .is_some_and(|k| *k == TokenKind::KeywordLet),
+        _ => false,
+    };
+    !is_detached
+        && next
+            .iter()
+            .filter_map(|(r, _)| r.as_ref().ok())
+            .find(is_significant)
+            .is_some_and(TokenKind::is_documentable)
+}
+
+/// Checks whether a name is reserved by the language, i.e. is lexed as a keyword
+/// rather than as a value identifier.
+pub fn is_keyword(name: &str) -> bool {
+    let mut lex = TokenKind::lexer(name);
+    let reserved = matches!(
+        lex.next(),
+        Some(Ok(k)) if k.is_primitive() || k.is_method() || k.is_content() || matches!(
+            k,
+            TokenKind::KeywordLet
+                | TokenKind::KeywordRes
+                | TokenKind::KeywordUse
+                | TokenKind::KeywordAs
+                | TokenKind::KeywordOn
+                | TokenKind::KeywordRec
+        )
+    );
+    reserved && lex.next().is_none()
+}
+
+#[test]
+fn test_is_keyword() {
+    assert!(is_keyword("let"));
+    assert!(is_keyword("int"));
+    assert!(is_keyword("query"));
+    assert!(is_keyword("headers"));
+    assert!(!is_keyword("interest"));
+    assert!(!is_keyword("let_"));
+    assert!(!is_keyword("@let"));
+}
+
 /// Parses a string of characters, yields a list of tokens and/or errors.
 pub fn tokenize(loc: Locator, input: &str) -> (Option<TokenList<Token>>, Vec<ParserError>) {
-    let lexer = TokenKind::lexer(input).spanned();
+    let lexed: Vec<_> = TokenKind::lexer(input).spanned().collect();
     let mut list = TokenList::new(loc.clone());
     let mut errors = Vec::new();
 
-    for (result, range) in lexer {
-        match result {
+    for (index, (result, range)) in lexed.iter().enumerate() {
+        let range = range.clone();
+        match *result {
             Ok(kind) => {
                 let slice = &input[range.clone()];
+                let kind =
+                    if kind.is_doc() && !is_attached(slice, &lexed[..index], &lexed[index + 1..]) {
+                        match kind {
+                            TokenKind::DocLine => TokenKind::CommentLine,
+                            _ => TokenKind::CommentBlock,
+                        }
+                    } else {
+                        kind
+                    };
                 let value = match kind {
                     TokenKind::LiteralNumber => TokenValue::Number(parse_number(slice)),
                     TokenKind::LiteralString => {
@@ -343,6 +523,10 @@ pub fn tokenize(loc: Locator, input: &str) -> (Option<TokenList<Token>>, Vec<Par
                     TokenKind::AnnotationInline => {
                         TokenValue::Symbol(list.register(parse_quoted_string(slice)))
                     }
+                    TokenKind::DocLine => TokenValue::Symbol(list.register(parse_doc_line(slice))),
+                    TokenKind::DocBlock => {
+                        TokenValue::Symbol(list.register(parse_doc_block(slice)))
+                    }
                     TokenKind::IdentifierReference => TokenValue::Symbol(list.register(slice)),
                     TokenKind::IdentifierValue => TokenValue::Symbol(list.register(slice)),
                     TokenKind::PathElementSegment => {
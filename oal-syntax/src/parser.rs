@@ -96,6 +96,8 @@ impl<'a, T: Core> Method<'a, T> {
             TokenKind::MethodDelete => atom::Method::Delete,
             TokenKind::MethodOptions => atom::Method::Options,
             TokenKind::MethodHead => atom::Method::Head,
+            TokenKind::MethodTrace => atom::Method::Trace,
+            TokenKind::MethodQuery => atom::Method::Query,
             _ => unreachable!(),
         }
     }
@@ -199,6 +201,15 @@ impl<'a, T: Core> Annotation<'a, T> {
     }
 }
 
+terminal_node!(Gram, Doc, k if k.is_doc());
+
+impl<'a, T: Core> Doc<'a, T> {
+    /// Returns the text of the doc comment, stripped of comment markers.
+    pub fn as_str(&self) -> &'a str {
+        self.node().as_str()
+    }
+}
+
 // TODO: add support for document attributes
 syntax_nodes!(
     Gram,
@@ -264,6 +275,10 @@ impl<'a, T: Core> Annotations<'a, T> {
     pub fn items(&self) -> impl Iterator<Item = Annotation<'a, T>> {
         self.node().children().filter_map(Annotation::cast)
     }
+
+    pub fn docs(&self) -> impl Iterator<Item = Doc<'a, T>> {
+        self.node().children().filter_map(Doc::cast)
+    }
 }
 
 impl<'a, T: Core> Binding<'a, T> {
@@ -286,6 +301,12 @@ impl<'a, T: Core> Declaration<'a, T> {
             .items()
     }
 
+    pub fn docs(&self) -> impl Iterator<Item = Doc<'a, T>> {
+        Annotations::cast(self.node().nth(Self::ANNOTATIONS_POS))
+            .expect("expected annotations")
+            .docs()
+    }
+
     pub fn identifier(&self) -> Identifier<'a, T> {
         Identifier::cast(self.node().nth(Self::IDENTIFIER_POS))
             .expect("declaration lhs must be an identifier")
@@ -366,6 +387,12 @@ impl<'a, T: Core> Terminal<'a, T> {
     pub fn annotations(&self) -> impl Iterator<Item = Annotation<'a, T>> {
         self.prefix_annotations().chain(self.suffix_annotation())
     }
+
+    pub fn docs(&self) -> impl Iterator<Item = Doc<'a, T>> {
+        Annotations::cast(self.node().nth(Self::PREFIX_ANN_POS))
+            .expect("expected annotations")
+            .docs()
+    }
 }
 
 impl<'a, T: Core> Array<'a, T> {
@@ -519,10 +546,23 @@ impl<'a, T: Core> XferDomain<'a, T> {
 }
 
 impl<'a, T: Core> Transfer<'a, T> {
-    const METHODS_POS: usize = 0;
-    const PARAMS_POS: usize = 1;
-    const DOMAIN_POS: usize = 2;
-    const RANGE_POS: usize = 4;
+    const ANNOTATIONS_POS: usize = 0;
+    const METHODS_POS: usize = 1;
+    const PARAMS_POS: usize = 2;
+    const DOMAIN_POS: usize = 3;
+    const RANGE_POS: usize = 5;
+
+    pub fn annotations(&self) -> impl Iterator<Item = Annotation<'a, T>> {
+        Annotations::cast(self.node().nth(Self::ANNOTATIONS_POS))
+            .expect("expected annotations")
+            .items()
+    }
+
+    pub fn docs(&self) -> impl Iterator<Item = Doc<'a, T>> {
+        Annotations::cast(self.node().nth(Self::ANNOTATIONS_POS))
+            .expect("expected annotations")
+            .docs()
+    }
 
     pub fn methods(&self) -> impl Iterator<Item = atom::Method> + 'a {
         XferMethods::cast(self.node().nth(Self::METHODS_POS))
@@ -715,7 +755,9 @@ pub fn parse_identifier<T: Core>(c: &mut Context<T>, s: Cursor) -> ParserResult
 
 pub fn parse_line_annotations<T: Core>(c: &mut Context<T>, s: Cursor) -> ParserResult {
     let ns = &mut Vec::new();
-    let p: ParserFn<T> = |c, s| parse_token(c, s, TokenKind::AnnotationLine);
+    // Doc comments are collected along with line annotations.
+    let p: ParserFn<T> =
+        |c, s| parse_token_with(c, s, |k| *k == TokenKind::AnnotationLine || k.is_doc());
     let s = repeat(c, s, ns, &[p]);
     Ok((s, c.compose(SyntaxKind::Annotations, ns)))
 }
@@ -1083,14 +1125,18 @@ fn test_parse_xfer_methods() {
 }
 
 pub fn parse_transfer<T: Core>(c: &mut Context<T>, s: Cursor) -> ParserResult {
-    let (s, n0) = parse_xfer_methods(c, s)?;
-    let (s, n1) =
-        parse_xfer_params(c, s).unwrap_or_else(|_| (s, c.compose(SyntaxKind::XferParams, &[])));
+    let (s, n0) = parse_line_annotations(c, s)?;
+    let (s, n1) = parse_xfer_methods(c, s)?;
     let (s, n2) =
+        parse_xfer_params(c, s).unwrap_or_else(|_| (s, c.compose(SyntaxKind::XferParams, &[])));
+    let (s, n3) =
         parse_xfer_domain(c, s).unwrap_or_else(|_| (s, c.compose(SyntaxKind::XferDomain, &[])));
-    let (s, n3) = parse_token(c, s, TokenKind::OperatorArrow)?;
-    let (s, n4) = parse_range_kind(c, s)?;
-    Ok((s, c.compose(SyntaxKind::Transfer, &[n0, n1, n2, n3, n4])))
+    let (s, n4) = parse_token(c, s, TokenKind::OperatorArrow)?;
+    let (s, n5) = parse_range_kind(c, s)?;
+    Ok((
+        s,
+        c.compose(SyntaxKind::Transfer, &[n0, n1, n2, n3, n4, n5]),
+    ))
 }
 
 #[test]
@@ -203,6 +203,12 @@ fn parse_decl_transfer() {
         Content::cast(assert_term(opds.next().expect("expected operand")))
             .expect("expected second content");
         assert!(opds.next().is_none(), "expected no more operand");
+    });
+    parse("let a = trace, query : {} -> {};", |p: Prog| {
+        let xfer = Transfer::cast(assert_decl(p, "a").rhs()).expect("expected transfer");
+
+        let methods: Vec<_> = xfer.methods().collect();
+        assert_eq!(methods, vec![atom::Method::Trace, atom::Method::Query]);
     })
 }
 
@@ -552,6 +558,113 @@ let r = {};
     )
 }
 
+#[test]
+fn parse_doc_comments() {
+    parse(
+        r#"
+/// Some *identifier*.
+/// Second line.
+# required: true
+let id = num;
+/**
+ * Some record.
+ */
+let r = {
+    /// Some property.
+    'p str
+};
+let x = /// Some transfer.
+    get -> r;
+"#,
+        |p: Prog| {
+            let decls = &mut p.declarations();
+
+            let decl = decls.next().expect("expected a declaration");
+            assert_eq!(
+                decl.docs().map(|d| d.as_str()).collect::<Vec<_>>(),
+                vec!["Some *identifier*.", "Second line."]
+            );
+            assert_eq!(decl.annotations().count(), 1);
+
+            let decl = decls.next().expect("expected another declaration");
+            let docs = &mut decl.docs();
+            assert_eq!(
+                docs.next().expect("expected a doc comment").as_str(),
+                "Some record."
+            );
+            assert!(docs.next().is_none(), "expected no more doc comment");
+
+            let obj = Object::cast(assert_term(decl.rhs())).expect("expected an object");
+            let n = obj.properties().next().expect("expected a property");
+            let term = Terminal::cast(n).expect("expected a terminal");
+            assert_eq!(
+                term.docs().map(|d| d.as_str()).collect::<Vec<_>>(),
+                vec!["Some property."]
+            );
+
+            let decl = decls.next().expect("expected another declaration");
+            let xfer = Transfer::cast(decl.rhs()).expect("expected a transfer");
+            assert_eq!(
+                xfer.docs().map(|d| d.as_str()).collect::<Vec<_>>(),
+                vec!["Some transfer."]
+            );
+        },
+    )
+}
+
+#[test]
+fn parse_detached_doc_comments() {
+    parse(
+        r#"
+/// Before an import.
+use "module.oal";
+let a = {};
+/// Before a resource.
+res /a on get -> a;
+////////////////
+/// Documents b.
+//// Not a doc comment.
+let b = {};
+let c = /** Before a recursion. */ rec x [x];
+/// At the end of the file.
+"#,
+        |p: Prog| {
+            assert_eq!(p.imports().count(), 1);
+            assert_eq!(p.resources().count(), 1);
+            let decls = &mut p.declarations();
+            let decl = decls.next().expect("expected a declaration");
+            assert_eq!(decl.docs().count(), 0);
+            let decl = decls.next().expect("expected another declaration");
+            assert_eq!(
+                decl.docs().map(|d| d.as_str()).collect::<Vec<_>>(),
+                vec!["Documents b."]
+            );
+            let decl = decls.next().expect("expected another declaration");
+            Recursion::cast(decl.rhs()).expect("expected a recursion");
+        },
+    )
+}
+
+#[test]
+fn parse_doc_comments_without_annotations() {
+    parse(
+        r#"
+let b = get, /// Between methods.
+    put -> {};
+let f /// In a declaration head.
+    x = x;
+"#,
+        |p: Prog| {
+            let decls = &mut p.declarations();
+            let decl = decls.next().expect("expected a declaration");
+            let xfer = Transfer::cast(decl.rhs()).expect("expected a transfer");
+            assert_eq!(xfer.docs().count(), 0);
+            let decl = decls.next().expect("expected another declaration");
+            assert_eq!(decl.bindings().count(), 1);
+        },
+    )
+}
+
 #[test]
 fn parse_recursion() {
     parse("let a = rec x [x];", |p: Prog| {
@@ -0,0 +1,351 @@
+pub mod errors;
+
+#[cfg(test)]
+mod tests;
+
+use crate::errors::{Error, Result};
+use oal_compiler::spec;
+use oal_compiler::spec::SchemaExpr;
+use oal_model::span::Span;
+use oal_openapi::codegen::{literal, pascal, schema_label};
+use oal_openapi::Naming;
+use oal_syntax::atom;
+use std::collections::{HashMap, HashSet};
+
+/// The type of objects without properties.
+const EMPTY_OBJECT: &str = "Record<string, unknown>";
+
+/// The type of requests without parameters nor body.
+const EMPTY_REQUEST: &str = "Record<string, never>";
+
+/// The global types that generated declarations must not shadow.
+const BUILTINS: &[&str] = &[
+    "Array", "Boolean", "Date", "Error", "Function", "Map", "Number", "Object", "Partial",
+    "Promise", "Record", "RegExp", "Set", "String", "Symbol",
+];
+
+fn is_identifier(name: &str) -> bool {
+    name.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_' || c == '$')
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$')
+}
+
+/// Renders a property key, quoted if not an identifier.
+fn key(name: &str) -> String {
+    if is_identifier(name) {
+        name.to_owned()
+    } else {
+        literal(name)
+    }
+}
+
+/// Renders a documentation comment, if there is anything to document.
+fn doc(desc: Option<&str>, deprecated: bool, indent: &str) -> String {
+    let mut lines: Vec<String> = desc
+        .into_iter()
+        .flat_map(str::lines)
+        .map(|l| l.replace("*/", "*\\/"))
+        .collect();
+    if deprecated {
+        lines.push("@deprecated".to_owned());
+    }
+    match lines.as_slice() {
+        [] => String::new(),
+        [line] => format!("{indent}/** {line} */\n"),
+        lines => {
+            let body: String = lines
+                .iter()
+                .map(|l| {
+                    if l.is_empty() {
+                        format!("{indent} *\n")
+                    } else {
+                        format!("{indent} * {l}\n")
+                    }
+                })
+                .collect();
+            format!("{indent}/**\n{body}{indent} */\n")
+        }
+    }
+}
+
+/// Allocates the type names of the schema references,
+/// avoiding the global types and the generated operation types.
+fn type_names(spec: &spec::Spec, naming: &Naming) -> HashMap<atom::Ident, String> {
+    let mut used: HashSet<String> = BUILTINS.iter().map(|s| s.to_string()).collect();
+    used.insert("Operations".to_owned());
+    for rel in spec.rels.iter() {
+        let xfers = rel
+            .xfers
+            .iter()
+            .filter_map(|(m, x)| x.as_ref().map(|x| (m, x)));
+        for (method, xfer) in xfers {
+            let base = pascal(&naming.transfer_id(xfer, method, &rel.uri));
+            used.insert(format!("{base}Request"));
+            used.insert(format!("{base}Response"));
+        }
+    }
+    let mut names = HashMap::new();
+    for (ident, reference) in spec.refs.iter() {
+        let spec::Reference::Schema(schema) = reference else {
+            continue;
+        };
+        let base = pascal(&schema_label(ident, schema));
+        let mut name = base.clone();
+        let mut n = 1;
+        while !used.insert(name.clone()) {
+            n += 1;
+            name = format!("{base}{n}");
+        }
+        names.insert(ident.clone(), name);
+    }
+    names
+}
+
+pub struct Builder {
+    spec: spec::Spec,
+    naming: Naming,
+    /// The type names of the schema references.
+    names: HashMap<atom::Ident, String>,
+}
+
+impl Builder {
+    pub fn new(spec: spec::Spec) -> Builder {
+        let naming = Naming::default();
+        let names = type_names(&spec, &naming);
+        Builder {
+            spec,
+            naming,
+            names,
+        }
+    }
+
+    /// Sets the naming strategy for operation identifiers.
+    pub fn with_naming(mut self, naming: Naming) -> Self {
+        self.names = type_names(&self.spec, &naming);
+        self.naming = naming;
+        self
+    }
+
+    /// Returns the type of a schema.
+    fn schema(&self, s: &spec::Schema) -> String {
+        match &s.expr {
+            SchemaExpr::Num(_) | SchemaExpr::Int(_) => "number".to_owned(),
+            SchemaExpr::Str(p) if !p.enumeration.is_empty() => p
+                .enumeration
+                .iter()
+                .map(|e| literal(e))
+                .collect::<Vec<_>>()
+                .join(" | "),
+            SchemaExpr::Str(_) | SchemaExpr::Uri(_) | SchemaExpr::Rel(_) => "string".to_owned(),
+            SchemaExpr::Bool(_) => "boolean".to_owned(),
+            SchemaExpr::Array(a) => format!("{}[]", self.operand(&a.item)),
+            SchemaExpr::Object(o) => self.object([o], spec::Property::is_required),
+            SchemaExpr::Op(op) => {
+                let sep = match op.op {
+                    atom::VariadicOperator::Join => " & ",
+                    atom::VariadicOperator::Any | atom::VariadicOperator::Sum => " | ",
+                    atom::VariadicOperator::Range => unreachable!(),
+                };
+                op.schemas
+                    .iter()
+                    .map(|s| self.operand(s))
+                    .collect::<Vec<_>>()
+                    .join(sep)
+            }
+            SchemaExpr::Ref(ident) => self
+                .names
+                .get(ident)
+                .cloned()
+                .unwrap_or_else(|| "unknown".to_owned()),
+        }
+    }
+
+    /// Returns the type of a schema as operand of an array or of another operator.
+    fn operand(&self, s: &spec::Schema) -> String {
+        let is_compound = match &s.expr {
+            SchemaExpr::Op(op) => op.schemas.len() > 1,
+            SchemaExpr::Str(p) => p.enumeration.len() > 1,
+            _ => false,
+        };
+        if is_compound {
+            format!("({})", self.schema(s))
+        } else {
+            self.schema(s)
+        }
+    }
+
+    fn property(&self, p: &spec::Property, required: bool) -> String {
+        let mark = if required { "" } else { "?" };
+        format!("{}{mark}: {}", key(p.name.as_ref()), self.schema(&p.schema))
+    }
+
+    /// Returns the inline type of an object.
+    fn object<'a, I>(&self, o: I, required: fn(&spec::Property) -> bool) -> String
+    where
+        I: IntoIterator<Item = &'a spec::Object>,
+    {
+        let props: Vec<_> = o
+            .into_iter()
+            .flat_map(|o| o.props.iter())
+            .map(|p| self.property(p, required(p)))
+            .collect();
+        if props.is_empty() {
+            EMPTY_OBJECT.to_owned()
+        } else {
+            format!("{{ {} }}", props.join("; "))
+        }
+    }
+
+    /// Returns the declaration of a schema reference.
+    fn declaration(&self, name: &str, s: &spec::Schema) -> String {
+        let comment = doc(s.desc.as_deref(), s.deprecated, "");
+        match &s.expr {
+            SchemaExpr::Object(o) if !o.props.is_empty() => {
+                let body: String = o
+                    .props
+                    .iter()
+                    .map(|p| {
+                        let desc = p.desc.as_deref().or(p.schema.desc.as_deref());
+                        let deprecated = p.deprecated || p.schema.deprecated;
+                        format!(
+                            "{}  {};\n",
+                            doc(desc, deprecated, "  "),
+                            self.property(p, p.is_required())
+                        )
+                    })
+                    .collect();
+                format!("{comment}export interface {name} {{\n{body}}}\n")
+            }
+            _ => format!("{comment}export type {name} = {};\n", self.schema(s)),
+        }
+    }
+
+    /// Returns the declaration of the request type of an operation.
+    fn request(&self, name: &str, uri: &spec::Uri, xfer: &spec::Transfer) -> String {
+        let mut fields = Vec::new();
+        let path: Vec<_> = uri
+            .path
+            .iter()
+            .filter_map(|s| match s {
+                spec::UriSegment::Variable(p) => Some(self.property(p, true)),
+                spec::UriSegment::Literal(_) => None,
+            })
+            .collect();
+        if !path.is_empty() {
+            fields.push(format!("path: {{ {} }}", path.join("; ")));
+        }
+        let params = [uri.params.as_ref(), xfer.params.as_ref()];
+        if params.iter().flatten().any(|o| !o.props.is_empty()) {
+            let required = params
+                .iter()
+                .flatten()
+                .any(|o| o.props.iter().any(spec::Property::is_required_param));
+            let mark = if required { "" } else { "?" };
+            let query = self.object(
+                params.into_iter().flatten(),
+                spec::Property::is_required_param,
+            );
+            fields.push(format!("query{mark}: {query}"));
+        }
+        if let Some(headers) = xfer.domain.headers.as_ref().filter(|h| !h.props.is_empty()) {
+            let required = headers.props.iter().any(spec::Property::is_required_param);
+            let mark = if required { "" } else { "?" };
+            let headers = self.object([headers], spec::Property::is_required_param);
+            fields.push(format!("headers{mark}: {headers}"));
+        }
+        if let Some(schema) = &xfer.domain.schema {
+            fields.push(format!("body: {}", self.schema(schema)));
+        }
+        if fields.is_empty() {
+            format!("export type {name} = {EMPTY_REQUEST};\n")
+        } else {
+            let body: String = fields.iter().map(|f| format!("  {f};\n")).collect();
+            format!("export interface {name} {{\n{body}}}\n")
+        }
+    }
+
+    /// Returns the declaration of the response type of an operation.
+    fn response(&self, name: &str, xfer: &spec::Transfer) -> String {
+        let variants: Vec<_> = xfer
+            .ranges
+            .values()
+            .map(|c| {
+                let status = match c.status {
+                    Some(atom::HttpStatus::Code(code)) => code.to_string(),
+                    _ => "number".to_owned(),
+                };
+                let mut fields = vec![format!("status: {status}")];
+                if let Some(headers) = c.headers.as_ref().filter(|h| !h.props.is_empty()) {
+                    let headers = self.object([headers], spec::Property::is_required_param);
+                    fields.push(format!("headers: {headers}"));
+                }
+                if let Some(schema) = &c.schema {
+                    fields.push(format!("body: {}", self.schema(schema)));
+                }
+                format!("{{ {} }}", fields.join("; "))
+            })
+            .collect();
+        match variants.as_slice() {
+            [] => format!("export type {name} = {{ status: number }};\n"),
+            [variant] => format!("export type {name} = {variant};\n"),
+            variants => {
+                let body: String = variants.iter().map(|v| format!("\n  | {v}")).collect();
+                format!("export type {name} ={body};\n")
+            }
+        }
+    }
+
+    /// Generates the TypeScript declarations.
+    pub fn build(&self) -> Result<String> {
+        let mut decls = Vec::new();
+        for (ident, reference) in self.spec.refs.iter() {
+            if let spec::Reference::Schema(s) = reference {
+                decls.push(self.declaration(&self.names[ident], s));
+            }
+        }
+
+        let mut ids: HashMap<String, Option<Span>> = HashMap::new();
+        let mut entries = Vec::new();
+        for rel in self.spec.rels.iter() {
+            let xfers = rel
+                .xfers
+                .iter()
+                .filter_map(|(m, x)| x.as_ref().map(|x| (m, x)));
+            for (method, xfer) in xfers {
+                let id = self.naming.transfer_id(xfer, method, &rel.uri);
+                // Distinct identifiers can still map to the same type names.
+                let base = pascal(&id);
+                let span = xfer.span.clone().or_else(|| rel.span.clone());
+                if let Some(previous) = ids.insert(base.clone(), span.clone()) {
+                    return Err(Error::DuplicateOperationId {
+                        id,
+                        span,
+                        previous,
+                    });
+                }
+                let request = format!("{base}Request");
+                let response = format!("{base}Response");
+                decls.push(self.request(&request, &rel.uri, xfer));
+                decls.push(self.response(&response, xfer));
+                let desc = xfer.summary.as_deref().or(xfer.desc.as_deref());
+                entries.push(format!(
+                    "{}  {}: {{ request: {request}; response: {response} }};\n",
+                    doc(desc, xfer.deprecated, "  "),
+                    key(&id)
+                ));
+            }
+        }
+        if !entries.is_empty() {
+            decls.push(format!(
+                "export interface Operations {{\n{}}}\n",
+                entries.concat()
+            ));
+        }
+
+        Ok(format!(
+            "// Generated from an Oxlip program.\n\n{}",
+            decls.join("\n")
+        ))
+    }
+}
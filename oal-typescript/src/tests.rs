@@ -0,0 +1,120 @@
+use crate::errors::Error;
+use crate::Builder;
+use oal_compiler::tests::eval;
+use oal_openapi::Naming;
+
+#[test]
+fn typescript_schemas() -> anyhow::Result<()> {
+    let code = r#"
+        let @kind = str `enum: [small, large]`;
+        let @named = { 'name! str };
+        # description: "An item of the catalog."
+        let @item = @named & {
+          'id! int
+        , 'kind @kind
+        , 'tags [str | int]
+        , 'x-ref uri
+        };
+        let @choice = @named ~ bool;
+        let @node = { 'children [@node] `description: "The subtrees."` };
+        res /items on get -> @item, put : @choice -> <>;
+        res /nodes on get -> @node;
+    "#;
+
+    let ts = Builder::new(eval(code)?).build()?;
+
+    assert!(ts.contains(r#"export type Kind = "small" | "large";"#));
+    assert!(ts.contains("export interface Named {\n  name: string;\n}\n"));
+    assert!(ts.contains(
+        "/** An item of the catalog. */\n\
+         export type Item = Named & { id: number; kind?: Kind; tags?: (string | number)[]; \"x-ref\"?: string };\n"
+    ));
+    assert!(ts.contains("export type Choice = Named | boolean;"));
+    assert!(
+        ts.contains("export interface Node {\n  /** The subtrees. */\n  children?: Node[];\n}\n")
+    );
+
+    Ok(())
+}
+
+#[test]
+fn typescript_operations() -> anyhow::Result<()> {
+    let code = r#"
+        let @item = { 'id! int };
+        let h = { 'etag! str };
+        res /items/{ 'id int } ? { 'q str } on
+          # summary: "Update an item"
+          put { 'dry! bool } : <headers={ 'if-match str }, @item>
+            -> <status=200, headers=h, @item>
+            :: <status=404, {}>,
+          delete -> <status=204>;
+    "#;
+
+    let ts = Builder::new(eval(code)?)
+        .with_naming(Naming::Camel)
+        .build()?;
+
+    assert!(ts.contains(
+        "export interface PutItemsIdRequest {\n  \
+           path: { id: number };\n  \
+           query: { q?: string; dry: boolean };\n  \
+           headers?: { \"if-match\"?: string };\n  \
+           body: Item;\n\
+         }\n"
+    ));
+    assert!(ts.contains(
+        "export type PutItemsIdResponse =\n  \
+           | { status: 200; headers: { etag: string }; body: Item }\n  \
+           | { status: 404; body: Record<string, unknown> };\n"
+    ));
+    assert!(ts.contains("export interface DeleteItemsIdRequest {\n  path: { id: number };\n  query?: { q?: string };\n}\n"));
+    assert!(ts.contains("export type DeleteItemsIdResponse = { status: 204 };\n"));
+    assert!(ts.contains(
+        "export interface Operations {\n  \
+           /** Update an item */\n  \
+           putItemsId: { request: PutItemsIdRequest; response: PutItemsIdResponse };\n  \
+           deleteItemsId: { request: DeleteItemsIdRequest; response: DeleteItemsIdResponse };\n\
+         }\n"
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn typescript_duplicate_operation() -> anyhow::Result<()> {
+    let code = r#"
+        res /a on
+          # operationId: "list-items"
+          get -> {};
+        res /b on
+          # operationId: "listItems"
+          get -> {};
+    "#;
+
+    let err = Builder::new(eval(code)?)
+        .build()
+        .expect_err("expected a duplicate operation");
+    assert!(matches!(err, Error::DuplicateOperationId { .. }));
+
+    Ok(())
+}
+
+#[test]
+fn typescript_reserved_names() -> anyhow::Result<()> {
+    let code = r#"
+        let @record = { 'id int };
+        let @operations = {};
+        let @get-items-response = str;
+        res /items on get -> { 'r @record, 'o @operations, 's @get-items-response };
+    "#;
+
+    let ts = Builder::new(eval(code)?).build()?;
+
+    assert!(ts.contains("export interface Record2 {\n  id?: number;\n}\n"));
+    assert!(ts.contains("export type Operations2 = Record<string, unknown>;\n"));
+    assert!(ts.contains("export type GetItemsResponse2 = string;\n"));
+    assert!(ts.contains("r?: Record2; o?: Operations2; s?: GetItemsResponse2"));
+    assert!(!ts.contains("export interface Record "));
+
+    Ok(())
+}
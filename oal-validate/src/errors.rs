@@ -0,0 +1,17 @@
+use oal_syntax::atom;
+
+/// The operation lookup error type.
+#[derive(thiserror::Error, Debug)]
+pub enum Error {
+    #[error("no resource matches {path}")]
+    NotFound { path: String },
+    #[error("method {method} is not allowed on {path}")]
+    MethodNotAllowed {
+        method: String,
+        path: String,
+        /// The methods declared by the resource.
+        allowed: Vec<atom::Method>,
+    },
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
@@ -0,0 +1,324 @@
+pub mod errors;
+pub mod validation;
+
+#[cfg(test)]
+mod tests;
+
+use crate::errors::{Error, Result};
+pub use crate::validation::{describe, Validator, Violation};
+use oal_compiler::spec::{self, status_label, UriSegment};
+use oal_syntax::atom;
+use serde_json::Value;
+
+/// The media type of contents without an explicit one.
+const DEFAULT_MEDIA: &str = "application/json";
+
+/// A request or a response exchanged in an operation.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Message {
+    pub headers: Vec<(String, String)>,
+    /// The media type of the body, taken from the `content-type` header if unspecified.
+    pub media: Option<String>,
+    pub body: Option<Value>,
+}
+
+impl Message {
+    /// Returns the value of a header, ignoring the case of its name.
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(n, _)| n.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.as_str())
+    }
+
+    fn media(&self) -> Option<&str> {
+        self.media
+            .as_deref()
+            .or_else(|| self.header("content-type"))
+    }
+}
+
+fn method_from(name: &str) -> Option<atom::Method> {
+    match name.to_ascii_uppercase().as_str() {
+        "GET" => Some(atom::Method::Get),
+        "PUT" => Some(atom::Method::Put),
+        "POST" => Some(atom::Method::Post),
+        "PATCH" => Some(atom::Method::Patch),
+        "DELETE" => Some(atom::Method::Delete),
+        "OPTIONS" => Some(atom::Method::Options),
+        "HEAD" => Some(atom::Method::Head),
+        "TRACE" => Some(atom::Method::Trace),
+        "QUERY" => Some(atom::Method::Query),
+        _ => None,
+    }
+}
+
+/// Ranks how closely a declared status covers the given code, lower is closer.
+fn status_rank(status: Option<&atom::HttpStatus>, code: u16) -> Option<u8> {
+    match status {
+        Some(atom::HttpStatus::Code(c)) => (c.get() == code).then_some(0),
+        Some(atom::HttpStatus::Range(r)) => {
+            let class = match r {
+                atom::HttpStatusRange::Info => 1,
+                atom::HttpStatusRange::Success => 2,
+                atom::HttpStatusRange::Redirect => 3,
+                atom::HttpStatusRange::ClientError => 4,
+                atom::HttpStatusRange::ServerError => 5,
+            };
+            (class == code / 100).then_some(1)
+        }
+        None => Some(2),
+    }
+}
+
+/// Returns the media type without parameters, in lowercase.
+fn essence(media: &str) -> String {
+    media
+        .split(';')
+        .next()
+        .unwrap_or(media)
+        .trim()
+        .to_ascii_lowercase()
+}
+
+/// Checks whether a declared media type, if any, accepts the given one.
+fn media_matches(declared: Option<&str>, actual: &str) -> bool {
+    let actual = essence(actual);
+    match declared {
+        Some(declared) => essence(declared) == actual,
+        None => actual.ends_with("json"),
+    }
+}
+
+/// Splits a path into percent-decoded segments.
+fn segments(path: &str) -> Vec<String> {
+    path.strip_prefix('/')
+        .unwrap_or(path)
+        .split('/')
+        .map(|s| {
+            percent_encoding::percent_decode_str(s)
+                .decode_utf8_lossy()
+                .into_owned()
+        })
+        .collect()
+}
+
+/// Validates payloads against the operations of a specification.
+pub struct Contract<'a> {
+    spec: &'a spec::Spec,
+    validator: Validator<'a>,
+}
+
+impl<'a> Contract<'a> {
+    pub fn new(spec: &'a spec::Spec) -> Self {
+        Contract {
+            spec,
+            validator: Validator::new(&spec.refs),
+        }
+    }
+
+    /// Accepts properties not declared by object schemas, which are reported by default.
+    pub fn allow_unknown_fields(mut self) -> Self {
+        self.validator = self.validator.allow_unknown_fields();
+        self
+    }
+
+    /// Finds the operation for a method and a concrete path without query string, e.g. `/items/1`.
+    ///
+    /// Among the resources matching the path, the one with the most literal segments is chosen.
+    pub fn operation(&self, method: &str, path: &str) -> Result<Operation<'a>> {
+        let segments = segments(path);
+        let mut best: Option<(usize, &spec::Relation, Variables)> = None;
+        for rel in self.spec.rels.iter() {
+            if rel.uri.path.len() != segments.len() {
+                continue;
+            }
+            let mut literals = 0;
+            let mut vars = Vec::new();
+            let matched = rel.uri.path.iter().zip(&segments).all(|(s, text)| match s {
+                UriSegment::Literal(l) => {
+                    literals += 1;
+                    l.as_ref() == text
+                }
+                UriSegment::Variable(p) => {
+                    vars.push((p.as_ref(), text.clone()));
+                    !text.is_empty()
+                }
+            });
+            if matched && best.as_ref().is_none_or(|(n, _, _)| literals > *n) {
+                best = Some((literals, rel, vars));
+            }
+        }
+        let Some((_, relation, variables)) = best else {
+            return Err(Error::NotFound {
+                path: path.to_owned(),
+            });
+        };
+        let found = method_from(method).and_then(|m| relation.xfers[m].as_ref().map(|x| (m, x)));
+        let Some((method, transfer)) = found else {
+            return Err(Error::MethodNotAllowed {
+                method: method.to_owned(),
+                path: path.to_owned(),
+                allowed: relation
+                    .xfers
+                    .iter()
+                    .filter_map(|(m, x)| x.as_ref().map(|_| m))
+                    .collect(),
+            });
+        };
+        Ok(Operation {
+            method,
+            relation,
+            transfer,
+            variables,
+            validator: self.validator,
+        })
+    }
+}
+
+/// The values of the path variables of a request.
+pub type Variables<'a> = Vec<(&'a spec::Property, String)>;
+
+/// An operation of a specification, matched by a concrete path.
+pub struct Operation<'a> {
+    pub method: atom::Method,
+    pub relation: &'a spec::Relation,
+    pub transfer: &'a spec::Transfer,
+    /// The values of the path variables.
+    pub variables: Variables<'a>,
+    validator: Validator<'a>,
+}
+
+impl<'a> Operation<'a> {
+    /// Returns the response contents declared for the closest status to the given one.
+    ///
+    /// Exact status codes take precedence over status ranges, which take precedence over default responses.
+    pub fn responses(&self, status: u16) -> Vec<&'a spec::Content> {
+        let ranges = &self.transfer.ranges;
+        let rank = |s: &Option<atom::HttpStatus>| status_rank(s.as_ref(), status);
+        let Some(best) = ranges.keys().filter_map(|(s, _)| rank(s)).min() else {
+            return Vec::new();
+        };
+        ranges
+            .iter()
+            .filter(|((s, _), _)| rank(s) == Some(best))
+            .map(|(_, c)| c)
+            .collect()
+    }
+
+    /// Validates a request with its query parameters.
+    pub fn validate_request(&self, query: &[(String, String)], msg: &Message) -> Vec<Violation> {
+        let mut out = Vec::new();
+
+        for (prop, value) in self.variables.iter() {
+            let path = format!("/path/{}", prop.name);
+            self.validator.param(&path, &prop.schema, value, &mut out);
+        }
+
+        let params = [
+            self.relation.uri.params.as_ref(),
+            self.transfer.params.as_ref(),
+        ];
+        for prop in params.into_iter().flatten().flat_map(|o| o.props.iter()) {
+            let name = prop.name.as_ref();
+            let path = format!("/query/{name}");
+            match query.iter().find(|(n, _)| n == name) {
+                Some((_, value)) => self.validator.param(&path, &prop.schema, value, &mut out),
+                None if prop.is_required_param() => out.push(Violation::new(
+                    path,
+                    describe(&prop.schema),
+                    "missing required parameter",
+                )),
+                None => {}
+            }
+        }
+
+        let domain = &self.transfer.domain;
+        self.headers(domain, msg, &mut out);
+        if let (Some(declared), Some(actual)) = (domain.media.as_deref(), msg.media()) {
+            if !media_matches(Some(declared), actual) {
+                out.push(Violation::new(
+                    "/header/content-type",
+                    declared,
+                    format!("unexpected media type {actual}"),
+                ));
+            }
+        }
+        self.body(domain, msg, &mut out);
+
+        out
+    }
+
+    /// Validates a response with its status.
+    pub fn validate_response(&self, status: u16, msg: &Message) -> Vec<Violation> {
+        let mut out = Vec::new();
+
+        let contents = self.responses(status);
+        if contents.is_empty() {
+            let declared: Vec<_> = self
+                .transfer
+                .ranges
+                .keys()
+                .map(|(s, _)| status_label(*s))
+                .collect();
+            out.push(Violation::new(
+                "/status",
+                declared.join(", "),
+                format!("undeclared status {status}"),
+            ));
+            return out;
+        }
+
+        let content = match msg.media() {
+            None => contents[0],
+            Some(actual) => match contents
+                .iter()
+                .find(|c| media_matches(c.media.as_deref(), actual))
+            {
+                Some(c) => c,
+                None => {
+                    let declared: Vec<_> = contents
+                        .iter()
+                        .map(|c| c.media.as_deref().unwrap_or(DEFAULT_MEDIA))
+                        .collect();
+                    out.push(Violation::new(
+                        "/header/content-type",
+                        declared.join(", "),
+                        format!("unexpected media type {actual}"),
+                    ));
+                    return out;
+                }
+            },
+        };
+
+        self.headers(content, msg, &mut out);
+        self.body(content, msg, &mut out);
+
+        out
+    }
+
+    fn headers(&self, content: &spec::Content, msg: &Message, out: &mut Vec<Violation>) {
+        let headers = content.headers.as_ref();
+        for prop in headers.into_iter().flat_map(|o| o.props.iter()) {
+            let name = prop.name.as_ref();
+            let path = format!("/header/{name}");
+            match msg.header(name) {
+                Some(value) => self.validator.param(&path, &prop.schema, value, out),
+                None if prop.is_required_param() => out.push(Violation::new(
+                    path,
+                    describe(&prop.schema),
+                    "missing required header",
+                )),
+                None => {}
+            }
+        }
+    }
+
+    fn body(&self, content: &spec::Content, msg: &Message, out: &mut Vec<Violation>) {
+        match (content.schema.as_ref(), msg.body.as_ref()) {
+            (Some(schema), Some(body)) => self.validator.value("/body", schema, body, out),
+            (None, Some(_)) => out.push(Violation::new("/body", "no body", "unexpected body")),
+            _ => {}
+        }
+    }
+}
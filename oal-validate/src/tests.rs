@@ -0,0 +1,229 @@
+use crate::errors::Error;
+use crate::{Contract, Message, Violation};
+use oal_compiler::tests::eval;
+use oal_syntax::atom;
+use serde_json::json;
+
+fn violation(path: &str, expected: &str, reason: &str) -> Violation {
+    Violation::new(path, expected, reason)
+}
+
+fn body(value: serde_json::Value) -> Message {
+    Message {
+        body: Some(value),
+        ..Default::default()
+    }
+}
+
+const CODE: &str = r#"
+    let @named = { 'name! str `minLength: 3` };
+    let @item = @named & {
+      'id! int `minimum: 1`
+    , 'kind str `enum: [small, large]`
+    , 'meta {}
+    };
+    let @shape = { 'radius! num } | { 'side! num } | { 'width num };
+    let update = put : <@item>
+      -> <status=200, headers={ 'etag! str }, @item>
+      :: <status=4XX, media="application/problem+json", { 'title! str }>;
+    res /items/{ 'id int } on get -> @item, update;
+    res /items/first on get -> @item;
+    res /shapes ? { 'limit! int `maximum: 10` } on post : <@shape> -> <status=204>;
+"#;
+
+#[test]
+fn validate_operation_lookup() -> anyhow::Result<()> {
+    let spec = eval(CODE)?;
+    let contract = Contract::new(&spec);
+
+    let op = contract.operation("get", "/items/first")?;
+    assert_eq!(op.method, atom::Method::Get);
+    assert!(op.variables.is_empty());
+
+    let op = contract.operation("PUT", "/items/a%20b")?;
+    assert_eq!(op.method, atom::Method::Put);
+    assert_eq!(op.variables.len(), 1);
+    assert_eq!(op.variables[0].1, "a b");
+
+    assert!(matches!(
+        contract.operation("GET", "/other"),
+        Err(Error::NotFound { .. })
+    ));
+    let Err(Error::MethodNotAllowed { allowed, .. }) = contract.operation("DELETE", "/items/1")
+    else {
+        panic!("expected an error");
+    };
+    assert_eq!(allowed, [atom::Method::Get, atom::Method::Put]);
+
+    Ok(())
+}
+
+#[test]
+fn validate_request() -> anyhow::Result<()> {
+    let spec = eval(CODE)?;
+    let contract = Contract::new(&spec);
+
+    let op = contract.operation("PUT", "/items/x")?;
+    let msg = body(
+        json!({ "id": 0, "name": "ab", "kind": "medium", "meta": { "any": 1 }, "other": true }),
+    );
+    assert_eq!(
+        op.validate_request(&[], &msg),
+        [
+            violation("/path/id", "int", "expected an integer"),
+            violation("/body/name", "str", "expected at least 3 characters"),
+            violation("/body/id", "int", "expected at least 1"),
+            violation("/body/kind", "str", "expected one of small, large"),
+            violation(
+                "/body/other",
+                "@named & { 'id, 'kind, 'meta }",
+                "unknown property"
+            ),
+        ]
+    );
+
+    let msg = body(json!({ "id": 1 }));
+    assert_eq!(
+        op.validate_request(&[], &msg),
+        [
+            violation("/path/id", "int", "expected an integer"),
+            violation("/body/name", "str", "missing required property"),
+        ]
+    );
+
+    let op = contract.operation("POST", "/shapes")?;
+    let query = [("limit".to_owned(), "11".to_owned())];
+    let msg = body(json!({ "radius": 1, "side": 2 }));
+    let shape = "{ 'radius } | { 'side } | { 'width }";
+    assert_eq!(
+        op.validate_request(&query, &msg),
+        [
+            violation("/query/limit", "int", "expected at most 10"),
+            violation("/body", shape, "expected one of the alternatives"),
+        ]
+    );
+
+    // Without the check for unknown fields, the payload matches several alternatives.
+    let contract = Contract::new(&spec).allow_unknown_fields();
+    let op = contract.operation("POST", "/shapes")?;
+    assert_eq!(
+        op.validate_request(&[], &msg),
+        [
+            violation("/query/limit", "int", "missing required parameter"),
+            violation("/body", shape, "matches 3 alternatives, expected one"),
+        ]
+    );
+
+    Ok(())
+}
+
+#[test]
+fn validate_response() -> anyhow::Result<()> {
+    let spec = eval(CODE)?;
+    let contract = Contract::new(&spec);
+    let op = contract.operation("PUT", "/items/1")?;
+
+    let mut msg = body(json!({ "id": 1, "name": "abc" }));
+    assert_eq!(
+        op.validate_response(200, &msg),
+        [violation("/header/etag", "str", "missing required header")]
+    );
+    msg.headers.push(("ETag".to_owned(), "x".to_owned()));
+    assert!(op.validate_response(200, &msg).is_empty());
+
+    assert_eq!(
+        op.validate_response(500, &msg),
+        [violation("/status", "200, 4XX", "undeclared status 500")]
+    );
+
+    let mut msg = body(json!({ "title": "Not found" }));
+    msg.media = Some("application/json".to_owned());
+    assert_eq!(
+        op.validate_response(404, &msg),
+        [violation(
+            "/header/content-type",
+            "application/problem+json",
+            "unexpected media type application/json"
+        )]
+    );
+    msg.media = None;
+    msg.headers.push((
+        "Content-Type".to_owned(),
+        "application/problem+json; charset=utf-8".to_owned(),
+    ));
+    assert!(op.validate_response(404, &msg).is_empty());
+
+    let op = contract.operation("POST", "/shapes")?;
+    assert_eq!(
+        op.validate_response(204, &body(json!({}))),
+        [violation("/body", "no body", "unexpected body")]
+    );
+
+    Ok(())
+}
+
+#[test]
+fn validate_intersection_of_alternatives() -> anyhow::Result<()> {
+    let spec = eval(
+        r#"
+        res /pets on post : <{ 'a! str } & ({ 'b! str } | { 'c! str })> -> <status=204>;
+    "#,
+    )?;
+    let contract = Contract::new(&spec);
+    let op = contract.operation("POST", "/pets")?;
+
+    assert!(op
+        .validate_request(&[], &body(json!({ "a": "x", "b": "y" })))
+        .is_empty());
+    assert!(op
+        .validate_request(&[], &body(json!({ "a": "x", "c": "z" })))
+        .is_empty());
+
+    let schema = "{ 'a } & ({ 'b } | { 'c })";
+    assert_eq!(
+        op.validate_request(&[], &body(json!({ "a": "x", "b": "y", "d": 1 }))),
+        [violation("/body/d", schema, "unknown property")]
+    );
+    assert_eq!(
+        op.validate_request(&[], &body(json!({ "a": "x", "b": "y", "c": "z" }))),
+        [violation(
+            "/body",
+            "{ 'b } | { 'c }",
+            "matches 2 alternatives, expected one"
+        )]
+    );
+
+    Ok(())
+}
+
+#[test]
+fn validate_numbers() -> anyhow::Result<()> {
+    let spec = eval(
+        r#"
+        res /numbers on post : <{
+          'price num `multipleOf: 0.1`
+        , 'ratio num `multipleOf: 0`
+        , 'count int `multipleOf: 2`
+        , 'big int
+        }> -> <status=204>;
+    "#,
+    )?;
+    let contract = Contract::new(&spec);
+    let op = contract.operation("POST", "/numbers")?;
+
+    let msg =
+        body(json!({ "price": 0.3, "ratio": 1.5, "count": 4.0, "big": 18446744073709551615u64 }));
+    assert!(op.validate_request(&[], &msg).is_empty());
+
+    let msg = body(json!({ "price": 0.35, "count": 3, "big": 1.5 }));
+    assert_eq!(
+        op.validate_request(&[], &msg),
+        [
+            violation("/body/price", "num", "expected a multiple of 0.1"),
+            violation("/body/count", "int", "expected a multiple of 2"),
+            violation("/body/big", "int", "expected an integer"),
+        ]
+    );
+
+    Ok(())
+}
@@ -0,0 +1,360 @@
+use oal_compiler::spec::{self, SchemaExpr};
+use oal_syntax::atom;
+use serde::Serialize;
+use serde_json::Value;
+use std::fmt::{Display, Formatter};
+
+/// The relative tolerance when checking that a number is a multiple of another.
+const MULTIPLE_TOLERANCE: f64 = 1e-9;
+
+/// A part of a payload which does not conform to the specification.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct Violation {
+    /// The location of the offending value, e.g. `/body/items/0/id`.
+    pub path: String,
+    /// The expected schema, in the notation of the language, e.g. `[@item]`.
+    pub expected: String,
+    pub reason: String,
+}
+
+impl Violation {
+    pub fn new<P, E, R>(path: P, expected: E, reason: R) -> Self
+    where
+        P: Into<String>,
+        E: Into<String>,
+        R: Into<String>,
+    {
+        Violation {
+            path: path.into(),
+            expected: expected.into(),
+            reason: reason.into(),
+        }
+    }
+}
+
+impl Display for Violation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}: {} (schema {})",
+            self.path, self.reason, self.expected
+        )
+    }
+}
+
+/// Describes a schema in the notation of the language, without expanding references.
+pub fn describe(s: &spec::Schema) -> String {
+    match &s.expr {
+        SchemaExpr::Num(_) => "num".to_owned(),
+        SchemaExpr::Int(_) => "int".to_owned(),
+        SchemaExpr::Bool(_) => "bool".to_owned(),
+        SchemaExpr::Str(_) => "str".to_owned(),
+        SchemaExpr::Uri(_) | SchemaExpr::Rel(_) => "uri".to_owned(),
+        SchemaExpr::Array(a) => format!("[{}]", describe(&a.item)),
+        SchemaExpr::Object(o) if o.props.is_empty() => "{}".to_owned(),
+        SchemaExpr::Object(o) => {
+            let names: Vec<_> = o.props.iter().map(|p| format!("'{}", p.name)).collect();
+            format!("{{ {} }}", names.join(", "))
+        }
+        SchemaExpr::Op(op) => {
+            let sep = match op.op {
+                atom::VariadicOperator::Join => " & ",
+                atom::VariadicOperator::Any => " ~ ",
+                atom::VariadicOperator::Sum => " | ",
+                atom::VariadicOperator::Range => " :: ",
+            };
+            let operands: Vec<_> = op
+                .schemas
+                .iter()
+                .map(|s| match s.expr {
+                    SchemaExpr::Op(_) => format!("({})", describe(s)),
+                    _ => describe(s),
+                })
+                .collect();
+            operands.join(sep)
+        }
+        SchemaExpr::Ref(r) => r.to_string(),
+    }
+}
+
+/// Validates values against the schemas of a specification.
+#[derive(Clone, Copy)]
+pub struct Validator<'a> {
+    refs: &'a spec::References,
+    allow_unknown: bool,
+}
+
+impl<'a> Validator<'a> {
+    pub fn new(refs: &'a spec::References) -> Self {
+        Validator {
+            refs,
+            allow_unknown: false,
+        }
+    }
+
+    /// Accepts properties not declared by object schemas, which are reported by default.
+    ///
+    /// Objects without any declared property always accept any property.
+    pub fn allow_unknown_fields(mut self) -> Self {
+        self.allow_unknown = true;
+        self
+    }
+
+    /// Checks whether a value conforms to a schema.
+    pub fn accepts(&self, s: &spec::Schema, v: &Value) -> bool {
+        self.matches(s, v, true)
+    }
+
+    fn matches(&self, s: &spec::Schema, v: &Value, closed: bool) -> bool {
+        let mut violations = Vec::new();
+        self.check("", s, v, closed, &mut violations);
+        violations.is_empty()
+    }
+
+    /// Validates a value against a schema.
+    pub fn value(&self, path: &str, s: &spec::Schema, v: &Value, out: &mut Vec<Violation>) {
+        self.check(path, s, v, true, out)
+    }
+
+    /// Validates a value against a schema, where `closed` tells whether unknown properties are reported,
+    /// i.e. whether the schema is not an operand of an intersection.
+    fn check(
+        &self,
+        path: &str,
+        s: &spec::Schema,
+        v: &Value,
+        closed: bool,
+        out: &mut Vec<Violation>,
+    ) {
+        let violation = |out: &mut Vec<Violation>, reason: String| {
+            out.push(Violation::new(path, describe(s), reason));
+        };
+        match &s.expr {
+            SchemaExpr::Num(p) => match v.as_f64() {
+                Some(n) => {
+                    bounds(n, p.minimum, p.maximum, |r| violation(out, r));
+                    if let Some(m) = p.multiple_of.filter(|m| *m != 0.0) {
+                        if !is_multiple(n, m) {
+                            violation(out, format!("expected a multiple of {m}"));
+                        }
+                    }
+                }
+                None => violation(out, "expected a number".to_owned()),
+            },
+            SchemaExpr::Int(p) => match integer(v) {
+                Some(n) => {
+                    let min = p.minimum.map(i128::from);
+                    let max = p.maximum.map(i128::from);
+                    bounds(n, min, max, |r| violation(out, r));
+                    if let Some(m) = p.multiple_of.filter(|m| *m != 0) {
+                        if n % i128::from(m) != 0 {
+                            violation(out, format!("expected a multiple of {m}"));
+                        }
+                    }
+                }
+                None => violation(out, "expected an integer".to_owned()),
+            },
+            SchemaExpr::Bool(_) => {
+                if !v.is_boolean() {
+                    violation(out, "expected a boolean".to_owned());
+                }
+            }
+            SchemaExpr::Str(p) => match v.as_str() {
+                Some(text) => string(p, text, |r| violation(out, r)),
+                None => violation(out, "expected a string".to_owned()),
+            },
+            SchemaExpr::Uri(_) | SchemaExpr::Rel(_) => {
+                if !v.is_string() {
+                    violation(out, "expected a URI".to_owned());
+                }
+            }
+            SchemaExpr::Array(a) => match v.as_array() {
+                Some(items) => {
+                    for (i, item) in items.iter().enumerate() {
+                        self.check(&format!("{path}/{i}"), &a.item, item, true, out);
+                    }
+                }
+                None => violation(out, "expected an array".to_owned()),
+            },
+            SchemaExpr::Object(o) => match v.as_object() {
+                Some(map) => {
+                    for p in o.props.iter() {
+                        let name = p.name.as_ref();
+                        let path = format!("{path}/{}", pointer_escape(name));
+                        match map.get(name) {
+                            Some(v) => self.check(&path, &p.schema, v, true, out),
+                            None if p.is_required() => out.push(Violation::new(
+                                path,
+                                describe(&p.schema),
+                                "missing required property",
+                            )),
+                            None => {}
+                        }
+                    }
+                    if closed {
+                        self.unknown(path, s, v, out);
+                    }
+                }
+                None => violation(out, "expected an object".to_owned()),
+            },
+            SchemaExpr::Op(op) => match op.op {
+                atom::VariadicOperator::Join => {
+                    for s in op.schemas.iter() {
+                        self.check(path, s, v, false, out);
+                    }
+                    if closed {
+                        self.unknown(path, s, v, out);
+                    }
+                }
+                _ => {
+                    let matches = op
+                        .schemas
+                        .iter()
+                        .filter(|s| self.matches(s, v, closed))
+                        .count();
+                    if matches == 0 {
+                        violation(out, "expected one of the alternatives".to_owned());
+                    } else if matches > 1 && op.op == atom::VariadicOperator::Sum {
+                        violation(out, format!("matches {matches} alternatives, expected one"));
+                    }
+                }
+            },
+            SchemaExpr::Ref(r) => {
+                if let Some(spec::Reference::Schema(s)) = self.refs.get(r) {
+                    self.check(path, s, v, closed, out);
+                }
+            }
+        }
+    }
+
+    /// Reports the properties of an object which are not declared by its schema.
+    fn unknown(&self, path: &str, s: &spec::Schema, v: &Value, out: &mut Vec<Violation>) {
+        if self.allow_unknown {
+            return;
+        }
+        let (Some(map), Some(names)) = (v.as_object(), self.properties(s, v)) else {
+            return;
+        };
+        for name in map.keys().filter(|k| !names.contains(&k.as_str())) {
+            let path = format!("{path}/{}", pointer_escape(name));
+            out.push(Violation::new(path, describe(s), "unknown property"));
+        }
+    }
+
+    /// Returns the names of the properties declared for a value by an object schema or
+    /// a combination of them, or `None` if the schema accepts any property.
+    ///
+    /// Alternatives only declare properties if they match the value,
+    /// or if none of them does.
+    fn properties<'b>(&'b self, s: &'b spec::Schema, v: &Value) -> Option<Vec<&'b str>> {
+        match &s.expr {
+            SchemaExpr::Object(o) if !o.props.is_empty() => {
+                Some(o.props.iter().map(|p| p.name.as_ref()).collect())
+            }
+            SchemaExpr::Op(op) => {
+                let schemas: Vec<_> = match op.op {
+                    atom::VariadicOperator::Join => op.schemas.iter().collect(),
+                    _ => {
+                        let matching: Vec<_> = op
+                            .schemas
+                            .iter()
+                            .filter(|s| self.matches(s, v, false))
+                            .collect();
+                        if matching.is_empty() {
+                            op.schemas.iter().collect()
+                        } else {
+                            matching
+                        }
+                    }
+                };
+                let mut names = Vec::new();
+                for s in schemas {
+                    names.append(&mut self.properties(s, v)?);
+                }
+                Some(names)
+            }
+            SchemaExpr::Ref(_) => self.resolve(s).and_then(|s| self.properties(s, v)),
+            _ => None,
+        }
+    }
+
+    /// Validates a parameter or header, converting its raw value after the schema.
+    pub fn param(&self, path: &str, s: &spec::Schema, raw: &str, out: &mut Vec<Violation>) {
+        let v = match self.resolve(s).map(|s| &s.expr) {
+            Some(SchemaExpr::Num(_) | SchemaExpr::Int(_) | SchemaExpr::Bool(_)) => {
+                serde_json::from_str(raw).unwrap_or_else(|_| Value::String(raw.to_owned()))
+            }
+            _ => Value::String(raw.to_owned()),
+        };
+        self.value(path, s, &v, out);
+    }
+
+    /// Follows references to the schema they denote.
+    fn resolve<'b>(&'b self, s: &'b spec::Schema) -> Option<&'b spec::Schema> {
+        match &s.expr {
+            SchemaExpr::Ref(r) => match self.refs.get(r) {
+                Some(spec::Reference::Schema(s)) => self.resolve(s),
+                _ => None,
+            },
+            _ => Some(s),
+        }
+    }
+}
+
+/// Checks whether a number is a multiple of another non-zero number,
+/// up to the rounding errors of floating-point divisions, e.g. `0.3 / 0.1`.
+fn is_multiple(n: f64, m: f64) -> bool {
+    let q = n / m;
+    (q - q.round()).abs() <= MULTIPLE_TOLERANCE * q.abs().max(1.0)
+}
+
+/// Returns the value of an integer, including numbers without fractional part, e.g. `1.0`.
+fn integer(v: &Value) -> Option<i128> {
+    v.as_i64()
+        .map(i128::from)
+        .or_else(|| v.as_u64().map(i128::from))
+        .or_else(|| {
+            v.as_f64()
+                .filter(|f| f.is_finite() && f.fract() == 0.0)
+                .map(|f| f as i128)
+        })
+}
+
+fn bounds<T, F>(n: T, min: Option<T>, max: Option<T>, mut report: F)
+where
+    T: PartialOrd + Display,
+    F: FnMut(String),
+{
+    if let Some(min) = min.filter(|min| n < *min) {
+        report(format!("expected at least {min}"));
+    }
+    if let Some(max) = max.filter(|max| n > *max) {
+        report(format!("expected at most {max}"));
+    }
+}
+
+fn string<F: FnMut(String)>(p: &spec::PrimString, text: &str, mut report: F) {
+    if !p.enumeration.is_empty() && !p.enumeration.iter().any(|e| e == text) {
+        report(format!("expected one of {}", p.enumeration.join(", ")));
+    }
+    let length = text.chars().count();
+    if let Some(n) = p.min_length.filter(|n| length < *n) {
+        report(format!("expected at least {n} characters"));
+    }
+    if let Some(n) = p.max_length.filter(|n| length > *n) {
+        report(format!("expected at most {n} characters"));
+    }
+    if let Some(pattern) = &p.pattern {
+        // Invalid patterns are not the fault of the payload and are ignored.
+        if let Ok(re) = regex::Regex::new(pattern) {
+            if !re.is_match(text) {
+                report(format!("expected to match {pattern}"));
+            }
+        }
+    }
+}
+
+/// Escapes a property name as a JSON pointer segment.
+fn pointer_escape(name: &str) -> String {
+    name.replace('~', "~0").replace('/', "~1")
+}
@@ -51,28 +51,25 @@ impl<'a> Loader<anyhow::Error> for WebLoader<'a> {
     }
 
     fn parse(&mut self, loc: Locator, input: String) -> anyhow::Result<Tree> {
-        let (tree, mut errs) = oal_syntax::parse(loc.clone(), &input);
-        if let Some(err) = errs.pop() {
-            let span = match err {
-                oal_syntax::errors::Error::Grammar(ref err) => err.span(),
-                oal_syntax::errors::Error::Lexicon(ref err) => err.span(),
-                _ => Span::new(loc, 0..0),
-            };
-            let err = report(&input, span, err).unwrap_or(INTERNAL_ERRROR.to_owned());
-            Err(anyhow!(err))
+        let (tree, errs) = oal_syntax::parse(loc.clone(), &input);
+        if !errs.is_empty() {
+            let reports = errs.iter().map(|err| {
+                let span = match err {
+                    oal_syntax::errors::Error::Grammar(ref err) => err.span(),
+                    oal_syntax::errors::Error::Lexicon(ref err) => err.span(),
+                    _ => Span::new(loc.clone(), 0..0),
+                };
+                report(&input, span, err).unwrap_or(INTERNAL_ERRROR.to_owned())
+            });
+            Err(anyhow!(reports.collect::<String>()))
         } else {
             Ok(tree.unwrap())
         }
     }
 
     fn compile(&mut self, mods: &ModuleSet, loc: &Locator) -> anyhow::Result<()> {
-        if let Err(err) = oal_compiler::compile::compile(mods, loc) {
-            let span = match err.span() {
-                Some(s) => s.clone(),
-                None => Span::new(loc.clone(), 0..0),
-            };
-            let err = report(self.0, span, err).unwrap_or(INTERNAL_ERRROR.to_owned());
-            Err(anyhow!(err))
+        if let Err(errs) = oal_compiler::compile::compile(mods, loc) {
+            Err(anyhow!(report_all(self.0, loc, &errs)))
         } else {
             Ok(())
         }
@@ -84,15 +81,26 @@ fn process(input: &str) -> anyhow::Result<String> {
     let loader = &mut WebLoader(input);
     let main = Locator::try_from(INPUT).unwrap();
     let mods = oal_compiler::module::load(loader, &main)?;
-    let spec = oal_compiler::eval::eval(&mods)?;
+    let spec = oal_compiler::eval::eval(&mods)
+        .map_err(|errs| anyhow!(report_all(input, &main, &errs)))?;
     let builder = oal_openapi::Builder::new(spec);
-    let api = builder.into_openapi();
+    let api = builder.into_openapi()?;
     let api_yaml = serde_yaml::to_string(&api)?;
     Ok(api_yaml)
 }
 
 /// Generates an error report.
 fn report<M: ToString>(input: &str, span: Span, msg: M) -> anyhow::Result<String> {
+    report_with(input, span, msg, &[])
+}
+
+/// Generates an error report along with secondary labelled spans.
+fn report_with<M: ToString>(
+    input: &str,
+    span: Span,
+    msg: M,
+    labels: &[(Span, String)],
+) -> anyhow::Result<String> {
     let mut builder = Report::build(ReportKind::Error, INPUT, span.start())
         .with_config(Config::default().with_color(false))
         .with_message(msg);
@@ -100,6 +108,10 @@ fn report<M: ToString>(input: &str, span: Span, msg: M) -> anyhow::Result<String
         let s = CharSpan::from(input, span);
         builder.add_label(Label::new(s))
     }
+    for (span, text) in labels {
+        let s = CharSpan::from(input, span.clone());
+        builder.add_label(Label::new(s).with_message(text))
+    }
     let mut buf = Vec::new();
     builder
         .finish()
@@ -108,6 +120,19 @@ fn report<M: ToString>(input: &str, span: Span, msg: M) -> anyhow::Result<String
     Ok(out)
 }
 
+/// Generates the concatenated reports for a collection of compiler errors.
+fn report_all(input: &str, loc: &Locator, errs: &oal_compiler::errors::Errors) -> String {
+    errs.iter()
+        .map(|err| {
+            let span = match err.span() {
+                Some(s) => s.clone(),
+                None => Span::new(loc.clone(), 0..0),
+            };
+            report_with(input, span, err, err.labels()).unwrap_or(INTERNAL_ERRROR.to_owned())
+        })
+        .collect()
+}
+
 /// A span of Unicode code points within the unique source.
 struct CharSpan(oal_model::span::CharSpan);
 